@@ -1,18 +1,20 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde_json::Value;
 
 use crate::{
+    enums::int_enum,
+    form::encode,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
+    response::{decode, decode_body},
+    torrents::api_version_at_least,
     Client, Error,
 };
 
 /// All Application API methods are under "app", e.g.: /api/v2/app/methodName
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct BuildInfo {
     /// QT version
     pub qt: String,
@@ -26,7 +28,41 @@ pub struct BuildInfo {
     pub bitness: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    /// Interface name shown to the user
+    pub name: String,
+    /// Interface id to use in current_network_interface and get_network_interface_addresses
+    pub value: String,
+}
+
+/// Entries listed by get_directory_content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryMode {
+    /// Directories and files
+    All,
+    /// Only directories
+    Dirs,
+    /// Only files
+    Files,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryEntryKind {
+    Dir,
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    /// Absolute path of the entry on the server
+    pub path: String,
+    /// Directory or file, None when listed with DirectoryMode::All
+    pub kind: Option<DirectoryEntryKind>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Preferences {
     /// Currently selected language (e.g. en_GB for English)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -247,6 +283,18 @@ pub struct Preferences {
     /// True if proxy is only used for torrents
     #[serde(skip_serializing_if = "Option::is_none")]
     pub proxy_torrents_only: Option<bool>,
+    /// True if host names are resolved through the proxy (qBittorrent 4.6+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_hostname_lookup: Option<bool>,
+    /// True if the proxy is used for BitTorrent connections (qBittorrent 4.6+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_bittorrent: Option<bool>,
+    /// True if the proxy is used for RSS feeds (qBittorrent 4.6+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_rss: Option<bool>,
+    /// True if the proxy is used for general purposes, e.g. search and updates (qBittorrent 4.6+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_misc: Option<bool>,
     /// True if external IP filter should be enabled
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ip_filter_enabled: Option<bool>,
@@ -468,98 +516,291 @@ pub struct Preferences {
     pub utp_tcp_mixed_mode: Option<UtpTcpMixedMode>,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
+impl Preferences {
+    /// True if the proxy type or the proxy fields of qBittorrent 4.6+ are set
+    fn has_proxy_fields(&self) -> bool {
+        self.proxy_type.is_some()
+            || self.proxy_hostname_lookup.is_some()
+            || self.proxy_bittorrent.is_some()
+            || self.proxy_rss.is_some()
+            || self.proxy_misc.is_some()
+    }
+
+    /// JSON sent by set_preferences to a server of the given Web API version (as returned by
+    /// get_api_version)
+    ///
+    /// Versions before v2.9 get proxy_type as an integer and don't get the proxy fields added
+    /// in qBittorrent 4.6. Newer versions get proxy_type as a string, with proxy_auth_enabled
+    /// set for the legacy types with authentication.
+    pub fn to_json_for_api_version(&self, version: &str) -> Result<Value, Error> {
+        let mut json = serde_json::to_value(self)?;
+        let Some(fields) = json.as_object_mut() else {
+            return Ok(json);
+        };
+        if api_version_at_least(version, &[2, 9]) {
+            if self.proxy_auth_enabled.is_none()
+                && self
+                    .proxy_type
+                    .as_ref()
+                    .is_some_and(ProxyType::requires_auth)
+            {
+                fields.insert("proxy_auth_enabled".to_string(), Value::Bool(true));
+            }
+            return Ok(json);
+        }
+        for field in [
+            "proxy_hostname_lookup",
+            "proxy_bittorrent",
+            "proxy_rss",
+            "proxy_misc",
+        ] {
+            if fields.remove(field).is_some() {
+                ::log::debug!("{field} is not supported by Web API {version}");
+            }
+        }
+        if let Some(proxy_type) = &self.proxy_type {
+            let auth = self.proxy_auth_enabled.unwrap_or_default();
+            match proxy_type.legacy_value(auth) {
+                Some(value) => {
+                    fields.insert("proxy_type".to_string(), Value::from(value));
+                }
+                None => {
+                    ::log::debug!(
+                        "proxy_type {proxy_type:?} is not supported by Web API {version}"
+                    );
+                    fields.remove("proxy_type");
+                }
+            }
+        }
+        Ok(json)
+    }
+}
+
+/// Where the torrents of a watched folder are downloaded to, a value of scan_dirs
+///
+/// Sent as 0 for the monitored folder, 1 for the default save path, or as the path itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ScanDir {
     /// Download to the monitored folder
-    Monitored = 0,
+    MonitoredFolder,
     /// Download to the default save path
-    Default = 1,
+    DefaultPath,
+    /// Download to this path
+    CustomPath(String),
+    /// Integer value not known by this crate
+    Unknown(i64),
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum SchedulerDays {
-    EveryDay = 0,
-    EveryWeekday = 1,
-    EveryWeekend = 2,
-    EveryMonday = 3,
-    EveryTuesday = 4,
-    EveryWednesday = 5,
-    EveryThursday = 6,
-    EveryFriday = 7,
-    EverySaturday = 8,
-    EverySunday = 9,
+impl Serialize for ScanDir {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            ScanDir::MonitoredFolder => serializer.serialize_i64(0),
+            ScanDir::DefaultPath => serializer.serialize_i64(1),
+            ScanDir::CustomPath(path) => serializer.serialize_str(path),
+            ScanDir::Unknown(value) => serializer.serialize_i64(*value),
+        }
+    }
 }
 
-///     NB: the first options allows you to use both encrypted and unencrypted connections (this is the default); other options are mutually exclusive: e.g. by forcing encryption on you won't be able to use unencrypted connections and vice versa.
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum Encryption {
-    PreferEncryption = 0,
-    ForceEncryptionOn = 1,
-    ForceEncryptionOff = 2,
+impl<'de> Deserialize<'de> for ScanDir {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Encoded {
+            Value(i64),
+            Path(String),
+        }
+
+        Ok(match Encoded::deserialize(deserializer)? {
+            Encoded::Value(0) => ScanDir::MonitoredFolder,
+            Encoded::Value(1) => ScanDir::DefaultPath,
+            Encoded::Value(value) => ScanDir::Unknown(value),
+            Encoded::Path(path) => ScanDir::CustomPath(path),
+        })
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(i8)]
+int_enum! {
+    pub enum SchedulerDays {
+        EveryDay = 0,
+        EveryWeekday = 1,
+        EveryWeekend = 2,
+        EveryMonday = 3,
+        EveryTuesday = 4,
+        EveryWednesday = 5,
+        EveryThursday = 6,
+        EveryFriday = 7,
+        EverySaturday = 8,
+        EverySunday = 9,
+    }
+}
+
+int_enum! {
+    ///     NB: the first options allows you to use both encrypted and unencrypted connections (this is the default); other options are mutually exclusive: e.g. by forcing encryption on you won't be able to use unencrypted connections and vice versa.
+    pub enum Encryption {
+        PreferEncryption = 0,
+        ForceEncryptionOn = 1,
+        ForceEncryptionOff = 2,
+    }
+}
+
+/// Proxy type, a string since Web API v2.9 (qBittorrent 4.6) and an integer before
+///
+/// Both encodings are read. Values are sent as strings, or as integers to older servers, see
+/// Preferences::to_json_for_api_version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProxyType {
     /// Proxy is disabled
-    Disabled = 0,
-    /// HTTP proxy without authentication
-    HttpNoAuth = 1,
-    /// SOCKS5 proxy without authentication
-    Socks5NoAuth = 2,
-    /// HTTP proxy with authentication
-    HttpAuth = 3,
-    /// SOCKS5 proxy with authentication
-    Socks5Auth = 4,
+    Disabled,
+    /// HTTP proxy, authenticated if proxy_auth_enabled
+    Http,
+    /// SOCKS5 proxy, authenticated if proxy_auth_enabled
+    Socks5,
     /// SOCKS4 proxy without authentication
-    Socks4NoAuth = 5,
+    Socks4,
+    /// HTTP proxy with authentication (integer value of older servers)
+    HttpAuth,
+    /// SOCKS5 proxy with authentication (integer value of older servers)
+    Socks5Auth,
+    /// Value not known by this crate, integers as decimal strings
+    Other(String),
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum DyndnsService {
-    DyDNS = 0,
-    NOIP = 1,
+impl ProxyType {
+    /// String value of qBittorrent 4.6+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProxyType::Disabled => "None",
+            ProxyType::Http | ProxyType::HttpAuth => "HTTP",
+            ProxyType::Socks5 | ProxyType::Socks5Auth => "SOCKS5",
+            ProxyType::Socks4 => "SOCKS4",
+            ProxyType::Other(value) => value,
+        }
+    }
+
+    /// Integer value of servers before qBittorrent 4.6, None for unknown values
+    pub fn legacy_value(&self, auth: bool) -> Option<i64> {
+        match self {
+            ProxyType::Disabled => Some(0),
+            ProxyType::Http if auth => Some(3),
+            ProxyType::Http => Some(1),
+            ProxyType::Socks5 if auth => Some(4),
+            ProxyType::Socks5 => Some(2),
+            ProxyType::HttpAuth => Some(3),
+            ProxyType::Socks5Auth => Some(4),
+            ProxyType::Socks4 => Some(5),
+            ProxyType::Other(value) => value.parse().ok(),
+        }
+    }
+
+    /// True for the legacy values with authentication
+    fn requires_auth(&self) -> bool {
+        matches!(self, ProxyType::HttpAuth | ProxyType::Socks5Auth)
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum MaxRatioAct {
-    Pause = 0,
-    Remove = 1,
+impl From<i64> for ProxyType {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => ProxyType::Disabled,
+            1 => ProxyType::Http,
+            2 => ProxyType::Socks5,
+            3 => ProxyType::HttpAuth,
+            4 => ProxyType::Socks5Auth,
+            5 => ProxyType::Socks4,
+            value => ProxyType::Other(value.to_string()),
+        }
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum BittorrentProtocol {
-    Both = 0,
-    TCP = 1,
-    UTP = 2,
+impl From<&str> for ProxyType {
+    fn from(value: &str) -> Self {
+        match value {
+            "None" => ProxyType::Disabled,
+            "HTTP" => ProxyType::Http,
+            "SOCKS5" => ProxyType::Socks5,
+            "SOCKS4" => ProxyType::Socks4,
+            value => ProxyType::Other(value.to_string()),
+        }
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum UploadChokingAlgorithm {
-    RoundRobin = 0,
-    FastestUpload = 1,
-    AntiLeech = 2,
+impl Serialize for ProxyType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum UploadSlotsBehavior {
-    FixedSlots = 0,
-    UploadRateBased = 1,
+impl<'de> Deserialize<'de> for ProxyType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Encoded {
+            Legacy(i64),
+            Name(String),
+        }
+
+        Ok(match Encoded::deserialize(deserializer)? {
+            Encoded::Legacy(value) => ProxyType::from(value),
+            Encoded::Name(value) => ProxyType::from(value.as_str()),
+        })
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum UtpTcpMixedMode {
-    PreferTCP = 0,
-    PeerProportional = 1,
+int_enum! {
+    pub enum DyndnsService {
+        DyDNS = 0,
+        NOIP = 1,
+    }
+}
+
+int_enum! {
+    pub enum MaxRatioAct {
+        Pause = 0,
+        Remove = 1,
+    }
+}
+
+int_enum! {
+    pub enum BittorrentProtocol {
+        Both = 0,
+        TCP = 1,
+        UTP = 2,
+    }
+}
+
+int_enum! {
+    pub enum UploadChokingAlgorithm {
+        RoundRobin = 0,
+        FastestUpload = 1,
+        AntiLeech = 2,
+    }
+}
+
+int_enum! {
+    pub enum UploadSlotsBehavior {
+        FixedSlots = 0,
+        UploadRateBased = 1,
+    }
+}
+
+int_enum! {
+    pub enum UtpTcpMixedMode {
+        PreferTCP = 0,
+        PeerProportional = 1,
+    }
 }
 
 impl Client {
@@ -584,8 +825,9 @@ impl Client {
             method: Method::Version,
             arguments: None,
         };
-        let response = self.send_request(&request).await?;
-        check_default_status(&response, String::from_utf8(response.body().to_vec())?)
+        Ok(String::from_utf8(
+            self.cached_body(&request).await?.to_vec(),
+        )?)
     }
 
     /// Get API version
@@ -609,8 +851,9 @@ impl Client {
             method: Method::WebapiVersion,
             arguments: None,
         };
-        let response = self.send_request(&request).await?;
-        check_default_status(&response, String::from_utf8(response.body().to_vec())?)
+        Ok(String::from_utf8(
+            self.cached_body(&request).await?.to_vec(),
+        )?)
     }
 
     /// Get build info
@@ -634,11 +877,8 @@ impl Client {
             method: Method::BuildInfo,
             arguments: None,
         };
-        let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        let body = self.cached_body(&request).await?;
+        decode_body(request.method, &body)
     }
 
     /// Shutdown application
@@ -661,7 +901,7 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        self.check_default_status(&response, ())
     }
 
     /// Get application preferences
@@ -681,15 +921,25 @@ impl Client {
     /// The response is a JSON object with several fields (key-value) pairs representing the application's settings. The contents may vary depending on which settings are present in qBittorrent.ini.
     ///
     pub async fn get_preferences(&mut self) -> Result<Preferences, Error> {
+        let request = ApiRequest {
+            method: Method::Preferences,
+            arguments: None,
+        };
+        let body = self.cached_body(&request).await?;
+        decode_body(request.method, &body)
+    }
+
+    /// Get application preferences from the server, bypassing the response cache
+    ///
+    /// For read-modify-write updates of a preference, where writing back a cached value could
+    /// drop the changes made since.
+    pub(crate) async fn get_preferences_uncached(&mut self) -> Result<Preferences, Error> {
         let request = ApiRequest {
             method: Method::Preferences,
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
     }
 
     /// Set application preferences
@@ -718,14 +968,23 @@ impl Client {
     ///
     /// For a list of possible preference options see Get application preferences
     ///
+    /// The proxy fields are adapted to the API version of the server, see
+    /// Preferences::to_json_for_api_version.
+    ///
     pub async fn set_preferences(&mut self, values: Preferences) -> Result<(), Error> {
-        let arguments = Arguments::Json(json!(values));
+        let json = if values.has_proxy_fields() {
+            let version = self.get_api_version().await?;
+            serde_json::to_string(&values.to_json_for_api_version(&version)?)?
+        } else {
+            serde_json::to_string(&values)?
+        };
+        let arguments = Arguments::Form(format!("json={}", encode(&json)));
         let request = ApiRequest {
             method: Method::SetPreferences,
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        self.check_default_status(&response, ())
     }
 
     /// Get default save path
@@ -749,7 +1008,139 @@ impl Client {
             method: Method::DefaultSavePath,
             arguments: None,
         };
+        Ok(String::from_utf8(
+            self.cached_body(&request).await?.to_vec(),
+        )?)
+    }
+
+    /// Get network interfaces
+    ///
+    /// Name: networkInterfaceList
+    ///
+    /// Parameters:
+    ///
+    /// None
+    ///
+    /// Returns:
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// Vec<NetworkInterface>
+    ///
+    /// The response is a JSON array of the network interfaces the application can bind to.
+    ///
+    pub async fn get_network_interfaces(&mut self) -> Result<Vec<NetworkInterface>, Error> {
+        let request = ApiRequest {
+            method: Method::NetworkInterfaceList,
+            arguments: None,
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, decode(request.method, &response)?)
+    }
+
+    /// Get network interface addresses
+    ///
+    /// Name: networkInterfaceAddressList
+    ///
+    /// Parameters:
+    /// Parameter Type Description
+    /// iface string Value of the network interface, empty for all interfaces
+    ///
+    /// Returns:
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// Vec<String>
+    ///
+    /// The response is a JSON array of the IP addresses of the interface, to use in
+    /// current_interface_address.
+    ///
+    pub async fn get_network_interface_addresses(
+        &mut self,
+        iface: &str,
+    ) -> Result<Vec<String>, Error> {
+        let request = ApiRequest {
+            method: Method::NetworkInterfaceAddressList,
+            arguments: Some(Arguments::Form(format!("iface={}", encode(iface)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, decode(request.method, &response)?)
+    }
+
+    /// Send test email
+    ///
+    /// Name: sendTestEmail
+    ///
+    /// Parameters:
+    ///
+    /// None
+    ///
+    /// Returns:
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// None
+    ///
+    /// Sends a test email with the mail notification settings of the preferences.
+    ///
+    pub async fn send_test_email(&mut self) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::SendTestEmail,
+            arguments: None,
+        };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, String::from_utf8(response.body().to_vec())?)
+        self.check_default_status(&response, ())
+    }
+
+    /// Get directory content
+    ///
+    /// Name: getDirectoryContent
+    ///
+    /// Parameters:
+    /// Parameter Type Description
+    /// dirPath string Absolute path of the directory on the server
+    /// mode string all (default), dirs or files
+    ///
+    /// Returns:
+    /// HTTP Status Code Scenario
+    /// 400 Directory path or mode is not valid
+    /// 401 Not authorized
+    /// 404 Directory does not exist
+    /// 200 All other scenarios
+    ///
+    /// Vec<DirectoryEntry>
+    ///
+    /// The response is a JSON array of the paths of the entries. The kind of the entries is only
+    /// known when listing DirectoryMode::Dirs or DirectoryMode::Files.
+    ///
+    pub async fn get_directory_content(
+        &mut self,
+        dir_path: &str,
+        mode: DirectoryMode,
+    ) -> Result<Vec<DirectoryEntry>, Error> {
+        let (mode, kind) = match mode {
+            DirectoryMode::All => ("all", None),
+            DirectoryMode::Dirs => ("dirs", Some(DirectoryEntryKind::Dir)),
+            DirectoryMode::Files => ("files", Some(DirectoryEntryKind::File)),
+        };
+        let request = ApiRequest {
+            method: Method::GetDirectoryContent,
+            arguments: Some(Arguments::Form(format!(
+                "dirPath={}&mode={mode}",
+                encode(dir_path)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        let paths: Vec<String> = match response.status_code().as_u16() {
+            200 => decode(request.method, &response)?,
+            400 => return Err(Error::InvalidDirectoryPath),
+            401 => return Err(Error::NotAuth),
+            404 => return Err(Error::NoDirectory),
+            _ => return Err(self.status_error(&response)),
+        };
+        Ok(paths
+            .into_iter()
+            .map(|path| DirectoryEntry { path, kind })
+            .collect())
     }
 }
@@ -1,8 +1,9 @@
 /// All Authentication API methods are under "auth", e.g.: /api/v2/auth/methodName.
 /// qBittorrent uses cookie-based authentication.
 use crate::{
+    client::Credentials,
+    form::encode,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
     Client, Error,
 };
 
@@ -36,22 +37,34 @@ impl Client {
     /// Note: Set Referer or Origin header to the exact same domain and port as used in the HTTP query Host header.
     ///
     pub async fn login(&mut self, username: &str, password: &str) -> Result<(), Error> {
-        let request = ApiRequest {
-            method: Method::Login,
-            arguments: Some(Arguments::Form(format!(
-                "username={username}&password={password}"
-            ))),
+        let response = self
+            .send_request(&login_request(username, password))
+            .await?;
+        login_status(response.status_code().as_u16())?;
+        self.session().credentials = Some(Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Log in again with the saved credentials, e.g. after qBittorrent restarted and forgot the
+    /// session. Returns false if there are no credentials.
+    pub(crate) async fn relogin(&mut self) -> Result<bool, Error> {
+        let Some(credentials) = self.session().credentials.clone() else {
+            return Ok(false);
         };
-        let response = self.send_request(&request).await?;
-        match response.status_code().as_u16() {
-            200 => Ok(()),
-            403 => Err(Error::Banned),
-            _ => Err(Error::WrongStatusCode),
-        }
+        self.session().cookie.clear();
+        self.login(&credentials.username, &credentials.password)
+            .await?;
+        Ok(true)
     }
 
     /// Logout
     ///
+    /// The saved credentials are forgotten too, so the following requests are not logged in
+    /// again automatically.
+    ///
     /// Name: logout
     ///
     /// Parameters:
@@ -70,7 +83,77 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        self.cookie = String::new();
-        check_default_status(&response, ())
+        {
+            let mut session = self.session();
+            session.cookie.clear();
+            session.credentials = None;
+        }
+        self.check_default_status(&response, ())
+    }
+
+    /// Close the session
+    ///
+    /// Logs out so the session does not linger on the server until it times out. Clones of the
+    /// client share the same session and can't be used after it is closed.
+    ///
+    pub async fn close(mut self) -> Result<(), Error> {
+        let logged_in = !self.session().cookie.is_empty();
+        if !logged_in {
+            return Ok(());
+        }
+        self.logout().await
+    }
+
+    /// SID of the current session, None if not logged in
+    ///
+    /// Save it to restore the session with Client::with_session_cookie after a restart instead
+    /// of logging in again.
+    ///
+    pub fn session_cookie(&self) -> Option<String> {
+        self.session()
+            .cookie
+            .strip_prefix("SID=")
+            .filter(|sid| !sid.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Create a client with a saved session SID
+    ///
+    /// The session is validated by requesting app/version. Returns Error::NotAuth if the server
+    /// no longer accepts the SID, e.g. after a restart or a session timeout.
+    ///
+    pub async fn with_session_cookie(uri: &str, sid: &str) -> Result<Client, Error> {
+        let mut client = Client::new(uri)?;
+        let sid = sid.strip_prefix("SID=").unwrap_or(sid);
+        client.session().cookie = format!("SID={sid}");
+        let request = ApiRequest {
+            method: Method::Version,
+            arguments: None,
+        };
+        let response = client.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(client),
+            403 => Err(Error::NotAuth),
+            _ => Err(client.status_error(&response)),
+        }
+    }
+}
+
+pub(crate) fn login_request(username: &str, password: &str) -> ApiRequest {
+    ApiRequest {
+        method: Method::Login,
+        arguments: Some(Arguments::Form(format!(
+            "username={}&password={}",
+            encode(username),
+            encode(password)
+        ))),
+    }
+}
+
+pub(crate) fn login_status(status: u16) -> Result<(), Error> {
+    match status {
+        200 => Ok(()),
+        403 => Err(Error::Banned),
+        _ => Err(Error::WrongStatusCode),
     }
 }
@@ -0,0 +1,178 @@
+// Banning
+//
+// Ban peers by declared rules, e.g. fake clients or peers only leeching, with a dry-run mode
+// and an audit log of the bans.
+
+use std::collections::HashSet;
+
+use crate::{
+    maintenance::now,
+    peers::{PeerMonitor, PeerStats},
+    sync::Peer,
+    transfer::PeerAddr,
+    Error,
+};
+
+/// Peers banned by a BanRule
+#[derive(Debug, Clone, PartialEq)]
+pub enum BanCondition {
+    /// The client name or the peer ID client starts with the prefix (case insensitive), e.g.
+    /// "Xunlei" or "-XL"
+    ClientPrefix(String),
+    /// The peer reports more than 100% progress, or less progress than the data uploaded to it
+    /// on this connection accounts for, by more than margin (0.0 to 1.0 of the torrent size)
+    FakeProgress { margin: f64 },
+    /// More than this many bytes were uploaded to the peer over all its connections, and
+    /// nothing was downloaded from it
+    Leecher { uploaded_above: i64 },
+}
+
+impl BanCondition {
+    /// Whether the peer, connected to a torrent of torrent_size bytes, matches
+    pub fn matches(&self, peer: &Peer, stats: Option<&PeerStats>, torrent_size: i64) -> bool {
+        match self {
+            BanCondition::ClientPrefix(prefix) => {
+                let prefix = prefix.to_lowercase();
+                peer.client.to_lowercase().starts_with(&prefix)
+                    || peer
+                        .peer_id_client
+                        .as_ref()
+                        .is_some_and(|client| client.to_lowercase().starts_with(&prefix))
+            }
+            BanCondition::FakeProgress { margin } => {
+                let received = match torrent_size {
+                    0 => 0.0,
+                    size => peer.uploaded as f64 / size as f64,
+                };
+                peer.progress > 1.0 || received > peer.progress + margin
+            }
+            BanCondition::Leecher { uploaded_above } => {
+                stats.is_some_and(|stats| stats.uploaded > *uploaded_above && stats.downloaded == 0)
+            }
+        }
+    }
+}
+
+/// Ban rule of a PeerBanner
+#[derive(Debug, Clone, PartialEq)]
+pub struct BanRule {
+    /// Name reported in the bans of the rule
+    pub name: String,
+    /// Peers banned by the rule
+    pub condition: BanCondition,
+}
+
+impl BanRule {
+    pub fn new(name: &str, condition: BanCondition) -> Self {
+        Self {
+            name: name.to_string(),
+            condition,
+        }
+    }
+}
+
+/// Ban decided by a PeerBanner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanRecord {
+    /// Peer address
+    pub peer: PeerAddr,
+    /// Client name of the peer
+    pub client: String,
+    /// Hash of the torrent the peer was connected to
+    pub hash: String,
+    /// Name of the rule that matched
+    pub rule: String,
+    /// Unix timestamp of the ban
+    pub time: i64,
+    /// False in dry-run mode
+    pub executed: bool,
+}
+
+/// Rules banning peers, evaluated in order against the peers of a PeerMonitor
+///
+/// A peer is banned by the first rule it matches, and reported once per address: banned
+/// peers disconnect, and in dry-run mode a peer is not reported again at the next runs.
+#[derive(Debug, Clone, Default)]
+pub struct PeerBanner {
+    rules: Vec<BanRule>,
+    dry_run: bool,
+    reported: HashSet<String>,
+    audit: Vec<BanRecord>,
+}
+
+impl PeerBanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rule(mut self, rule: BanRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Report the bans without banning anything
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Poll the monitor, evaluate the rules and ban the matching peers with transfer/banPeers
+    ///
+    /// Returns the bans of this run, which are also added to the audit log.
+    ///
+    pub async fn run(&mut self, monitor: &mut PeerMonitor) -> Result<Vec<BanRecord>, Error> {
+        monitor.poll().await?;
+        let time = now();
+        let mut bans = Vec::new();
+        for (hash, peer) in monitor.connections() {
+            let addr = PeerAddr::new(&peer.ip, peer.port);
+            if self.reported.contains(&addr.to_string()) {
+                continue;
+            }
+            let size = monitor
+                .torrents()
+                .raw_torrent(hash)
+                .and_then(|fields| fields.get("total_size"))
+                .and_then(|size| size.as_i64())
+                .unwrap_or_default();
+            let stats = monitor.peers().get(&peer.ip);
+            let Some(rule) = self
+                .rules
+                .iter()
+                .find(|rule| rule.condition.matches(peer, stats, size))
+            else {
+                continue;
+            };
+            bans.push(BanRecord {
+                peer: addr,
+                client: peer.client.clone(),
+                hash: hash.to_string(),
+                rule: rule.name.clone(),
+                time,
+                executed: !self.dry_run,
+            });
+        }
+        if !self.dry_run && !bans.is_empty() {
+            let peers: Vec<PeerAddr> = bans.iter().map(|ban| ban.peer.clone()).collect();
+            monitor.client.ban_peers(&peers).await?;
+        }
+        for ban in &bans {
+            self.reported.insert(ban.peer.to_string());
+            ::log::debug!(
+                "{} peer {} ({}) of {} by rule {}",
+                if ban.executed { "banned" } else { "would ban" },
+                ban.peer,
+                ban.client,
+                ban.hash,
+                ban.rule
+            );
+        }
+        self.audit.extend(bans.iter().cloned());
+        Ok(bans)
+    }
+
+    /// All bans since the banner was created, oldest first
+    pub fn audit_log(&self) -> &[BanRecord] {
+        &self.audit
+    }
+}
@@ -0,0 +1,171 @@
+// Batch
+//
+// Run many torrent operations with bounded concurrency, see Client::concurrent.
+
+use futures_util::StreamExt;
+
+use crate::{Client, Error};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Operation applied to one torrent of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOp {
+    Pause,
+    Resume,
+    Recheck,
+    Reannounce,
+    SetCategory(String),
+    AddTags(Vec<String>),
+}
+
+/// One torrent operation of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem {
+    /// Torrent hash
+    pub hash: String,
+    /// Operation to apply
+    pub op: BatchOp,
+}
+
+impl BatchItem {
+    async fn run(&self, client: &mut Client) -> Result<(), Error> {
+        let hashes = vec![self.hash.as_str()];
+        match &self.op {
+            BatchOp::Pause => client.pause_torrent(hashes).await,
+            BatchOp::Resume => client.resume_torrent(hashes).await,
+            BatchOp::Recheck => client.recheck_torrent(hashes).await,
+            BatchOp::Reannounce => client.reannounce_torrent(hashes).await,
+            BatchOp::SetCategory(category) => client.set_category(hashes, category).await,
+            BatchOp::AddTags(tags) => {
+                client
+                    .add_tags(hashes, tags.iter().map(String::as_str).collect())
+                    .await
+            }
+        }
+    }
+}
+
+/// Results of an executed batch
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Items applied successfully
+    pub succeeded: Vec<BatchItem>,
+    /// Items that failed, with the error
+    pub failed: Vec<(BatchItem, Error)>,
+}
+
+impl BatchReport {
+    /// True if every item succeeded
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Batch builder, created by Client::batch
+///
+/// Every item is sent as its own request on a clone of the client, so a failure is reported
+/// for the torrent it belongs to without stopping the rest of the batch.
+#[derive(Debug)]
+pub struct Batch {
+    client: Client,
+    items: Vec<BatchItem>,
+    concurrency: usize,
+}
+
+impl Client {
+    /// Start a batch of torrent operations
+    pub fn batch(&self) -> Batch {
+        Batch {
+            client: self.clone(),
+            items: Vec::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl Batch {
+    /// Maximum number of requests in flight (default: 8)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    fn push(mut self, hashes: impl IntoIterator<Item = impl AsRef<str>>, op: BatchOp) -> Self {
+        self.items.extend(hashes.into_iter().map(|hash| BatchItem {
+            hash: hash.as_ref().to_string(),
+            op: op.clone(),
+        }));
+        self
+    }
+
+    /// Pause torrents
+    pub fn pause(self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.push(hashes, BatchOp::Pause)
+    }
+
+    /// Resume torrents
+    pub fn resume(self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.push(hashes, BatchOp::Resume)
+    }
+
+    /// Recheck torrents
+    pub fn recheck(self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.push(hashes, BatchOp::Recheck)
+    }
+
+    /// Reannounce torrents
+    pub fn reannounce(self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.push(hashes, BatchOp::Reannounce)
+    }
+
+    /// Set category of torrents
+    pub fn set_category(
+        self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        category: &str,
+    ) -> Self {
+        self.push(hashes, BatchOp::SetCategory(category.to_string()))
+    }
+
+    /// Add tags to torrents
+    pub fn add_tags(
+        self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        tags: &[&str],
+    ) -> Self {
+        let tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self.push(hashes, BatchOp::AddTags(tags))
+    }
+
+    /// Number of queued items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// True if no item is queued
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Run all queued items and collect their results
+    pub async fn execute(self) -> BatchReport {
+        let results: Vec<(BatchItem, Result<(), Error>)> = self
+            .client
+            .concurrent(self.concurrency)
+            .stream(self.items, |mut client, item| async move {
+                item.run(&mut client).await
+            })
+            .collect()
+            .await;
+
+        let mut report = BatchReport::default();
+        for (item, result) in results {
+            match result {
+                Ok(()) => report.succeeded.push(item),
+                Err(err) => report.failed.push((item, err)),
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,164 @@
+// Bencode
+//
+// Minimal bencode decoder for .torrent files. Dictionaries keep their raw bytes, so the info
+// hash can be computed over the exact encoding of the info dictionary.
+
+use std::collections::BTreeMap;
+
+use crate::Error;
+
+/// Maximum nesting of lists and dictionaries, file trees of v2 torrents are the deepest
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    Dict {
+        entries: BTreeMap<&'a [u8], Value<'a>>,
+        raw: &'a [u8],
+    },
+}
+
+impl<'a> Value<'a> {
+    /// Value of a dictionary key
+    pub(crate) fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.entries()?.get(key.as_bytes())
+    }
+
+    pub(crate) fn entries(&self) -> Option<&BTreeMap<&'a [u8], Value<'a>>> {
+        match self {
+            Value::Dict { entries, .. } => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Raw bytes of a dictionary
+    pub(crate) fn raw(&self) -> Option<&'a [u8]> {
+        match self {
+            Value::Dict { raw, .. } => Some(raw),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Value::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Byte string as UTF-8, invalid sequences replaced
+    pub(crate) fn as_string(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|value| String::from_utf8_lossy(value).into_owned())
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a complete bencoded value
+pub(crate) fn decode(data: &[u8]) -> Result<Value<'_>, Error> {
+    let mut decoder = Decoder { data, pos: 0 };
+    let value = decoder.value(0)?;
+    if decoder.pos != data.len() {
+        return Err(Error::InvalidTorrentFile("trailing data"));
+    }
+    Ok(value)
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn value(&mut self, depth: usize) -> Result<Value<'a>, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidTorrentFile("nesting too deep"));
+        }
+        match self.peek()? {
+            b'i' => {
+                self.pos += 1;
+                let digits = self.until(b'e')?;
+                Ok(Value::Int(parse_int(digits)?))
+            }
+            b'l' => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                while self.peek()? != b'e' {
+                    values.push(self.value(depth + 1)?);
+                }
+                self.pos += 1;
+                Ok(Value::List(values))
+            }
+            b'd' => {
+                let start = self.pos;
+                self.pos += 1;
+                let mut entries = BTreeMap::new();
+                while self.peek()? != b'e' {
+                    let key = self.bytes()?;
+                    let value = self.value(depth + 1)?;
+                    entries.insert(key, value);
+                }
+                self.pos += 1;
+                Ok(Value::Dict {
+                    entries,
+                    raw: &self.data[start..self.pos],
+                })
+            }
+            b'0'..=b'9' => Ok(Value::Bytes(self.bytes()?)),
+            _ => Err(Error::InvalidTorrentFile("unexpected byte")),
+        }
+    }
+
+    fn peek(&self) -> Result<u8, Error> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or(Error::InvalidTorrentFile("unexpected end of data"))
+    }
+
+    /// Bytes up to the end byte, which is skipped
+    fn until(&mut self, end: u8) -> Result<&'a [u8], Error> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|byte| *byte == end)
+            .ok_or(Error::InvalidTorrentFile("unexpected end of data"))?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = parse_int(self.until(b':')?)?;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|end| *end <= self.data.len())
+            .ok_or(Error::InvalidTorrentFile("invalid string length"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+fn parse_int(digits: &[u8]) -> Result<i64, Error> {
+    std::str::from_utf8(digits)
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or(Error::InvalidTorrentFile("invalid integer"))
+}
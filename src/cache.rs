@@ -0,0 +1,148 @@
+// Cache
+//
+// Client-side cache of the responses of read-only endpoints whose data rarely changes, e.g.
+// preferences and tags polled by dashboards.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+
+use crate::{
+    request::{ApiRequest, Arguments, Method},
+    response::response_body,
+    Client, Error,
+};
+
+/// Methods cached by ClientBuilder::cache_ttl
+pub const CACHEABLE_METHODS: [Method; 7] = [
+    Method::Version,
+    Method::WebapiVersion,
+    Method::BuildInfo,
+    Method::Preferences,
+    Method::DefaultSavePath,
+    Method::Tags,
+    Method::Categories,
+];
+
+/// Cached methods whose response may change after a request with method
+fn invalidated_by(method: Method) -> &'static [Method] {
+    match method {
+        Method::SetPreferences => &[Method::Preferences, Method::DefaultSavePath],
+        Method::Add => &[Method::Tags, Method::Categories],
+        Method::AddTags | Method::CreateTags => &[Method::Tags],
+        Method::CreateCategory | Method::EditCategory | Method::RemoveCategories => {
+            &[Method::Categories]
+        }
+        Method::Shutdown => &CACHEABLE_METHODS,
+        _ => &[],
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    body: Bytes,
+    expires: Instant,
+}
+
+/// Cached response bodies of a client, shared between its clones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ResponseCache {
+    ttls: HashMap<Method, Duration>,
+    entries: Arc<Mutex<HashMap<(Method, String), Entry>>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(
+        default: Option<Duration>,
+        methods: &HashMap<Method, Option<Duration>>,
+    ) -> Self {
+        let mut ttls: HashMap<Method, Duration> = default
+            .map(|ttl| {
+                CACHEABLE_METHODS
+                    .iter()
+                    .map(|method| (*method, ttl))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for (method, ttl) in methods {
+            match ttl {
+                Some(ttl) => ttls.insert(*method, *ttl),
+                None => ttls.remove(method),
+            };
+        }
+        Self {
+            ttls,
+            entries: Arc::default(),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<(Method, String), Entry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Drop the cached responses that a request with method may change
+    pub(crate) fn invalidate(&self, method: Method) {
+        let methods = invalidated_by(method);
+        if !methods.is_empty() {
+            self.entries()
+                .retain(|(cached, _), _| !methods.contains(cached));
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries().clear();
+    }
+}
+
+/// Cache key of the arguments of a request, None for multipart uploads which are never cached
+fn arguments_key(arguments: &Option<Arguments>) -> Option<String> {
+    match arguments {
+        Some(Arguments::Json(value)) => Some(value.to_string()),
+        Some(Arguments::Form(value)) => Some(value.clone()),
+        Some(Arguments::Multipart(_)) => None,
+        None => Some(String::new()),
+    }
+}
+
+impl Client {
+    /// Body of a successful response, from the cache if the method is cached and the response
+    /// is not older than its TTL. Other status codes fail as status_error.
+    pub(crate) async fn cached_body(&mut self, request: &ApiRequest) -> Result<Bytes, Error> {
+        let cached = self
+            .cache
+            .ttls
+            .get(&request.method)
+            .copied()
+            .zip(arguments_key(&request.arguments))
+            .map(|(ttl, key)| (ttl, (request.method, key)));
+        if let Some((_, key)) = &cached {
+            if let Some(entry) = self.cache.entries().get(key) {
+                if entry.expires > Instant::now() {
+                    return Ok(entry.body.clone());
+                }
+            }
+        }
+        let response = self.send_request(request).await?;
+        if response.status_code().as_u16() != 200 {
+            return Err(self.status_error(&response));
+        }
+        let body = response_body(&response)?;
+        if let Some((ttl, key)) = cached {
+            if let Some(expires) = Instant::now().checked_add(ttl) {
+                let entry = Entry {
+                    body: body.clone(),
+                    expires,
+                };
+                self.cache.entries().insert(key, entry);
+            }
+        }
+        Ok(body)
+    }
+
+    /// Drop all cached responses, see ClientBuilder::cache_ttl
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+}
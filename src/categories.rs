@@ -0,0 +1,81 @@
+// Categories
+//
+// Declare the categories and their save paths, creating, editing and removing categories on
+// the server to match.
+
+use std::collections::HashMap;
+
+use crate::{Client, Error};
+
+/// Changes made by Client::ensure_categories, names sorted
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CategoryReport {
+    /// Categories created
+    pub created: Vec<String>,
+    /// Categories whose save path was changed
+    pub edited: Vec<String>,
+    /// Categories removed
+    pub removed: Vec<String>,
+    /// Categories already as declared
+    pub unchanged: Vec<String>,
+}
+
+impl CategoryReport {
+    /// True if nothing was changed on the server
+    pub fn is_unchanged(&self) -> bool {
+        self.created.is_empty() && self.edited.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Save paths are equal up to trailing separators
+pub(crate) fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+impl Client {
+    /// Make the categories of the server match desired, a map of category name to save path
+    ///
+    /// Missing categories are created and categories with another save path are edited. Other
+    /// categories on the server are removed only if remove_others is set; their torrents are
+    /// left without category.
+    ///
+    pub async fn ensure_categories(
+        &mut self,
+        desired: &HashMap<String, String>,
+        remove_others: bool,
+    ) -> Result<CategoryReport, Error> {
+        let current = self.get_categories().await?;
+        let mut report = CategoryReport::default();
+        let mut names: Vec<&String> = desired.keys().collect();
+        names.sort();
+
+        for name in names {
+            let save_path = &desired[name];
+            match current.get(name) {
+                None => {
+                    self.create_category(name, save_path).await?;
+                    report.created.push(name.clone());
+                }
+                Some(category) if !same_path(&category.save_path, save_path) => {
+                    self.edit_category(name, save_path).await?;
+                    report.edited.push(name.clone());
+                }
+                Some(_) => report.unchanged.push(name.clone()),
+            }
+        }
+
+        if remove_others {
+            let mut others: Vec<&str> = current
+                .keys()
+                .filter(|name| !desired.contains_key(*name))
+                .map(String::as_str)
+                .collect();
+            if !others.is_empty() {
+                others.sort();
+                report.removed = others.iter().map(|name| name.to_string()).collect();
+                self.remove_categories(others).await?;
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,225 @@
+// Cert
+//
+// Certificate of an HTTPS WebUI, read by a separate TLS handshake since the transport doesn't
+// expose its own. WebUIs often use self-signed certificates, so the handshake accepts any
+// certificate and records whether the Mozilla root store trusts it.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+use tokio::net::TcpStream;
+use tokio::time::timeout_at;
+use tokio_rustls::{
+    rustls::{
+        self,
+        client::{
+            danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+            WebPkiServerVerifier,
+        },
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    },
+    TlsConnector,
+};
+use url::Url;
+
+use crate::{probe::TlsInfo, Error};
+
+/// Verifier accepting any certificate, recording whether webpki trusts it
+#[derive(Debug)]
+struct RecordingVerifier {
+    webpki: Arc<WebPkiServerVerifier>,
+    trusted: Mutex<bool>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let trusted = self
+            .webpki
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+            .is_ok();
+        *self.trusted.lock().unwrap_or_else(PoisonError::into_inner) = trusted;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.webpki.supported_verify_schemes()
+    }
+}
+
+/// Certificate of the HTTPS server of url, Error::Timeout if the handshake isn't done by
+/// deadline
+pub(crate) async fn server_certificate(url: &Url, deadline: Instant) -> Result<TlsInfo, Error> {
+    timeout_at(deadline.into(), handshake(url))
+        .await
+        .map_err(|_| Error::Timeout)?
+}
+
+async fn handshake(url: &Url) -> Result<TlsInfo, Error> {
+    let host = url
+        .host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+        .ok_or_else(|| Error::Tls("URL has no host".to_string()))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let server_name =
+        ServerName::try_from(host.to_string()).map_err(|err| Error::Tls(err.to_string()))?;
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let webpki = WebPkiServerVerifier::builder(Arc::new(roots))
+        .build()
+        .map_err(|err| Error::Tls(err.to_string()))?;
+    let verifier = Arc::new(RecordingVerifier {
+        webpki,
+        trusted: Mutex::new(false),
+    });
+    let config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+
+    let stream = TcpStream::connect((host, port)).await?;
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|err| Error::Tls(err.to_string()))?;
+    let (_, connection) = stream.get_ref();
+    let certificate = connection
+        .peer_certificates()
+        .and_then(|chain| chain.first())
+        .ok_or_else(|| Error::Tls("server sent no certificate".to_string()))?;
+    let mut info = parse_certificate(certificate)
+        .ok_or_else(|| Error::Tls("cannot parse the server certificate".to_string()))?;
+    info.trusted = *verifier
+        .trusted
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    Ok(info)
+}
+
+/// Issuer, subject and expiry of a DER X.509 certificate
+fn parse_certificate(der: &[u8]) -> Option<TlsInfo> {
+    let (_, certificate, _) = element(der)?;
+    let (_, tbs, _) = element(certificate)?;
+    let (tag, _, mut fields) = element(tbs)?;
+    if tag == 0xa0 {
+        // Explicit version, the serial number follows
+        fields = element(fields)?.2;
+    }
+    // Signature algorithm
+    let fields = element(fields)?.2;
+    let (_, issuer, fields) = element(fields)?;
+    let (_, validity, fields) = element(fields)?;
+    let (_, subject, _) = element(fields)?;
+    let not_before = element(validity)?.2;
+    let (time_tag, not_after, _) = element(not_before)?;
+    Some(TlsInfo {
+        issuer: name(issuer)?,
+        subject: name(subject)?,
+        not_after: time(time_tag, not_after)?,
+        trusted: false,
+    })
+}
+
+/// Tag, contents and following input of the DER element at the start of input
+fn element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+    let mut len = usize::from(first);
+    if first >= 0x80 {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 || input.len() < count {
+            return None;
+        }
+        let (bytes, rest) = input.split_at(count);
+        len = bytes
+            .iter()
+            .fold(0, |len, &byte| (len << 8) | usize::from(byte));
+        input = rest;
+    }
+    if input.len() < len {
+        return None;
+    }
+    let (contents, rest) = input.split_at(len);
+    Some((tag, contents, rest))
+}
+
+/// Distinguished name as attribute=value pairs in certificate order, e.g. CN=R3, O=Let's
+/// Encrypt, C=US
+fn name(mut rdns: &[u8]) -> Option<String> {
+    let mut parts = Vec::new();
+    while !rdns.is_empty() {
+        let (_, mut attributes, rest) = element(rdns)?;
+        rdns = rest;
+        while !attributes.is_empty() {
+            let (_, attribute, rest) = element(attributes)?;
+            attributes = rest;
+            let (_, oid, value) = element(attribute)?;
+            let (_, value, _) = element(value)?;
+            let label = match oid {
+                [0x55, 0x04, 0x03] => "CN",
+                [0x55, 0x04, 0x06] => "C",
+                [0x55, 0x04, 0x07] => "L",
+                [0x55, 0x04, 0x08] => "ST",
+                [0x55, 0x04, 0x0a] => "O",
+                [0x55, 0x04, 0x0b] => "OU",
+                _ => continue,
+            };
+            parts.push(format!("{label}={}", String::from_utf8_lossy(value)));
+        }
+    }
+    Some(parts.join(", "))
+}
+
+/// Unix timestamp of a DER UTCTime (0x17) or GeneralizedTime (0x18), e.g. 361013111145Z
+fn time(tag: u8, value: &[u8]) -> Option<i64> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let year: i64 = value.get(..2)?.parse().ok()?;
+            let century = if year < 50 { 2000 } else { 1900 };
+            (century + year, value.get(2..)?)
+        }
+        0x18 => (value.get(..4)?.parse().ok()?, value.get(4..)?),
+        _ => return None,
+    };
+    let field = |start: usize| rest.get(start..start + 2)?.parse::<i64>().ok();
+    let days = days_from_civil(year, field(0)?, field(2)?);
+    Some(days * 86400 + field(4)? * 3600 + field(6)? * 60 + field(8)?)
+}
+
+/// Number of days since 1970-01-01 of a date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,98 @@
+// Concurrent
+//
+// Bounded parallelism for composite features sending one request per torrent: every request
+// runs on its own clone of the client, which shares the session and settings.
+
+use std::future::Future;
+
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{torrents::TorrentProperties, Client, Error};
+
+/// Requests run with at most limit in flight, see Client::concurrent
+#[derive(Debug, Clone)]
+pub struct Concurrent {
+    client: Client,
+    limit: usize,
+}
+
+impl Client {
+    /// Run requests on clones of the client, at most limit at once (at least 1)
+    pub fn concurrent(&self, limit: usize) -> Concurrent {
+        Concurrent {
+            client: self.clone(),
+            limit: limit.max(1),
+        }
+    }
+}
+
+impl Concurrent {
+    /// Maximum number of requests in flight
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Call f for every item with a clone of the client, yielding the items with their results
+    /// as they complete
+    pub fn stream<I, T, F, Fut>(
+        &self,
+        items: I,
+        mut f: F,
+    ) -> impl Stream<Item = (I::Item, Result<T, Error>)>
+    where
+        I: IntoIterator,
+        I::Item: Clone,
+        F: FnMut(Client, I::Item) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let client = self.client.clone();
+        stream::iter(items)
+            .map(move |item| {
+                let result = f(client.clone(), item.clone());
+                async move { (item, result.await) }
+            })
+            .buffer_unordered(self.limit)
+    }
+
+    /// Call f for every item with a clone of the client, returning the results in the order of
+    /// the items
+    pub async fn map<I, T, F, Fut>(&self, items: I, mut f: F) -> Vec<Result<T, Error>>
+    where
+        I: IntoIterator,
+        F: FnMut(Client, I::Item) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let client = self.client.clone();
+        stream::iter(items)
+            .map(|item| f(client.clone(), item))
+            .buffered(self.limit)
+            .collect()
+            .await
+    }
+
+    /// Like Concurrent::map, failing with the first error in the order of the items
+    pub async fn try_map<I, T, F, Fut>(&self, items: I, f: F) -> Result<Vec<T>, Error>
+    where
+        I: IntoIterator,
+        F: FnMut(Client, I::Item) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.map(items, f).await.into_iter().collect()
+    }
+}
+
+/// Get the properties of torrents, fetching up to concurrency at once
+///
+/// Results are in the order of hashes, see Client::get_torrent_properties.
+pub async fn fetch_all_properties(
+    client: &Client,
+    hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    concurrency: usize,
+) -> Vec<Result<Option<TorrentProperties>, Error>> {
+    client
+        .concurrent(concurrency)
+        .map(hashes, |mut client, hash| async move {
+            client.get_torrent_properties(hash.as_ref()).await
+        })
+        .await
+}
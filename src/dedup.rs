@@ -0,0 +1,38 @@
+// Dedup
+//
+// Check whether a torrent already exists before adding it. qBittorrent answers "Ok." when
+// adding a torrent it already has and doesn't change anything.
+
+use crate::{
+    torrents::{AddTorrent, AddedTorrent, GetTorrentList, Torrent},
+    Client, Error,
+};
+
+/// Result of Client::add_torrent_dedup
+#[derive(Debug)]
+pub enum AddOutcome {
+    /// The torrent was added
+    Added(AddedTorrent),
+    /// The torrent is already in the list, nothing was sent
+    AlreadyExists(Box<Torrent>),
+}
+
+impl Client {
+    /// Add a torrent unless it already exists
+    ///
+    /// The info hash is taken from the magnet link or the .torrent file of values (see
+    /// AddTorrent::target_id). When it can't be known, e.g. for http URLs or several torrents,
+    /// the torrent is added without checking.
+    ///
+    pub async fn add_torrent_dedup(&mut self, values: AddTorrent) -> Result<AddOutcome, Error> {
+        if let Some(id) = values.target_id() {
+            let existing = self
+                .get_torrent_list(GetTorrentList::builder().hashes(vec![&id]).build())
+                .await?;
+            if let Some(torrent) = existing.into_iter().next() {
+                return Ok(AddOutcome::AlreadyExists(Box::new(torrent)));
+            }
+        }
+        Ok(AddOutcome::Added(self.add_torrent(values).await?))
+    }
+}
@@ -10,6 +10,8 @@ pub enum Error {
     Banned,
     #[error("Wrong response status code")]
     WrongStatusCode,
+    #[error("API error {code}: {message}")]
+    Api { code: i64, message: String },
     #[error("Error convert bytes to string")]
     BytesToString(#[from] std::string::FromUtf8Error),
     #[error("Torrent hash was not found")]
@@ -18,13 +20,39 @@ pub enum Error {
     StringToInt(#[from] std::num::ParseIntError),
     #[error("Torrent file is not valid")]
     NoValidTorrent,
+    #[error("Added torrent was not found in the torrent list")]
+    AddedTorrentNotFound,
+    #[error("Cannot parse torrent file: {0}")]
+    InvalidTorrentFile(&'static str),
+    #[error("Invalid IP range: {0}")]
+    InvalidIpRange(String),
+    #[error("Invalid info hash: {0}")]
+    InvalidInfoHash(String),
+    #[error("Invalid magnet link: {0}")]
+    InvalidMagnet(String),
+    #[error("Invalid speed limits schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("Invalid policy rule: {0}")]
+    InvalidRule(String),
+    #[error("IP version and static addresses are not supported over https, host {0}")]
+    HttpsAddressSelection(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error("No address of the requested IP version for host {0}")]
+    NoAddress(String),
 
     #[error("NC error")]
     Nc(#[from] netc::error::Error),
     #[error("json error")]
     Json(#[from] serde_json::Error),
-    #[error("dotenv error")]
-    DotEnv(#[from] dotenv::Error),
+    #[error("Invalid regular expression: {0}")]
+    Regex(#[from] regex::Error),
+    #[error("cannot decode response of {method}: {source}, body: {body_snippet}")]
+    Decode {
+        method: String,
+        source: serde_json::Error,
+        body_snippet: String,
+    },
     #[error("response not success: {0}")]
     BadResponse(String),
     #[error("response no contain arguments")]
@@ -41,4 +69,84 @@ pub enum Error {
     NoFileMeta,
     #[error("Unknown torrent fields")]
     UnknownTorrentFields,
+    #[error("None of the supplied peers are valid")]
+    NoValidPeers,
+    #[error("Category name does not exist")]
+    NoCategory,
+    #[error("Category name is empty or invalid")]
+    InvalidCategory,
+    #[error("Category editing failed")]
+    CategoryEditFailed,
+    #[error("Torrent metadata hasn't downloaded yet")]
+    NoMetadata,
+    #[error("File priority or file id is not valid")]
+    InvalidFilePriority,
+    #[error("No file of the torrent was selected")]
+    NoFileSelected,
+    #[error("File is not in the torrent")]
+    NoFile,
+    #[error("File path is not valid or already in use")]
+    RenameConflict,
+    #[error("Piece hashes cannot be checked locally")]
+    UnsupportedPieceHashes,
+    #[error("Request timed out")]
+    Timeout,
+    #[error("Request was cancelled")]
+    Cancelled,
+    #[error("Timed out waiting for the torrent state")]
+    WaitTimeout,
+    #[error("Torrent download failed in state {0}")]
+    DownloadFailed(String),
+    #[error("Directory path is not valid")]
+    InvalidDirectoryPath,
+    #[error("Directory does not exist")]
+    NoDirectory,
+    #[error("Torrent creation task was not found")]
+    NoCreatorTask,
+    #[error("Torrent creation task has not finished")]
+    CreatorTaskNotFinished,
+    #[error("Too many active torrent creation tasks")]
+    TooManyCreatorTasks,
+    #[error("Endpoint is not supported by the server API version")]
+    UnsupportedEndpoint,
+    #[error("Path is empty")]
+    EmptyPath,
+    #[error("User does not have write access to the directory")]
+    NoWriteAccess,
+    #[error("Unable to create the directory")]
+    CannotCreateDirectory,
+    #[error("Torrent data was not moved to the new location")]
+    MoveFailed,
+    #[error("Server state does not contain the free space on disk")]
+    NoFreeSpace,
+    #[error("Web seed URL is not valid")]
+    InvalidWebseedUrl,
+    #[error("Web seed URL was not found in the torrent")]
+    NoWebseed,
+    #[error("Not enough free space on disk: {required} bytes required, {available} available")]
+    InsufficientSpace { required: i64, available: i64 },
+    #[error("{endpoint} (request {request_id}): {source}")]
+    InContext {
+        endpoint: String,
+        request_id: u64,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Error without the context added by ClientBuilder::error_context
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::InContext { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// ID of the failed request, if the client adds the error context
+    pub fn request_id(&self) -> Option<u64> {
+        match self {
+            Error::InContext { request_id, .. } => Some(*request_id),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,246 @@
+// Events
+//
+// Torrent and server events derived from the sync API.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use serde_json::{Map, Value};
+use tokio::time::sleep;
+
+use crate::{
+    retry::is_transient,
+    sync::{PollPolicy, SyncDelta, SyncSession},
+    torrents::State,
+    Client, Error,
+};
+
+/// Change reported by Client::events
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A torrent was added
+    TorrentAdded { hash: String, name: String },
+    /// A torrent finished downloading
+    TorrentCompleted { hash: String, name: String },
+    /// A torrent was removed
+    TorrentRemoved { hash: String },
+    /// A torrent entered the error or missingFiles state
+    TorrentErrored {
+        hash: String,
+        name: String,
+        state: String,
+    },
+    /// A category was created
+    CategoryAdded { name: String },
+    /// Alternative speed limits were enabled or disabled
+    SpeedLimitToggled { enabled: bool },
+    /// The connection was restored or qBittorrent restarted. The state was synced again and
+    /// the following events report the changes since the last successful poll.
+    Reconnected,
+}
+
+/// Fields of a torrent needed to detect transitions
+struct TorrentSnapshot {
+    completed: bool,
+    errored: bool,
+}
+
+impl TorrentSnapshot {
+    fn new(fields: &Map<String, Value>) -> Self {
+        let progress = fields.get("progress").and_then(Value::as_f64);
+        let state = fields.get("state").and_then(Value::as_str);
+        Self {
+            completed: progress.is_some_and(|progress| progress >= 1.0),
+            errored: state.is_some_and(|state| State::from(state).is_errored()),
+        }
+    }
+}
+
+fn field_string(fields: &Map<String, Value>, key: &str) -> String {
+    fields
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+struct EventState<'a> {
+    client: &'a mut Client,
+    policy: PollPolicy,
+    idle_polls: u32,
+    session: SyncSession,
+    torrents: HashMap<String, TorrentSnapshot>,
+    alt_speed: Option<bool>,
+    pending: VecDeque<Event>,
+    primed: bool,
+    polled: bool,
+    /// qBittorrent version at the last successful poll
+    version: Option<String>,
+    /// Whether the last poll failed to connect
+    disconnected: bool,
+}
+
+impl EventState<'_> {
+    async fn poll(&mut self) -> Result<(), Error> {
+        let delta = match self.client.sync(&mut self.session).await {
+            Err(err) if matches!(err.root(), Error::NotAuth) && self.client.relogin().await? => {
+                self.disconnected = true;
+                self.client.sync(&mut self.session).await?
+            }
+            // Keep polling until qBittorrent is back, only the first connection must succeed
+            Err(err) if self.primed && is_transient(&err) => {
+                ::log::debug!("events poll failed, waiting for qBittorrent: {err}");
+                self.disconnected = true;
+                self.idle_polls = self.idle_polls.saturating_add(1);
+                return Ok(());
+            }
+            result => result?,
+        };
+        if !self.primed {
+            self.version = self.client.get_version().await.ok();
+        } else if self.disconnected || delta.resynced {
+            self.disconnected = false;
+            self.pending.push_back(Event::Reconnected);
+            let version = self.client.get_version().await.ok();
+            if version.is_some() && version != self.version {
+                ::log::debug!("qBittorrent version changed to {version:?}, resyncing");
+                self.version = version;
+                if !delta.full_update {
+                    self.session.reset();
+                }
+            }
+        }
+        self.collect(delta);
+        self.primed = true;
+        self.idle_polls = if self.session.is_active() {
+            0
+        } else {
+            self.idle_polls.saturating_add(1)
+        };
+        Ok(())
+    }
+
+    /// Compare the merged state with the previous snapshots. The first sync only records the
+    /// initial state, so existing torrents are not reported as added.
+    fn collect(&mut self, delta: SyncDelta) {
+        for hash in delta.torrents_removed {
+            if self.torrents.remove(&hash).is_some() && self.primed {
+                self.pending.push_back(Event::TorrentRemoved { hash });
+            }
+        }
+
+        for hash in delta.torrents_changed {
+            let Some(fields) = self.session.raw_torrent(&hash) else {
+                continue;
+            };
+            let current = TorrentSnapshot::new(fields);
+            let name = field_string(fields, "name");
+            match self.torrents.get(&hash) {
+                None if self.primed => {
+                    self.pending.push_back(Event::TorrentAdded {
+                        hash: hash.clone(),
+                        name: name.clone(),
+                    });
+                }
+                Some(previous) if !previous.completed && current.completed => {
+                    self.pending.push_back(Event::TorrentCompleted {
+                        hash: hash.clone(),
+                        name: name.clone(),
+                    });
+                }
+                _ => {}
+            }
+            let was_errored = self.torrents.get(&hash).is_some_and(|s| s.errored);
+            if self.primed && current.errored && !was_errored {
+                self.pending.push_back(Event::TorrentErrored {
+                    hash: hash.clone(),
+                    name,
+                    state: field_string(fields, "state"),
+                });
+            }
+            self.torrents.insert(hash, current);
+        }
+
+        if self.primed {
+            for name in delta.categories_added {
+                self.pending.push_back(Event::CategoryAdded { name });
+            }
+        }
+
+        if delta.server_state_changed {
+            let enabled = self
+                .session
+                .raw_server_state()
+                .get("use_alt_speed_limits")
+                .and_then(Value::as_bool);
+            if let (Some(previous), Some(enabled)) = (self.alt_speed, enabled) {
+                if previous != enabled {
+                    self.pending.push_back(Event::SpeedLimitToggled { enabled });
+                }
+            }
+            if enabled.is_some() {
+                self.alt_speed = enabled;
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Subscribe to torrent and server events
+    ///
+    /// Polls sync/maindata every poll_interval and yields only the changes since the previous
+    /// poll. The state at the first poll is the baseline and produces no events. Request
+    /// errors are yielded and polling continues on the next interval.
+    ///
+    /// Once the first poll succeeded, connection failures are not yielded: polling continues
+    /// until qBittorrent is reachable again. A lost session is logged in again with the
+    /// credentials of the last login. Both yield Event::Reconnected followed by the changes
+    /// during the outage.
+    ///
+    pub fn events(&mut self, poll_interval: Duration) -> BoxStream<'_, Result<Event, Error>> {
+        self.events_with_policy(PollPolicy::fixed(poll_interval))
+    }
+
+    /// Subscribe to torrent and server events, polling as configured by policy
+    ///
+    /// Same as events, but polls faster while a torrent is downloading and backs off when
+    /// everything is idle.
+    ///
+    pub fn events_with_policy(
+        &mut self,
+        policy: PollPolicy,
+    ) -> BoxStream<'_, Result<Event, Error>> {
+        let state = EventState {
+            client: self,
+            policy,
+            idle_polls: 0,
+            session: SyncSession::new(),
+            torrents: HashMap::new(),
+            alt_speed: None,
+            pending: VecDeque::new(),
+            primed: false,
+            polled: false,
+            version: None,
+            disconnected: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                if state.polled {
+                    sleep(state.policy.interval(state.idle_polls)).await;
+                }
+                state.polled = true;
+                if let Err(err) = state.poll().await {
+                    return Some((Err(err), state));
+                }
+            }
+        })
+        .boxed()
+    }
+}
@@ -0,0 +1,148 @@
+// Form
+//
+// Encoding of the request parameters: form encoding of values and JSON arguments, the
+// separators joining lists into a single parameter, and multipart bodies for file uploads.
+
+use bytes::Bytes;
+use serde_json::Value;
+
+use crate::torrents::torrent_id;
+
+/// Percent-encode a value for use in an `application/x-www-form-urlencoded` body
+pub fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// Encode the fields of a JSON object as form parameters. Null fields are skipped, strings are
+/// sent as is and other values as JSON.
+pub fn json_to_form(value: &Value) -> String {
+    match value {
+        Value::Object(fields) => fields
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| match value {
+                Value::String(value) => format!("{}={}", encode(key), encode(value)),
+                value => format!("{}={}", encode(key), encode(&value.to_string())),
+            })
+            .collect::<Vec<String>>()
+            .join("&"),
+        value => value.to_string(),
+    }
+}
+
+/// Join hashes with | as expected by the hashes parameters, truncating SHA256 info hashes
+pub fn join_hashes(hashes: impl IntoIterator<Item = impl AsRef<str>>) -> String {
+    let mut joined = String::new();
+    for hash in hashes {
+        if !joined.is_empty() {
+            joined.push('|');
+        }
+        joined.push_str(torrent_id(hash.as_ref()));
+    }
+    joined
+}
+
+/// Join tags into the comma separated, form encoded `tags` value
+pub fn join_tags(tags: &[&str]) -> String {
+    encode(&tags.join(","))
+}
+
+/// Join URLs into the | separated, form encoded `urls` value
+pub fn join_urls(urls: &[&str]) -> String {
+    encode(&urls.join("|"))
+}
+
+/// Join values into a newline separated, form encoded value, e.g. `categories`
+pub fn join_lines(values: &[&str]) -> String {
+    encode(&values.join("\n"))
+}
+
+/// multipart/form-data body, the only encoding qBittorrent accepts for .torrent uploads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Multipart {
+    /// Empty body with a random boundary
+    pub fn new() -> Self {
+        Self {
+            boundary: format!(
+                "rqa-{:016x}{:016x}",
+                rand::random::<u64>(),
+                rand::random::<u64>()
+            ),
+            body: Vec::new(),
+        }
+    }
+
+    /// Add a text field
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.headers(name, None);
+        self.body.extend_from_slice(value.as_bytes());
+        self.body.extend_from_slice(b"\r\n");
+    }
+
+    /// Add a file field
+    pub fn file(&mut self, name: &str, filename: &str, content_type: &str, data: &[u8]) {
+        self.headers(name, Some((filename, content_type)));
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+    }
+
+    /// Add the fields of a JSON object, converted as by json_to_form
+    pub fn json_fields(&mut self, value: &Value) {
+        if let Value::Object(fields) = value {
+            for (key, value) in fields.iter().filter(|(_, value)| !value.is_null()) {
+                match value {
+                    Value::String(value) => self.text(key, value),
+                    value => self.text(key, &value.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Content-Type header of the body, with its boundary
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Complete body, with the closing boundary
+    pub fn body(&self) -> Bytes {
+        let mut body = Vec::with_capacity(self.body.len() + self.boundary.len() + 6);
+        body.extend_from_slice(&self.body);
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body.into()
+    }
+
+    fn headers(&mut self, name: &str, file: Option<(&str, &str)>) {
+        let mut headers = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            quote(name)
+        );
+        if let Some((filename, content_type)) = file {
+            headers.push_str(&format!(
+                "; filename=\"{}\"\r\nContent-Type: {content_type}",
+                quote(filename)
+            ));
+        }
+        headers.push_str("\r\n\r\n");
+        self.body.extend_from_slice(headers.as_bytes());
+    }
+}
+
+/// Value of a quoted Content-Disposition parameter, quotes and line breaks percent-encoded
+fn quote(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
@@ -0,0 +1,151 @@
+// Format
+//
+// Human-friendly formatting of sizes, speeds and durations.
+
+use std::fmt;
+
+use crate::{
+    torrents::Torrent,
+    transfer::{ConnectionStatus, TransferInfo},
+};
+
+/// ETA reported by qBittorrent when the torrent will never finish (100 days)
+pub const INFINITE_ETA: i64 = 8_640_000;
+
+const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Format a size in bytes with binary units, e.g. 1.5 GiB
+pub fn format_bytes(bytes: i64) -> String {
+    if bytes < 0 {
+        return format!("-{}", format_bytes(bytes.saturating_neg()));
+    }
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Format a speed in bytes/s, e.g. 512.0 KiB/s
+pub fn format_speed(bytes_per_second: i64) -> String {
+    format!("{}/s", format_bytes(bytes_per_second))
+}
+
+/// Format a speed limit in bytes/s, 0 and -1 meaning unlimited
+pub fn format_limit(bytes_per_second: i64) -> String {
+    if bytes_per_second <= 0 {
+        "∞".to_string()
+    } else {
+        format_speed(bytes_per_second)
+    }
+}
+
+/// Format a duration in seconds with its two largest units, e.g. 2d 5h, 3m 20s
+pub fn format_duration(seconds: i64) -> String {
+    if seconds < 0 {
+        return "-".to_string();
+    }
+    let parts = [
+        (seconds / 86_400, "d"),
+        (seconds % 86_400 / 3600, "h"),
+        (seconds % 3600 / 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    match parts.iter().position(|(value, _)| *value > 0) {
+        Some(first) => parts[first..]
+            .iter()
+            .take(2)
+            .filter(|(value, _)| *value > 0)
+            .map(|(value, unit)| format!("{value}{unit}"))
+            .collect::<Vec<String>>()
+            .join(" "),
+        None => "0s".to_string(),
+    }
+}
+
+/// Format an ETA in seconds, with ∞ for the infinite ETA sentinel
+pub fn format_eta(seconds: i64) -> String {
+    if seconds >= INFINITE_ETA {
+        "∞".to_string()
+    } else {
+        format_duration(seconds)
+    }
+}
+
+impl Torrent {
+    /// Total size of files selected for download, e.g. 1.5 GiB
+    pub fn size_human(&self) -> String {
+        format_bytes(self.size)
+    }
+
+    /// Download speed, e.g. 512.0 KiB/s
+    pub fn dlspeed_human(&self) -> String {
+        format_speed(self.dlspeed)
+    }
+
+    /// Upload speed, e.g. 512.0 KiB/s
+    pub fn upspeed_human(&self) -> String {
+        format_speed(self.upspeed)
+    }
+
+    /// ETA, e.g. 2h 15m, or ∞ if the torrent will never finish
+    pub fn eta_human(&self) -> String {
+        format_eta(self.eta)
+    }
+
+    /// Progress in percent, e.g. 42.5%
+    pub fn progress_human(&self) -> String {
+        format!("{:.1}%", self.progress * 100.0)
+    }
+}
+
+impl fmt::Display for Torrent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {} of {}, down {}, up {}, eta {}",
+            self.name,
+            self.state,
+            self.progress_human(),
+            self.size_human(),
+            self.dlspeed_human(),
+            self.upspeed_human(),
+            self.eta_human()
+        )
+    }
+}
+
+impl TransferInfo {
+    /// One line summary of the global transfer, e.g.
+    /// "down 1.2 MiB/s (3.4 GiB), up 80.0 KiB/s (1.1 GiB), 386 DHT nodes, connected"
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for TransferInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "down {} ({}), up {} ({}), {} DHT nodes, {}",
+            format_speed(self.dl_info_speed),
+            format_bytes(self.dl_info_data),
+            format_speed(self.up_info_speed),
+            format_bytes(self.up_info_data),
+            self.dht_nodes,
+            self.connection_status
+        )
+    }
+}
+
+impl fmt::Display for ConnectionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,123 @@
+// Torrent handle
+//
+// Operations on a single torrent without passing its hash to every call.
+
+use crate::{
+    hash::InfoHash,
+    torrents::{File, PieceState, TorrentProperties, Tracker, Webseed},
+    transfer::PeerAddr,
+    Client, Error,
+};
+
+/// Handle to a single torrent, created by Client::torrent
+///
+/// The handle owns a clone of the client, sharing its session, so it can be moved into a
+/// spawned task while the client keeps being used.
+#[derive(Debug, Clone)]
+pub struct TorrentHandle {
+    client: Client,
+    hash: String,
+}
+
+impl Client {
+    /// Get a handle to the torrent with the given hash
+    pub fn torrent(&self, hash: &str) -> TorrentHandle {
+        TorrentHandle {
+            client: self.clone(),
+            hash: hash.to_string(),
+        }
+    }
+}
+
+impl TorrentHandle {
+    /// Torrent hash
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Validated torrent hash, see InfoHash
+    pub fn info_hash(&self) -> Result<InfoHash, Error> {
+        self.hash.parse()
+    }
+
+    /// Get torrent generic properties
+    pub async fn properties(&mut self) -> Result<Option<TorrentProperties>, Error> {
+        self.client.get_torrent_properties(&self.hash).await
+    }
+
+    /// Get torrent contents
+    pub async fn files(&mut self) -> Result<Vec<File>, Error> {
+        self.client.get_torrent_contents(&self.hash, "").await
+    }
+
+    /// Get torrent trackers
+    pub async fn trackers(&mut self) -> Result<Vec<Tracker>, Error> {
+        self.client.get_torrent_trackers(&self.hash).await
+    }
+
+    /// Get torrent web seeds
+    pub async fn webseeds(&mut self) -> Result<Vec<Webseed>, Error> {
+        self.client.get_torrent_webseeds(&self.hash).await
+    }
+
+    /// Get torrent pieces' states
+    pub async fn piece_states(&mut self) -> Result<Vec<PieceState>, Error> {
+        self.client.get_piece_states(&self.hash).await
+    }
+
+    /// Pause the torrent
+    pub async fn pause(&mut self) -> Result<(), Error> {
+        self.client.pause_torrent(vec![self.hash.as_str()]).await
+    }
+
+    /// Resume the torrent
+    pub async fn resume(&mut self) -> Result<(), Error> {
+        self.client.resume_torrent(vec![self.hash.as_str()]).await
+    }
+
+    /// Recheck the torrent
+    pub async fn recheck(&mut self) -> Result<(), Error> {
+        self.client.recheck_torrent(vec![self.hash.as_str()]).await
+    }
+
+    /// Reannounce the torrent
+    pub async fn reannounce(&mut self) -> Result<(), Error> {
+        self.client
+            .reannounce_torrent(vec![self.hash.as_str()])
+            .await
+    }
+
+    /// Delete the torrent, and its downloaded data if delete_files is true
+    pub async fn delete(mut self, delete_files: bool) -> Result<(), Error> {
+        self.client
+            .delete_torrent(vec![self.hash.as_str()], delete_files)
+            .await
+    }
+
+    /// Set the torrent category
+    pub async fn set_category(&mut self, category: &str) -> Result<(), Error> {
+        self.client
+            .set_category(vec![self.hash.as_str()], category)
+            .await
+    }
+
+    /// Add tags to the torrent
+    pub async fn add_tags(&mut self, tags: Vec<&str>) -> Result<(), Error> {
+        self.client.add_tags(vec![self.hash.as_str()], tags).await
+    }
+
+    /// Set the torrent download and upload speed limits (bytes/s)
+    pub async fn set_limits(&mut self, dl_limit: i64, up_limit: i64) -> Result<(), Error> {
+        self.client
+            .set_torrent_download_limit(vec![self.hash.as_str()], dl_limit)
+            .await?;
+        self.client
+            .set_torrent_upload_limit(vec![self.hash.as_str()], up_limit)
+            .await
+    }
+
+    /// Add peers to the torrent
+    pub async fn add_peers(&mut self, peers: &[PeerAddr]) -> Result<(), Error> {
+        self.client.add_peers(vec![self.hash.as_str()], peers).await
+    }
+}
@@ -0,0 +1,118 @@
+// Hash
+//
+// Info hash of a torrent validated once at construction, so a truncated or mistyped hash is
+// caught where it is parsed instead of failing later as an unknown torrent.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    meta::{base32, hex},
+    torrents::{is_info_hash, torrent_id, AddedTorrent, Torrent, TorrentProperties},
+    Error,
+};
+
+/// SHA1 (v1) or SHA256 (v2) info hash, stored as lowercase hex
+///
+/// Parsed from 40 or 64 hex characters, or 32 base32 characters as in the btih of magnet
+/// links. InfoHash derefs to str, so it can be passed as `&hash` to the methods taking a hash,
+/// and implements `AsRef<str>`, so a list of them can be passed to the methods taking hashes.
+/// It (de)serializes as a string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InfoHash(String);
+
+impl InfoHash {
+    /// Hex info hash
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// ID of the torrent in qBittorrent, the hash truncated to 40 characters for v2 hashes
+    pub fn id(&self) -> &str {
+        torrent_id(&self.0)
+    }
+
+    /// True if this is a SHA256 (v2) info hash
+    pub fn is_v2(&self) -> bool {
+        self.0.len() == 64
+    }
+}
+
+impl FromStr for InfoHash {
+    type Err = Error;
+
+    fn from_str(hash: &str) -> Result<Self, Self::Err> {
+        let trimmed = hash.trim();
+        if is_info_hash(trimmed) {
+            return Ok(InfoHash(trimmed.to_lowercase()));
+        }
+        match base32(trimmed) {
+            Some(bytes) if trimmed.len() == 32 => Ok(InfoHash(hex(&bytes))),
+            _ => Err(Error::InvalidInfoHash(hash.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for InfoHash {
+    type Error = Error;
+
+    fn try_from(hash: String) -> Result<Self, Self::Error> {
+        hash.parse()
+    }
+}
+
+impl From<InfoHash> for String {
+    fn from(hash: InfoHash) -> Self {
+        hash.0
+    }
+}
+
+impl fmt::Display for InfoHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Deref for InfoHash {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InfoHash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Torrent {
+    /// Info hash of the ID of the torrent, see Torrent::id
+    pub fn info_hash(&self) -> Option<InfoHash> {
+        self.id()?.parse().ok()
+    }
+}
+
+impl TorrentProperties {
+    /// SHA1 info hash, None for v2 only torrents and before qBittorrent 4.4
+    pub fn info_hash_v1(&self) -> Option<InfoHash> {
+        self.infohash_v1.as_deref()?.parse().ok()
+    }
+
+    /// SHA256 info hash, None for v1 only torrents and before qBittorrent 4.4
+    pub fn info_hash_v2(&self) -> Option<InfoHash> {
+        self.infohash_v2.as_deref()?.parse().ok()
+    }
+}
+
+impl AddedTorrent {
+    /// Info hash of the ID of the added torrent
+    pub fn info_hash(&self) -> Result<InfoHash, Error> {
+        self.hash.parse()
+    }
+}
@@ -0,0 +1,115 @@
+// Health
+//
+// Tracker health aggregated over the trackers of all torrents.
+
+use std::collections::BTreeMap;
+
+use futures_util::{pin_mut, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    torrents::{GetTorrentList, Tracker, TrackerStatus},
+    Client, Error,
+};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Status counts of one tracker URL over the torrents using it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackerHealth {
+    /// Number of torrents for which the tracker is working
+    pub working: usize,
+    /// Number of torrents for which the tracker is not working
+    pub not_working: usize,
+    /// Number of torrents for which the tracker is not contacted yet or updating
+    pub pending: usize,
+    /// Messages of the tracker when not working, with the number of torrents
+    pub messages: BTreeMap<String, usize>,
+}
+
+/// Tracker health over all torrents, built by Client::tracker_report
+#[derive(Debug, Default)]
+pub struct TrackerReport {
+    /// Health of each tracker URL, DHT, PeX and LSD excluded
+    pub trackers: BTreeMap<String, TrackerHealth>,
+    /// Hashes of the torrents whose trackers are all not working
+    pub dead_torrents: Vec<String>,
+    /// Torrents whose trackers could not be fetched, with the error
+    pub failed: Vec<(String, Error)>,
+}
+
+impl TrackerReport {
+    /// Add the trackers of a torrent to the report
+    pub fn add_torrent(&mut self, hash: &str, trackers: &[Tracker]) {
+        let mut urls = 0;
+        let mut dead = 0;
+        for tracker in trackers.iter().filter(|tracker| !tracker.is_special()) {
+            urls += 1;
+            let health = self.trackers.entry(tracker.url.clone()).or_default();
+            match tracker.status {
+                TrackerStatus::Working => health.working += 1,
+                TrackerStatus::NotWorking => {
+                    dead += 1;
+                    health.not_working += 1;
+                    if !tracker.msg.is_empty() {
+                        *health.messages.entry(tracker.msg.clone()).or_default() += 1;
+                    }
+                }
+                TrackerStatus::NotContacted | TrackerStatus::Updating => health.pending += 1,
+                TrackerStatus::Disabled | TrackerStatus::Unknown(_) => {}
+            }
+        }
+        if urls > 0 && dead == urls {
+            self.dead_torrents.push(hash.to_string());
+        }
+    }
+
+    /// Tracker URLs not working for any torrent
+    pub fn dead_trackers(&self) -> Vec<&str> {
+        self.trackers
+            .iter()
+            .filter(|(_, health)| health.not_working > 0 && health.working == 0)
+            .map(|(url, _)| url.as_str())
+            .collect()
+    }
+}
+
+impl Client {
+    /// Get the tracker health of all torrents, fetching up to 8 tracker lists concurrently
+    pub async fn tracker_report(&mut self) -> Result<TrackerReport, Error> {
+        self.tracker_report_with_concurrency(DEFAULT_CONCURRENCY)
+            .await
+    }
+
+    /// Get the tracker health of all torrents, fetching up to concurrency tracker lists at once
+    ///
+    /// Tracker lists are aggregated as they arrive, so only the report is kept in memory. A
+    /// torrent whose trackers can't be fetched is reported in failed without stopping the rest.
+    ///
+    pub async fn tracker_report_with_concurrency(
+        &mut self,
+        concurrency: usize,
+    ) -> Result<TrackerReport, Error> {
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        let hashes: Vec<String> = torrents
+            .iter()
+            .filter_map(|torrent| torrent.id().map(str::to_string))
+            .collect();
+        let results = self
+            .concurrent(concurrency)
+            .stream(hashes, |mut client, hash| async move {
+                client.get_torrent_trackers(&hash).await
+            });
+        pin_mut!(results);
+
+        let mut report = TrackerReport::default();
+        while let Some((hash, result)) = results.next().await {
+            match result {
+                Ok(trackers) => report.add_torrent(&hash, &trackers),
+                Err(err) => report.failed.push((hash, err)),
+            }
+        }
+        report.dead_torrents.sort();
+        Ok(report)
+    }
+}
@@ -0,0 +1,207 @@
+// Intake
+//
+// HTTP listener adding the torrents POSTed by other tools (autobrr, Jackett, scripts), enabled
+// by the intake feature.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::timeout;
+
+use crate::{
+    dedup::AddOutcome,
+    http::{read_request, write_response, HttpRequest},
+    torrents::AddTorrent,
+    Client, Error,
+};
+
+/// Default maximum size of a request body (10 MiB)
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// Default time allowed to send a whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Source of torrents, e.g. one per tool or indexer
+#[derive(Debug, Clone, Default)]
+pub struct IntakeSource {
+    /// Fields applied to every torrent of the source: category, tags, savepath, limits...
+    /// urls and torrents are replaced by the payload.
+    pub defaults: AddTorrent,
+    /// Token required in the X-Intake-Token header. It is not accepted in the URL, where it would
+    /// end up in proxy logs and shell history.
+    pub token: Option<String>,
+}
+
+impl IntakeSource {
+    pub fn new(defaults: AddTorrent) -> Self {
+        Self {
+            defaults,
+            token: None,
+        }
+    }
+
+    /// Require a token
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+}
+
+/// HTTP listener forwarding POSTed magnet links, URLs and .torrent files to qBittorrent
+///
+/// Each source is served at POST /add/{name}. The payload is either a .torrent file with
+/// content type application/x-bittorrent, a form with a url (or urls) field, or a plain text
+/// body with the URL. Torrents already in qBittorrent are not added again (see
+/// Client::add_torrent_dedup).
+///
+/// Responses are JSON: 200 with status "added" or "exists", hash and name, otherwise an error
+/// message with 400 (no payload), 401 (wrong token), 404 (unknown source), 405 (not POST),
+/// 408 (request not received in time), 413 (headers or body too large), 415 (invalid torrent)
+/// or 502 (qBittorrent request failed).
+#[derive(Debug)]
+pub struct IntakeServer {
+    client: Client,
+    sources: HashMap<String, IntakeSource>,
+    max_body: usize,
+    read_timeout: Duration,
+}
+
+impl IntakeServer {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            sources: HashMap::new(),
+            max_body: MAX_BODY,
+            read_timeout: READ_TIMEOUT,
+        }
+    }
+
+    /// Serve a source at /add/{name}
+    pub fn source(mut self, name: &str, source: IntakeSource) -> Self {
+        self.sources.insert(name.to_string(), source);
+        self
+    }
+
+    /// Maximum size of a request body in bytes (default: 10 MiB)
+    pub fn max_body(mut self, bytes: usize) -> Self {
+        self.max_body = bytes;
+        self
+    }
+
+    /// Time allowed to a client to send its whole request (default: 30s)
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Listen on addr and serve requests until an accept error
+    pub async fn run(self, addr: impl ToSocketAddrs) -> Result<(), Error> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener).await
+    }
+
+    /// Serve requests of a bound listener until an accept error
+    pub async fn serve(self, listener: TcpListener) -> Result<(), Error> {
+        let server = Arc::new(self);
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let server = server.clone();
+            tokio::spawn(async move {
+                if let Err(err) = server.handle(stream).await {
+                    ::log::debug!("intake connection error from {peer}: {err}");
+                }
+            });
+        }
+    }
+
+    async fn handle(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let read = timeout(self.read_timeout, read_request(&mut stream, self.max_body)).await;
+        let (status, body) = match read {
+            Ok(Ok(Some(request))) => self.answer(request).await,
+            Ok(Ok(None)) => return Ok(()),
+            Ok(Err(err)) if err.kind() == std::io::ErrorKind::InvalidData => {
+                (413, json!({ "error": err.to_string() }))
+            }
+            Ok(Err(err)) => return Err(err),
+            Err(_) => (408, json!({ "error": "request timeout" })),
+        };
+        let headers = [("content-type".to_string(), "application/json".to_string())];
+        let body = serde_json::to_vec(&body).unwrap_or_default();
+        write_response(&mut stream, status, &headers, &body).await
+    }
+
+    async fn answer(&self, request: HttpRequest) -> (u16, serde_json::Value) {
+        let error = |status, message: &str| (status, json!({ "error": message }));
+        let Some(source) = request
+            .path()
+            .strip_prefix("/add/")
+            .and_then(|name| self.sources.get(name))
+        else {
+            return error(404, "unknown source");
+        };
+        if request.method != "POST" {
+            return error(405, "only POST is allowed");
+        }
+        if let Some(token) = &source.token {
+            let sent = request.headers.get("x-intake-token");
+            if !sent.is_some_and(|sent| token_matches(sent, token)) {
+                return error(401, "wrong token");
+            }
+        }
+        let Some((urls, torrents)) = payload(&request) else {
+            return error(400, "no magnet link, URL or .torrent file");
+        };
+
+        let values = AddTorrent {
+            urls,
+            torrents,
+            ..source.defaults.clone()
+        };
+        // Each request adds on its own clone, all of them using the session of self.client
+        let mut client = self.client.clone();
+        match client.add_torrent_dedup(values).await {
+            Ok(AddOutcome::Added(added)) => (
+                200,
+                json!({ "status": "added", "hash": added.hash, "name": added.name }),
+            ),
+            Ok(AddOutcome::AlreadyExists(torrent)) => (
+                200,
+                json!({ "status": "exists", "hash": torrent.hash, "name": torrent.name }),
+            ),
+            Err(Error::NoValidTorrent) => error(415, "invalid torrent"),
+            Err(err) => error(502, &err.to_string()),
+        }
+    }
+}
+
+/// Compare the sent token in constant time, so the response time doesn't reveal how much of it
+/// is right
+fn token_matches(sent: &str, token: &str) -> bool {
+    sent.as_bytes().ct_eq(token.as_bytes()).into()
+}
+
+/// URLs and .torrent file of the request
+fn payload(request: &HttpRequest) -> Option<(String, Vec<u8>)> {
+    let content_type = request
+        .headers
+        .get("content-type")
+        .map(String::as_str)
+        .unwrap_or_default();
+    if content_type.starts_with("application/x-bittorrent") {
+        return (!request.body.is_empty()).then(|| (String::new(), request.body.clone()));
+    }
+    let urls = if content_type.starts_with("application/x-www-form-urlencoded") {
+        url::form_urlencoded::parse(&request.body)
+            .find(|(key, _)| key == "url" || key == "urls")
+            .map(|(_, value)| value.into_owned())
+            .unwrap_or_default()
+    } else {
+        String::from_utf8_lossy(&request.body).into_owned()
+    };
+    let urls = urls.trim();
+    (!urls.is_empty()).then(|| (urls.to_string(), Vec::new()))
+}
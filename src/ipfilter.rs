@@ -0,0 +1,270 @@
+// IP filter
+//
+// Point qBittorrent at an IP filter file, reload it, manage the IPs banned permanently by the
+// banned_ips preference, and write blocklists in the eMule .dat and PeerGuardian .p2p formats
+// it reads.
+
+use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{app::Preferences, Client, Error};
+
+/// Inclusive range of IP addresses of the same family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    pub start: IpAddr,
+    pub end: IpAddr,
+}
+
+impl IpRange {
+    /// Range of a CIDR block, e.g. 10.0.0.0/8 or 2001:db8::/32. An address without prefix
+    /// length is a range of one address.
+    pub fn from_cidr(cidr: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidIpRange(cidr.to_string());
+        let (ip, prefix) = match cidr.trim().split_once('/') {
+            Some((ip, prefix)) => (ip, Some(prefix)),
+            None => (cidr.trim(), None),
+        };
+        let ip: IpAddr = ip.parse().map_err(|_| invalid())?;
+        let bits = if ip.is_ipv4() { 32 } else { 128 };
+        let prefix: u32 = match prefix {
+            Some(prefix) => prefix.parse().map_err(|_| invalid())?,
+            None => bits,
+        };
+        if prefix > bits {
+            return Err(invalid());
+        }
+        Ok(match ip {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+                let start = u32::from(ip) & mask;
+                IpRange {
+                    start: Ipv4Addr::from(start).into(),
+                    end: Ipv4Addr::from(start | !mask).into(),
+                }
+            }
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+                let start = u128::from(ip) & mask;
+                IpRange {
+                    start: Ipv6Addr::from(start).into(),
+                    end: Ipv6Addr::from(start | !mask).into(),
+                }
+            }
+        })
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = Error;
+
+    fn from_str(cidr: &str) -> Result<Self, Self::Err> {
+        Self::from_cidr(cidr)
+    }
+}
+
+/// File format of a blocklist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlocklistFormat {
+    /// eMule ipfilter.dat: "start - end , access level , description"
+    Dat,
+    /// PeerGuardian text: "description:start-end", IPv4 only
+    P2p,
+}
+
+/// Blocked IP ranges with a description
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blocklist {
+    entries: Vec<(IpRange, String)>,
+}
+
+impl Blocklist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block a range
+    pub fn range(mut self, range: IpRange, description: &str) -> Self {
+        self.entries.push((range, description.to_string()));
+        self
+    }
+
+    /// Block a CIDR block, see IpRange::from_cidr
+    pub fn cidr(self, cidr: &str, description: &str) -> Result<Self, Error> {
+        Ok(self.range(IpRange::from_cidr(cidr)?, description))
+    }
+
+    /// Content of the blocklist file
+    ///
+    /// Returns Error::InvalidIpRange for IPv6 ranges in the .p2p format, which doesn't support
+    /// them.
+    ///
+    pub fn render(&self, format: BlocklistFormat) -> Result<String, Error> {
+        let mut out = String::new();
+        for (range, description) in &self.entries {
+            // Separators of the formats can't be part of the description
+            let description = description.replace([',', ':', '\n', '\r'], " ");
+            let _ = match (format, range.start, range.end) {
+                (BlocklistFormat::Dat, IpAddr::V4(start), IpAddr::V4(end)) => writeln!(
+                    out,
+                    "{} - {} , 000 , {description}",
+                    padded(start),
+                    padded(end)
+                ),
+                (BlocklistFormat::Dat, start, end) => {
+                    writeln!(out, "{start} - {end} , 000 , {description}")
+                }
+                (BlocklistFormat::P2p, IpAddr::V4(start), IpAddr::V4(end)) => {
+                    writeln!(out, "{description}:{start}-{end}")
+                }
+                (BlocklistFormat::P2p, start, end) => {
+                    return Err(Error::InvalidIpRange(format!("{start}-{end}")));
+                }
+            };
+        }
+        Ok(out)
+    }
+
+    /// Write the blocklist file, e.g. blocklist.dat or blocklist.p2p to match the format
+    pub async fn write(
+        &self,
+        path: impl AsRef<Path>,
+        format: BlocklistFormat,
+    ) -> Result<(), Error> {
+        tokio::fs::write(path, self.render(format)?).await?;
+        Ok(())
+    }
+}
+
+/// IPv4 address with three digits per octet, as in eMule .dat files
+fn padded(ip: Ipv4Addr) -> String {
+    let [a, b, c, d] = ip.octets();
+    format!("{a:03}.{b:03}.{c:03}.{d:03}")
+}
+
+impl Client {
+    /// Enable the IP filter with the filter file at path on the server
+    ///
+    /// The file must be readable by qBittorrent, .dat, .p2p and .p2b files are supported. If
+    /// trackers is true, the filter is also applied to trackers.
+    ///
+    pub async fn set_ip_filter(&mut self, path: &str, trackers: bool) -> Result<(), Error> {
+        self.set_preferences(Preferences {
+            ip_filter_enabled: Some(true),
+            ip_filter_path: Some(path.to_string()),
+            ip_filter_trackers: Some(trackers),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Disable the IP filter
+    pub async fn disable_ip_filter(&mut self) -> Result<(), Error> {
+        self.set_preferences(Preferences {
+            ip_filter_enabled: Some(false),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Reload the filter file, e.g. after it was rewritten, by disabling and enabling the
+    /// filter
+    ///
+    /// Returns false without changing anything if the filter is disabled.
+    ///
+    pub async fn reload_ip_filter(&mut self) -> Result<bool, Error> {
+        let preferences = self.get_preferences().await?;
+        if preferences.ip_filter_enabled != Some(true) {
+            return Ok(false);
+        }
+        self.disable_ip_filter().await?;
+        self.set_preferences(Preferences {
+            ip_filter_enabled: Some(true),
+            ..Default::default()
+        })
+        .await?;
+        Ok(true)
+    }
+
+    /// IPs banned by the banned_ips preference
+    ///
+    /// Unlike Client::ban_peers, these bans persist across restarts. Lines that are not an IP
+    /// address are skipped.
+    ///
+    pub async fn get_banned_ips(&mut self) -> Result<Vec<IpAddr>, Error> {
+        Ok(self
+            .banned_ip_lines()
+            .await?
+            .iter()
+            .filter_map(|line| match line.parse() {
+                Ok(ip) => Some(ip),
+                Err(_) => {
+                    ::log::debug!("skipping invalid banned IP {line:?}");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Add ip to the banned_ips preference, keeping the other bans
+    ///
+    /// Returns false without changing anything if ip is already banned.
+    ///
+    pub async fn ban_ip(&mut self, ip: IpAddr) -> Result<bool, Error> {
+        let mut lines = self.banned_ip_lines().await?;
+        if lines
+            .iter()
+            .any(|line| line.parse::<IpAddr>().ok() == Some(ip))
+        {
+            return Ok(false);
+        }
+        lines.push(ip.to_string());
+        self.set_banned_ip_lines(&lines).await?;
+        Ok(true)
+    }
+
+    /// Remove ip from the banned_ips preference, keeping the other bans
+    ///
+    /// Returns false without changing anything if ip is not banned.
+    ///
+    pub async fn unban_ip(&mut self, ip: IpAddr) -> Result<bool, Error> {
+        let mut lines = self.banned_ip_lines().await?;
+        let count = lines.len();
+        lines.retain(|line| line.parse::<IpAddr>().ok() != Some(ip));
+        if lines.len() == count {
+            return Ok(false);
+        }
+        self.set_banned_ip_lines(&lines).await?;
+        Ok(true)
+    }
+
+    /// Empty the banned_ips preference
+    pub async fn clear_bans(&mut self) -> Result<(), Error> {
+        self.set_banned_ip_lines(&[]).await
+    }
+
+    /// Non-empty lines of the banned_ips preference
+    async fn banned_ip_lines(&mut self) -> Result<Vec<String>, Error> {
+        let banned_ips = self
+            .get_preferences_uncached()
+            .await?
+            .banned_ips
+            .unwrap_or_default();
+        Ok(banned_ips
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn set_banned_ip_lines(&mut self, lines: &[String]) -> Result<(), Error> {
+        self.set_preferences(Preferences {
+            banned_ips: Some(lines.join("\n")),
+            ..Default::default()
+        })
+        .await
+    }
+}
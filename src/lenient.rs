@@ -0,0 +1,66 @@
+// Lenient
+//
+// Deserializers of numbers that some qBittorrent builds and reverse proxies send as strings,
+// or as floats where integers are expected, e.g. "ratio": "1.25" or "size": 1024.0.
+
+use serde::{de::Error as _, Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Integer of a number or a numeric string, floats are truncated
+fn to_i64(value: &Value) -> Option<i64> {
+    let float = |float: f64| float.is_finite().then_some(float as i64);
+    match value {
+        Value::Number(number) => number.as_i64().or_else(|| number.as_f64().and_then(float)),
+        Value::String(text) => {
+            let text = text.trim();
+            text.parse()
+                .ok()
+                .or_else(|| text.parse().ok().and_then(float))
+        }
+        _ => None,
+    }
+}
+
+fn to_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) fn i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    to_i64(&value).ok_or_else(|| D::Error::custom(format!("invalid integer: {value}")))
+}
+
+pub(crate) fn f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    to_f64(&value).ok_or_else(|| D::Error::custom(format!("invalid number: {value}")))
+}
+
+/// None for null and values that aren't numbers
+pub(crate) fn option_i64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Value>::deserialize(deserializer)?
+        .as_ref()
+        .and_then(to_i64))
+}
+
+/// None for null and values that aren't numbers
+pub(crate) fn option_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Value>::deserialize(deserializer)?
+        .as_ref()
+        .and_then(to_f64))
+}
@@ -1,13 +1,106 @@
 pub mod app;
 pub mod auth;
+pub mod banning;
+pub mod batch;
+mod bencode;
+pub mod cache;
+pub mod categories;
+mod cert;
 pub mod client;
+pub mod concurrent;
+pub mod dedup;
+mod enums;
 pub mod error;
+pub mod events;
+pub mod form;
+pub mod format;
+pub mod handle;
+pub mod hash;
+pub mod health;
+#[cfg(any(feature = "intake", feature = "test-util"))]
+mod http;
+#[cfg(feature = "intake")]
+pub mod intake;
+pub mod ipfilter;
+mod lenient;
+pub mod limits;
 pub mod log;
+pub mod magnet;
+pub mod maintenance;
+pub mod meta;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
+#[cfg(feature = "test-util")]
+pub mod mock;
+pub mod peers;
+pub mod pieces;
+pub mod policy;
+pub mod prefs;
+pub mod probe;
+pub mod query;
+pub mod rates;
+pub mod reannounce;
+#[cfg(any(feature = "record", feature = "test-util"))]
+pub mod record;
+pub mod relocate;
+pub mod rename;
+pub mod report;
 pub mod request;
+pub mod reseed;
+pub mod resolve;
 pub mod response;
+pub mod retry;
+pub mod schedule;
+pub mod selection;
+pub mod stats;
 pub mod sync;
+pub mod tagging;
+mod template;
+pub mod throttle;
+pub mod timing;
+pub mod torrentcreator;
 pub mod torrents;
 pub mod transfer;
+pub mod verify;
+pub mod wait;
+pub mod watch;
 
-pub use crate::client::Client;
+pub use crate::client::{Client, ClientBuilder};
 pub use crate::error::Error;
+pub use tokio_util::sync::CancellationToken;
+
+// Public types are Send + Sync, so clients and results can be moved to and shared between
+// tasks and threads
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+    send_sync::<Client>();
+    send_sync::<ClientBuilder>();
+    send_sync::<Error>();
+    send_sync::<app::Preferences>();
+    send_sync::<banning::PeerBanner>();
+    send_sync::<batch::Batch>();
+    send_sync::<concurrent::Concurrent>();
+    send_sync::<events::Event>();
+    send_sync::<handle::TorrentHandle>();
+    send_sync::<hash::InfoHash>();
+    send_sync::<limits::SpeedLimitGuard>();
+    send_sync::<magnet::Magnet>();
+    send_sync::<meta::TorrentMeta>();
+    send_sync::<peers::PeerMonitor>();
+    send_sync::<pieces::PieceOverview>();
+    send_sync::<policy::PolicyEngine>();
+    send_sync::<probe::ServerProbe>();
+    send_sync::<query::TorrentQuery>();
+    send_sync::<rates::RateHistory>();
+    send_sync::<retry::RetryPolicy>();
+    send_sync::<schedule::Scheduler>();
+    send_sync::<selection::FileSelector>();
+    send_sync::<stats::SessionStats>();
+    send_sync::<sync::SyncSession>();
+    send_sync::<sync::PeerSession>();
+    send_sync::<timing::ClientStats>();
+    send_sync::<torrents::AddTorrent>();
+    send_sync::<torrents::Torrent>();
+    send_sync::<torrents::TorrentProperties>();
+};
@@ -0,0 +1,111 @@
+// Limits
+//
+// Temporarily applied global speed limits, restored afterwards.
+
+use std::future::Future;
+use std::panic::{resume_unwind, AssertUnwindSafe};
+
+use futures_util::FutureExt;
+
+use crate::{Client, Error};
+
+/// Global speed limits applied by Client::apply_temporary_limits
+///
+/// Call restore to set the original limits back. If the guard is dropped without it, e.g. on an
+/// early return or a panic, the original limits are restored in a task spawned on the current
+/// tokio runtime.
+#[derive(Debug)]
+pub struct SpeedLimitGuard {
+    client: Client,
+    download: i64,
+    upload: i64,
+    restored: bool,
+}
+
+impl SpeedLimitGuard {
+    /// Original global download and upload limits (bytes/s)
+    pub fn original_limits(&self) -> (i64, i64) {
+        (self.download, self.upload)
+    }
+
+    /// Restore the original global limits
+    pub async fn restore(mut self) -> Result<(), Error> {
+        self.restored = true;
+        self.client.set_download_limit(self.download).await?;
+        self.client.set_upload_limit(self.upload).await
+    }
+}
+
+impl Drop for SpeedLimitGuard {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            ::log::debug!("speed limits not restored: no tokio runtime");
+            return;
+        };
+        let guard = SpeedLimitGuard {
+            client: self.client.clone(),
+            download: self.download,
+            upload: self.upload,
+            restored: false,
+        };
+        self.restored = true;
+        handle.spawn(async move {
+            if let Err(err) = guard.restore().await {
+                ::log::debug!("restore speed limits failed: {err}");
+            }
+        });
+    }
+}
+
+impl Client {
+    /// Apply global download and upload limits (bytes/s, 0 for unlimited) until the returned
+    /// guard is restored or dropped
+    pub async fn apply_temporary_limits(
+        &mut self,
+        download: i64,
+        upload: i64,
+    ) -> Result<SpeedLimitGuard, Error> {
+        let guard = SpeedLimitGuard {
+            client: self.clone(),
+            download: self.get_download_limit().await?,
+            upload: self.get_upload_limit().await?,
+            restored: false,
+        };
+        let applied = match self.set_download_limit(download).await {
+            Ok(()) => self.set_upload_limit(upload).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = applied {
+            if let Err(restore_err) = guard.restore().await {
+                ::log::debug!("restore speed limits failed: {restore_err}");
+            }
+            return Err(err);
+        }
+        Ok(guard)
+    }
+
+    /// Run fut with temporary global download and upload limits (bytes/s, 0 for unlimited)
+    ///
+    /// The original limits are restored when fut completes, even if it panics. The panic is
+    /// resumed after the limits are restored.
+    pub async fn with_temporary_limits<F, T>(
+        &mut self,
+        download: i64,
+        upload: i64,
+        fut: F,
+    ) -> Result<T, Error>
+    where
+        F: Future<Output = T>,
+    {
+        let guard = self.apply_temporary_limits(download, upload).await?;
+        let result = AssertUnwindSafe(fut).catch_unwind().await;
+        let restored = guard.restore().await;
+        match result {
+            Ok(value) => restored.map(|()| value),
+            Err(panic) => resume_unwind(panic),
+        }
+    }
+}
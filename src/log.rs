@@ -1,43 +1,108 @@
 /// Log
 ///
 /// All Log API methods are under "log", e.g.: /api/v2/log/methodName.
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bitflags::bitflags;
+use futures_util::{
+    future::BoxFuture,
+    stream::{self, BoxStream},
+    FutureExt, Stream, StreamExt,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use tokio::time::sleep;
 
 use crate::{
+    enums::int_enum,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
+    response::decode,
     Client, Error,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+bitflags! {
+    /// Types of log messages to include
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LogLevels: u8 {
+        const NORMAL = 1;
+        const INFO = 2;
+        const WARNING = 4;
+        const CRITICAL = 8;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "GetLogParams", into = "GetLogParams")]
 pub struct GetLog {
-    /// Include normal messages (default: true)
-    pub normal: bool,
-    /// Include info messages (default: true)
-    pub info: bool,
-    /// Include warning messages (default: true)
-    pub warning: bool,
-    /// Include critical messages (default: true)
-    pub critical: bool,
+    /// Types of messages to include (default: all)
+    pub levels: LogLevels,
     /// Exclude messages with "message id" <= last_known_id (default: -1)
     pub last_known_id: i64,
 }
 
+impl GetLog {
+    /// Messages of all types newer than the message with the given id
+    pub fn since(last_known_id: i64) -> Self {
+        Self {
+            last_known_id,
+            ..Default::default()
+        }
+    }
+
+    /// Messages of the given types
+    pub fn levels(mut self, levels: LogLevels) -> Self {
+        self.levels = levels;
+        self
+    }
+}
+
 impl Default for GetLog {
     fn default() -> Self {
         Self {
-            normal: true,
-            info: true,
-            warning: true,
-            critical: true,
+            levels: LogLevels::all(),
             last_known_id: -1,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Query parameters of log/main
+#[derive(Serialize, Deserialize)]
+struct GetLogParams {
+    normal: bool,
+    info: bool,
+    warning: bool,
+    critical: bool,
+    last_known_id: i64,
+}
+
+impl From<GetLog> for GetLogParams {
+    fn from(values: GetLog) -> Self {
+        Self {
+            normal: values.levels.contains(LogLevels::NORMAL),
+            info: values.levels.contains(LogLevels::INFO),
+            warning: values.levels.contains(LogLevels::WARNING),
+            critical: values.levels.contains(LogLevels::CRITICAL),
+            last_known_id: values.last_known_id,
+        }
+    }
+}
+
+impl From<GetLogParams> for GetLog {
+    fn from(params: GetLogParams) -> Self {
+        let mut levels = LogLevels::empty();
+        levels.set(LogLevels::NORMAL, params.normal);
+        levels.set(LogLevels::INFO, params.info);
+        levels.set(LogLevels::WARNING, params.warning);
+        levels.set(LogLevels::CRITICAL, params.critical);
+        Self {
+            levels,
+            last_known_id: params.last_known_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LogEntry {
     /// ID of the message
     pub id: i64,
@@ -50,16 +115,16 @@ pub struct LogEntry {
     pub kind: LogType,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum LogType {
-    NORMAL = 1,
-    INFO = 2,
-    WARNING = 4,
-    CRITICAL = 8,
+int_enum! {
+    pub enum LogType {
+        NORMAL = 1,
+        INFO = 2,
+        WARNING = 4,
+        CRITICAL = 8,
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GetPeerLog {
     /// Exclude messages with "message id" <= last_known_id (default: -1)
     pub last_known_id: i64,
@@ -71,7 +136,7 @@ impl Default for GetPeerLog {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LogPeerEntry {
     /// ID of the peer
     pub id: i64,
@@ -85,12 +150,20 @@ pub struct LogPeerEntry {
     pub reason: String,
 }
 
+impl From<LogType> for LogLevels {
+    fn from(kind: LogType) -> Self {
+        LogLevels::from_bits_truncate(i64::from(kind) as u8)
+    }
+}
+
 impl Client {
     /// Get log
     ///
     /// Name: main
     ///
     /// Parameters:
+    /// GetLog, the levels are sent as the normal, info, warning and critical parameters
+    ///
     /// Parameter  Type  Description
     /// normal  bool  Include normal messages (default: true)
     /// info  bool  Include info messages (default: true)
@@ -117,10 +190,7 @@ impl Client {
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
     }
 
     /// Get peer log
@@ -146,9 +216,102 @@ impl Client {
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
+        self.check_default_status(&response, decode(request.method, &response)?)
+    }
+}
+
+impl Client {
+    /// Tail the log
+    ///
+    /// Polls log/main every poll_interval and yields only the entries newer than the last one
+    /// seen, starting after filters.last_known_id. Request errors are yielded and polling
+    /// continues on the next interval.
+    ///
+    pub fn tail_log(
+        &mut self,
+        filters: GetLog,
+        poll_interval: Duration,
+    ) -> BoxStream<'_, Result<LogEntry, Error>> {
+        let last_known_id = filters.last_known_id;
+        tail(
+            self,
+            last_known_id,
+            poll_interval,
+            move |client, last_known_id| {
+                let filters = GetLog {
+                    last_known_id,
+                    ..filters.clone()
+                };
+                async move { client.get_log(filters).await }.boxed()
+            },
+            |entry: &LogEntry| entry.id,
         )
+        .boxed()
     }
+
+    /// Tail the peer log
+    ///
+    /// Polls log/peers every poll_interval and yields only the entries newer than the last one
+    /// seen, starting after filters.last_known_id. Request errors are yielded and polling
+    /// continues on the next interval.
+    ///
+    pub fn tail_peer_log(
+        &mut self,
+        filters: GetPeerLog,
+        poll_interval: Duration,
+    ) -> BoxStream<'_, Result<LogPeerEntry, Error>> {
+        tail(
+            self,
+            filters.last_known_id,
+            poll_interval,
+            |client, last_known_id| {
+                async move { client.get_peer_log(GetPeerLog { last_known_id }).await }.boxed()
+            },
+            |entry: &LogPeerEntry| entry.id,
+        )
+        .boxed()
+    }
+}
+
+/// Entries of a log polled every poll_interval, after last_known_id
+///
+/// fetch requests the entries newer than an id, id is the id of an entry. Request errors are
+/// yielded and polling continues on the next interval.
+fn tail<'a, T, F>(
+    client: &'a mut Client,
+    last_known_id: i64,
+    poll_interval: Duration,
+    fetch: F,
+    id: fn(&T) -> i64,
+) -> impl Stream<Item = Result<T, Error>> + 'a
+where
+    T: 'a,
+    F: for<'c> Fn(&'c mut Client, i64) -> BoxFuture<'c, Result<Vec<T>, Error>> + 'a,
+{
+    let state = (client, fetch, last_known_id, VecDeque::new(), false);
+    stream::unfold(
+        state,
+        move |(client, fetch, mut last_known_id, mut pending, mut polled)| async move {
+            loop {
+                if let Some(entry) = pending.pop_front() {
+                    return Some((Ok(entry), (client, fetch, last_known_id, pending, polled)));
+                }
+                if polled {
+                    sleep(poll_interval).await;
+                }
+                polled = true;
+                match fetch(client, last_known_id).await {
+                    Ok(entries) => {
+                        if let Some(last) = entries.iter().map(id).max() {
+                            last_known_id = last_known_id.max(last);
+                        }
+                        pending.extend(entries);
+                    }
+                    Err(err) => {
+                        return Some((Err(err), (client, fetch, last_known_id, pending, polled)))
+                    }
+                }
+            }
+        },
+    )
 }
@@ -0,0 +1,164 @@
+// Magnet
+//
+// Parse magnet links into their info hashes, name, trackers and web seeds, edit them, and
+// write them back as URIs.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{form::encode, hash::InfoHash, meta::TorrentMeta, torrents::AddTorrent, Error};
+
+/// Magnet link
+///
+/// Parsed with str::parse, which returns Error::InvalidMagnet if the URI has no valid btih or
+/// btmh info hash. Parameters other than xt, dn, tr and ws are kept as is and written back
+/// after them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Magnet {
+    /// SHA1 info hash of the btih exact topic
+    pub info_hash_v1: Option<InfoHash>,
+    /// SHA256 info hash of the btmh exact topic
+    pub info_hash_v2: Option<InfoHash>,
+    /// Display name (dn)
+    pub name: Option<String>,
+    /// Tracker URLs (tr)
+    pub trackers: Vec<String>,
+    /// Web seed URLs (ws)
+    pub webseeds: Vec<String>,
+    /// Other parameters, in order
+    pub params: Vec<(String, String)>,
+}
+
+impl Magnet {
+    /// Magnet link of an info hash, a btih for SHA1 and a btmh for SHA256 hashes
+    pub fn new(info_hash: InfoHash) -> Self {
+        if info_hash.is_v2() {
+            Magnet {
+                info_hash_v2: Some(info_hash),
+                ..Default::default()
+            }
+        } else {
+            Magnet {
+                info_hash_v1: Some(info_hash),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Info hash of the torrent, the SHA1 one if both are present
+    pub fn info_hash(&self) -> Option<&InfoHash> {
+        self.info_hash_v1.as_ref().or(self.info_hash_v2.as_ref())
+    }
+
+    /// ID of the torrent in qBittorrent, see InfoHash::id
+    pub fn id(&self) -> Option<&str> {
+        self.info_hash().map(InfoHash::id)
+    }
+
+    /// Append a tracker unless it's already listed
+    pub fn add_tracker(&mut self, tracker: &str) {
+        if !self.trackers.iter().any(|known| known == tracker) {
+            self.trackers.push(tracker.to_string());
+        }
+    }
+
+    /// Append trackers unless they are already listed
+    pub fn add_trackers<I, S>(&mut self, trackers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for tracker in trackers {
+            self.add_tracker(tracker.as_ref());
+        }
+    }
+}
+
+impl FromStr for Magnet {
+    type Err = Error;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidMagnet(uri.to_string());
+        let query = uri.trim().strip_prefix("magnet:?").ok_or_else(invalid)?;
+        let mut magnet = Magnet::default();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let value = value.into_owned();
+            match key.as_ref() {
+                "xt" => {
+                    if let Some(hash) = value.strip_prefix("urn:btih:") {
+                        let hash = hash.parse::<InfoHash>().ok().filter(|hash| !hash.is_v2());
+                        if magnet.info_hash_v1.is_none() && hash.is_some() {
+                            magnet.info_hash_v1 = hash;
+                            continue;
+                        }
+                    } else if let Some(hash) = value.strip_prefix("urn:btmh:1220") {
+                        let hash = hash.parse::<InfoHash>().ok().filter(InfoHash::is_v2);
+                        if magnet.info_hash_v2.is_none() && hash.is_some() {
+                            magnet.info_hash_v2 = hash;
+                            continue;
+                        }
+                    }
+                    magnet.params.push((key.into_owned(), value));
+                }
+                "dn" if magnet.name.is_none() => magnet.name = Some(value),
+                "tr" => magnet.add_tracker(&value),
+                "ws" => magnet.webseeds.push(value),
+                _ => magnet.params.push((key.into_owned(), value)),
+            }
+        }
+        magnet.info_hash().ok_or_else(invalid)?;
+        Ok(magnet)
+    }
+}
+
+impl fmt::Display for Magnet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut params = Vec::new();
+        if let Some(hash) = &self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{hash}"));
+        }
+        if let Some(hash) = &self.info_hash_v2 {
+            params.push(format!("xt=urn:btmh:1220{hash}"));
+        }
+        params.extend(self.name.iter().map(|name| format!("dn={}", encode(name))));
+        params.extend(
+            self.trackers
+                .iter()
+                .map(|tracker| format!("tr={}", encode(tracker))),
+        );
+        params.extend(
+            self.webseeds
+                .iter()
+                .map(|webseed| format!("ws={}", encode(webseed))),
+        );
+        params.extend(
+            self.params
+                .iter()
+                .map(|(key, value)| format!("{}={}", encode(key), encode(value))),
+        );
+        write!(f, "magnet:?{}", params.join("&"))
+    }
+}
+
+impl From<&TorrentMeta> for Magnet {
+    fn from(meta: &TorrentMeta) -> Self {
+        let hash = |hash: &Option<String>| hash.as_deref().and_then(|hash| hash.parse().ok());
+        Magnet {
+            info_hash_v1: hash(&meta.info_hash_v1),
+            info_hash_v2: hash(&meta.info_hash_v2),
+            name: Some(meta.name.clone()),
+            trackers: meta.trackers.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl AddTorrent {
+    /// Add magnet links
+    pub fn from_magnets(magnets: Vec<Magnet>) -> Self {
+        AddTorrent {
+            magnets,
+            ..Default::default()
+        }
+    }
+}
@@ -2,26 +2,14 @@
 
 // use serde::{Deserialize, Serialize};
 // use serde_json::json;
-// use crate::sync::GetPeersData;
+// use rqa::sync::GetPeersData;
 use tokio::runtime::Runtime;
 use tokio::time::{sleep, Duration};
 
-use client::Client;
-use error::Error;
+use rqa::{torrents, Client, Error};
 
 // use torrent::{TorrentAddArgs, TorrentGetArgs, TorrentRemoveArgs};
 
-pub mod app;
-pub mod auth;
-pub mod client;
-pub mod error;
-pub mod log;
-pub mod request;
-pub mod response;
-pub mod sync;
-pub mod torrents;
-pub mod transfer;
-
 async fn run() -> Result<(), Error> {
     let uri = dotenv::var("QAPI_TARGET").expect("not set QAPI_TARGET");
     let mut client = Client::new(&uri)?;
@@ -37,7 +25,7 @@ async fn run() -> Result<(), Error> {
     let urls = "magnet:?xt=urn:btih:dc05fd2481d6ca52f767183c70ac383e831f4ed1&dn=rutor.info_The+Sims+4%3A+Deluxe+Edition+%5Bv+1.91.186.1030+%2F+1.91.186.1530+%2B+DLCs%5D+%282014%29+PC+%7C+RePack+от+Chovka&tr=udp://opentor.net:6969&tr=http://retracker.local/announce".to_string();
     let category = Some("games".to_string());
 
-    let v: crate::torrents::AddTorrent = torrents::AddTorrent {
+    let v: torrents::AddTorrent = torrents::AddTorrent {
         urls,
         category,
         ..Default::default()
@@ -57,7 +45,7 @@ async fn run() -> Result<(), Error> {
     // dbg!(client.get_download_limit().await?);
     // dbg!(client.toggle_alt_speed().await?);
 
-    // let req = crate::sync::GetPeersData {
+    // let req = rqa::sync::GetPeersData {
     //     rid: 45,
     //     hash: "14061948332125cc81b0c7466d2bd33ee0f26f46".to_string(),
     // };
@@ -94,7 +82,7 @@ async fn run() -> Result<(), Error> {
 }
 
 fn main() {
-    dotenv::dotenv().ok().unwrap();
+    dotenv::dotenv().expect("cannot load .env");
     env_logger::init();
 
     let rt = Runtime::new().unwrap();
@@ -0,0 +1,140 @@
+// Maintenance
+//
+// Find errored, stalled and unregistered torrents for cleanup tooling.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::{pin_mut, StreamExt};
+
+use crate::{
+    torrents::{GetTorrentList, State, Torrent},
+    Client, Error,
+};
+
+const CONCURRENCY: usize = 8;
+
+/// Tracker messages commonly sent for torrents deleted from the tracker
+pub const UNREGISTERED_PATTERNS: [&str; 5] = [
+    "unregistered torrent",
+    "torrent not registered",
+    "torrent not found",
+    "unknown torrent",
+    "torrent has been deleted",
+];
+
+/// Why a torrent was found
+#[derive(Debug, Clone, PartialEq)]
+pub enum FindingReason {
+    /// The torrent is in the error or missingFiles state
+    Errored,
+    /// The torrent is stalled and had no activity for this long
+    Stalled { idle: Duration },
+    /// A tracker answered with a message matching an unregistered pattern
+    Unregistered { tracker: String, message: String },
+}
+
+/// Torrent found by a maintenance query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Torrent hash
+    pub hash: String,
+    /// Torrent name
+    pub name: String,
+    /// Torrent state
+    pub state: String,
+    pub reason: FindingReason,
+}
+
+impl Finding {
+    fn new(torrent: &Torrent, reason: FindingReason) -> Option<Self> {
+        Some(Self {
+            hash: torrent.id()?.to_string(),
+            name: torrent.name.clone(),
+            state: torrent.state.clone(),
+            reason,
+        })
+    }
+}
+
+/// Current Unix time (seconds)
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+impl Client {
+    /// Find torrents in the error or missingFiles state
+    pub async fn find_errored(&mut self) -> Result<Vec<Finding>, Error> {
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        Ok(torrents
+            .iter()
+            .filter(|torrent| torrent.state().is_errored())
+            .filter_map(|torrent| Finding::new(torrent, FindingReason::Errored))
+            .collect())
+    }
+
+    /// Find stalled downloads (stalledDL, metaDL or forcedMetaDL) without activity for at least min_age
+    ///
+    /// Torrents that never had activity are measured from the time they were added.
+    ///
+    pub async fn find_stalled(&mut self, min_age: Duration) -> Result<Vec<Finding>, Error> {
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        let now = now();
+        Ok(torrents
+            .iter()
+            .filter(|torrent| {
+                let state = torrent.state();
+                state == State::StalledDL || state.is_fetching_metadata()
+            })
+            .filter_map(|torrent| {
+                let since = if torrent.last_activity > 0 {
+                    torrent.last_activity
+                } else {
+                    torrent.added_on
+                };
+                let idle = Duration::from_secs((now - since).max(0) as u64);
+                if idle < min_age {
+                    return None;
+                }
+                Finding::new(torrent, FindingReason::Stalled { idle })
+            })
+            .collect())
+    }
+
+    /// Find torrents with a tracker message matching one of the patterns (case insensitive)
+    ///
+    /// Use UNREGISTERED_PATTERNS for the messages of common trackers. Tracker lists are fetched
+    /// concurrently; a torrent whose trackers can't be fetched fails the whole query.
+    ///
+    pub async fn find_unregistered(&mut self, patterns: &[&str]) -> Result<Vec<Finding>, Error> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_lowercase()).collect();
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        let results = self.concurrent(CONCURRENCY).stream(
+            torrents.iter().filter(|torrent| torrent.id().is_some()),
+            |mut client, torrent| async move {
+                let hash = torrent.id().unwrap_or_default();
+                client.get_torrent_trackers(hash).await
+            },
+        );
+        pin_mut!(results);
+
+        let mut findings = Vec::new();
+        while let Some((torrent, trackers)) = results.next().await {
+            let matched = trackers?.into_iter().find(|tracker| {
+                let message = tracker.msg.to_lowercase();
+                patterns.iter().any(|pattern| message.contains(pattern))
+            });
+            if let Some(tracker) = matched {
+                let reason = FindingReason::Unregistered {
+                    tracker: tracker.url,
+                    message: tracker.msg,
+                };
+                findings.extend(Finding::new(torrent, reason));
+            }
+        }
+        findings.sort_by(|a, b| a.hash.cmp(&b.hash));
+        Ok(findings)
+    }
+}
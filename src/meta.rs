@@ -0,0 +1,269 @@
+// Meta
+//
+// Parse .torrent files and magnet links locally to know the info hash of a torrent before
+// adding it.
+
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use crate::{
+    bencode::{self, Value},
+    magnet::Magnet,
+    torrents::{torrent_id, AddTorrent},
+    Error,
+};
+
+/// File of a torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaFile {
+    /// Path of the file in the save path, under the torrent name for multi file torrents
+    pub path: String,
+    /// File size (bytes)
+    pub length: i64,
+}
+
+/// Metainfo of a .torrent file
+#[derive(Debug, Clone)]
+pub struct TorrentMeta {
+    /// Torrent name
+    pub name: String,
+    /// SHA1 info hash (hex) of v1 and hybrid torrents
+    pub info_hash_v1: Option<String>,
+    /// SHA256 info hash (hex) of v2 and hybrid torrents
+    pub info_hash_v2: Option<String>,
+    /// Files of the torrent, padding files excluded
+    pub files: Vec<MetaFile>,
+    /// Total size (bytes) of the files
+    pub total_size: i64,
+    /// Piece size (bytes)
+    pub piece_length: i64,
+    /// Tracker URLs of all tiers
+    pub trackers: Vec<String>,
+    /// True if the torrent is private
+    pub private: bool,
+    /// Torrent comment
+    pub comment: Option<String>,
+    data: Vec<u8>,
+}
+
+impl TorrentMeta {
+    /// Parse the content of a .torrent file
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        let root = bencode::decode(data)?;
+        let info = root
+            .get("info")
+            .filter(|info| info.entries().is_some())
+            .ok_or(Error::InvalidTorrentFile("missing info dictionary"))?;
+        let raw_info = info.raw().unwrap_or_default();
+        let name = info
+            .get("name.utf-8")
+            .or_else(|| info.get("name"))
+            .and_then(Value::as_string)
+            .ok_or(Error::InvalidTorrentFile("missing name"))?;
+        let is_v1 = info.get("pieces").is_some();
+        let is_v2 = info.get("meta version").and_then(Value::as_int) == Some(2);
+        if !is_v1 && !is_v2 {
+            return Err(Error::InvalidTorrentFile("missing pieces"));
+        }
+
+        let files = if is_v1 {
+            v1_files(info, &name)?
+        } else {
+            let tree = info
+                .get("file tree")
+                .ok_or(Error::InvalidTorrentFile("missing file tree"))?;
+            // Files of single file torrents are at the root of the tree, others under the name
+            let single = tree.entries().is_some_and(|entries| {
+                entries.len() == 1 && entries.values().all(|node| node.get("").is_some())
+            });
+            let mut files = Vec::new();
+            v2_files(tree, if single { "" } else { &name }, &mut files)?;
+            files
+        };
+
+        Ok(Self {
+            info_hash_v1: is_v1.then(|| hex(&Sha1::digest(raw_info))),
+            info_hash_v2: is_v2.then(|| hex(&Sha256::digest(raw_info))),
+            total_size: files.iter().map(|file| file.length).sum(),
+            files,
+            piece_length: info
+                .get("piece length")
+                .and_then(Value::as_int)
+                .unwrap_or_default(),
+            trackers: trackers(&root),
+            private: info.get("private").and_then(Value::as_int) == Some(1),
+            comment: root
+                .get("comment.utf-8")
+                .or_else(|| root.get("comment"))
+                .and_then(Value::as_string),
+            name,
+            data: data.to_vec(),
+        })
+    }
+
+    /// Read and parse a .torrent file
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let data = tokio::fs::read(path).await?;
+        Self::from_bytes(&data)
+    }
+
+    /// Raw content of the .torrent file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// ID of the torrent in qBittorrent: the SHA1 info hash, or the SHA256 info hash truncated
+    /// to 40 characters for v2 only torrents
+    pub fn id(&self) -> &str {
+        self.info_hash_v1
+            .as_deref()
+            .or(self.info_hash_v2.as_deref())
+            .map(torrent_id)
+            .unwrap_or_default()
+    }
+
+    /// Magnet URI with the info hashes, name and trackers
+    pub fn magnet_uri(&self) -> String {
+        Magnet::from(self).to_string()
+    }
+}
+
+impl AddTorrent {
+    /// Add the torrent of a parsed .torrent file
+    pub fn from_meta(meta: &TorrentMeta) -> Self {
+        AddTorrent {
+            torrents: meta.as_bytes().to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// ID of the torrent added, if it adds a single magnet link or .torrent file
+    pub fn target_id(&self) -> Option<String> {
+        match (self.all_urls().as_slice(), self.torrents.is_empty()) {
+            ([url], true) => magnet_id(url),
+            ([], false) => TorrentMeta::from_bytes(&self.torrents)
+                .ok()
+                .map(|meta| meta.id().to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// ID of the torrent of a magnet link, from its btih (hex or base32) or btmh info hash
+pub fn magnet_id(uri: &str) -> Option<String> {
+    let magnet: Magnet = uri.parse().ok()?;
+    magnet.id().map(str::to_string)
+}
+
+/// Decode RFC 4648 base32 without padding
+pub(crate) fn base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(value.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in value.bytes() {
+        let digit = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | u64::from(digit);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+fn v1_files(info: &Value, name: &str) -> Result<Vec<MetaFile>, Error> {
+    let Some(files) = info.get("files") else {
+        let length = info
+            .get("length")
+            .and_then(Value::as_int)
+            .ok_or(Error::InvalidTorrentFile("missing length"))?;
+        return Ok(vec![MetaFile {
+            path: name.to_string(),
+            length,
+        }]);
+    };
+    let files = files
+        .as_list()
+        .ok_or(Error::InvalidTorrentFile("invalid files"))?;
+    let mut result = Vec::with_capacity(files.len());
+    for file in files {
+        let is_padding = file
+            .get("attr")
+            .and_then(Value::as_bytes)
+            .is_some_and(|attr| attr.contains(&b'p'));
+        if is_padding {
+            continue;
+        }
+        let length = file
+            .get("length")
+            .and_then(Value::as_int)
+            .ok_or(Error::InvalidTorrentFile("missing file length"))?;
+        let path = file
+            .get("path.utf-8")
+            .or_else(|| file.get("path"))
+            .and_then(Value::as_list)
+            .ok_or(Error::InvalidTorrentFile("missing file path"))?;
+        let components: Vec<String> = path.iter().filter_map(Value::as_string).collect();
+        result.push(MetaFile {
+            path: format!("{name}/{}", components.join("/")),
+            length,
+        });
+    }
+    Ok(result)
+}
+
+/// Collect the files of a v2 file tree, whose files are dictionaries with an empty key
+fn v2_files(tree: &Value, prefix: &str, files: &mut Vec<MetaFile>) -> Result<(), Error> {
+    let entries = tree
+        .entries()
+        .ok_or(Error::InvalidTorrentFile("invalid file tree"))?;
+    for (name, node) in entries {
+        let name = String::from_utf8_lossy(name);
+        let path = if prefix.is_empty() {
+            name.into_owned()
+        } else {
+            format!("{prefix}/{name}")
+        };
+        match node.get("") {
+            Some(file) => files.push(MetaFile {
+                length: file
+                    .get("length")
+                    .and_then(Value::as_int)
+                    .ok_or(Error::InvalidTorrentFile("missing file length"))?,
+                path,
+            }),
+            None => v2_files(node, &path, files)?,
+        }
+    }
+    Ok(())
+}
+
+/// Trackers of announce-list, or announce if there is no list
+fn trackers(root: &Value) -> Vec<String> {
+    let mut trackers: Vec<String> = Vec::new();
+    let tiers = root.get("announce-list").and_then(Value::as_list);
+    for tier in tiers.unwrap_or_default() {
+        for tracker in tier.as_list().unwrap_or_default() {
+            if let Some(tracker) = tracker.as_string() {
+                if !trackers.contains(&tracker) {
+                    trackers.push(tracker);
+                }
+            }
+        }
+    }
+    if trackers.is_empty() {
+        trackers.extend(root.get("announce").and_then(Value::as_string));
+    }
+    trackers
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
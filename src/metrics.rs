@@ -0,0 +1,237 @@
+// Metrics
+//
+// Prometheus metrics of torrents and transfers, enabled by the metrics feature.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::{
+    sync::SyncSession,
+    torrents::Torrent,
+    transfer::{ConnectionStatus, TransferInfo},
+    Client, Error,
+};
+
+/// Polls sync/maindata and transfer/info and renders the Prometheus text exposition format
+///
+/// Torrents are kept in a SyncSession, so each scrape only transfers the changes since the
+/// previous one. Serve the output of scrape on a /metrics endpoint with any HTTP server.
+#[derive(Debug)]
+pub struct Exporter {
+    client: Client,
+    session: SyncSession,
+    transfer: Option<TransferInfo>,
+}
+
+impl Exporter {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            session: SyncSession::new(),
+            transfer: None,
+        }
+    }
+
+    /// Poll the server and render the metrics
+    pub async fn scrape(&mut self) -> Result<String, Error> {
+        self.poll().await?;
+        self.render()
+    }
+
+    /// Poll sync/maindata and transfer/info
+    pub async fn poll(&mut self) -> Result<(), Error> {
+        self.client.sync(&mut self.session).await?;
+        self.transfer = Some(self.client.get_transfer_info().await?);
+        Ok(())
+    }
+
+    /// Render the metrics of the last poll
+    pub fn render(&self) -> Result<String, Error> {
+        let mut torrents: Vec<Torrent> = self.session.torrents()?.into_values().collect();
+        torrents.sort_by(|a, b| a.hash.cmp(&b.hash));
+        let mut out = String::new();
+        if let Some(info) = &self.transfer {
+            render_transfer(&mut out, info);
+        }
+        if let Ok(state) = self.session.server_state() {
+            if let Some(alltime_dl) = state.alltime_dl {
+                metric(
+                    &mut out,
+                    "qbittorrent_alltime_downloaded_bytes_total",
+                    "counter",
+                    "Data downloaded since the statistics were reset",
+                    &[(Vec::new(), alltime_dl as f64)],
+                );
+            }
+            if let Some(alltime_ul) = state.alltime_ul {
+                metric(
+                    &mut out,
+                    "qbittorrent_alltime_uploaded_bytes_total",
+                    "counter",
+                    "Data uploaded since the statistics were reset",
+                    &[(Vec::new(), alltime_ul as f64)],
+                );
+            }
+            if let Some(free_space) = state.free_space_on_disk {
+                metric(
+                    &mut out,
+                    "qbittorrent_free_space_bytes",
+                    "gauge",
+                    "Free space on the disk of the default save path",
+                    &[(Vec::new(), free_space as f64)],
+                );
+            }
+        }
+        render_torrents(&mut out, &torrents);
+        Ok(out)
+    }
+}
+
+fn render_transfer(out: &mut String, info: &TransferInfo) {
+    let gauges = [
+        (
+            "qbittorrent_download_speed_bytes",
+            "Global download rate",
+            info.dl_info_speed,
+        ),
+        (
+            "qbittorrent_upload_speed_bytes",
+            "Global upload rate",
+            info.up_info_speed,
+        ),
+        (
+            "qbittorrent_download_limit_bytes",
+            "Global download rate limit, 0 if unlimited",
+            info.dl_rate_limit,
+        ),
+        (
+            "qbittorrent_upload_limit_bytes",
+            "Global upload rate limit, 0 if unlimited",
+            info.up_rate_limit,
+        ),
+        (
+            "qbittorrent_dht_nodes",
+            "DHT nodes connected to",
+            info.dht_nodes,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        metric(out, name, "gauge", help, &[(Vec::new(), value as f64)]);
+    }
+    metric(
+        out,
+        "qbittorrent_downloaded_bytes_total",
+        "counter",
+        "Data downloaded this session",
+        &[(Vec::new(), info.dl_info_data as f64)],
+    );
+    metric(
+        out,
+        "qbittorrent_uploaded_bytes_total",
+        "counter",
+        "Data uploaded this session",
+        &[(Vec::new(), info.up_info_data as f64)],
+    );
+    let connected = matches!(info.connection_status, ConnectionStatus::Connected);
+    metric(
+        out,
+        "qbittorrent_connected",
+        "gauge",
+        "1 if the client is connected",
+        &[(Vec::new(), if connected { 1.0 } else { 0.0 })],
+    );
+}
+
+/// Name, help and value of a gauge exported for each torrent
+type TorrentGauge = (&'static str, &'static str, fn(&Torrent) -> f64);
+
+fn render_torrents(out: &mut String, torrents: &[Torrent]) {
+    let mut states: BTreeMap<&str, usize> = BTreeMap::new();
+    for torrent in torrents {
+        *states.entry(torrent.state.as_str()).or_default() += 1;
+    }
+    let counts: Vec<(Vec<(&str, &str)>, f64)> = states
+        .iter()
+        .map(|(state, count)| (vec![("state", *state)], *count as f64))
+        .collect();
+    metric(
+        out,
+        "qbittorrent_torrents",
+        "gauge",
+        "Number of torrents by state",
+        &counts,
+    );
+
+    let gauges: [TorrentGauge; 5] = [
+        (
+            "qbittorrent_torrent_progress",
+            "Torrent progress (0 to 1)",
+            |torrent| torrent.progress,
+        ),
+        (
+            "qbittorrent_torrent_download_speed_bytes",
+            "Torrent download rate",
+            |torrent| torrent.dlspeed as f64,
+        ),
+        (
+            "qbittorrent_torrent_upload_speed_bytes",
+            "Torrent upload rate",
+            |torrent| torrent.upspeed as f64,
+        ),
+        (
+            "qbittorrent_torrent_ratio",
+            "Torrent share ratio",
+            |torrent| torrent.ratio,
+        ),
+        (
+            "qbittorrent_torrent_size_bytes",
+            "Size of the files selected for download",
+            |torrent| torrent.size as f64,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        let samples: Vec<(Vec<(&str, &str)>, f64)> = torrents
+            .iter()
+            .map(|torrent| (torrent_labels(torrent), value(torrent)))
+            .collect();
+        metric(out, name, "gauge", help, &samples);
+    }
+}
+
+fn torrent_labels(torrent: &Torrent) -> Vec<(&str, &str)> {
+    vec![
+        ("hash", torrent.hash.as_deref().unwrap_or_default()),
+        ("name", torrent.name.as_str()),
+        ("category", torrent.category.as_str()),
+    ]
+}
+
+/// Write a metric family with its HELP and TYPE lines
+fn metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: &[(Vec<(&str, &str)>, f64)],
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{name} {value}");
+        } else {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = writeln!(out, "{name}{{{}}} {value}", labels.join(","));
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,73 @@
+// Middleware
+//
+// Hooks called around every HTTP request of a client, e.g. to add the authentication headers
+// of a proxy, sign or log requests, or inject failures in tests.
+
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::Bytes;
+pub use netc::Response;
+use url::Url;
+
+use crate::Error;
+
+/// HTTP request about to be sent, passed to Middleware::on_request
+#[derive(Debug, Clone)]
+pub struct OutgoingRequest {
+    /// Endpoint relative to the API URL, e.g. app/version
+    pub path: String,
+    /// URL the request is sent to
+    pub url: Url,
+    /// Headers sent after the headers set by the client
+    pub headers: Vec<(String, String)>,
+    /// Content-Type of the body
+    pub content_type: String,
+    /// Form encoded body, or multipart for file uploads
+    pub body: Bytes,
+}
+
+impl OutgoingRequest {
+    /// Add a header to the request
+    pub fn header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+}
+
+/// Hooks registered with ClientBuilder::middleware
+///
+/// Middlewares are called in the order they were registered for every attempt of a request,
+/// login and retries included.
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent. An error fails the attempt without sending it.
+    fn on_request(&self, _request: &mut OutgoingRequest) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called with the response of the request, before its status code is checked
+    fn on_response(&self, _request: &OutgoingRequest, _response: &Response) {}
+}
+
+/// Middlewares of a client, shared by its clones
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares(pub(crate) Vec<Arc<dyn Middleware>>);
+
+impl fmt::Debug for Middlewares {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Middlewares({})", self.0.len())
+    }
+}
+
+impl Middlewares {
+    pub(crate) fn on_request(&self, request: &mut OutgoingRequest) -> Result<(), Error> {
+        self.0
+            .iter()
+            .try_for_each(|middleware| middleware.on_request(request))
+    }
+
+    pub(crate) fn on_response(&self, request: &OutgoingRequest, response: &Response) {
+        for middleware in &self.0 {
+            middleware.on_response(request, response);
+        }
+    }
+}
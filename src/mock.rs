@@ -0,0 +1,250 @@
+// Mock
+//
+// Minimal qBittorrent WebUI mock for tests, enabled by the test-util feature.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::{
+    http::{read_request, write_response},
+    record::load,
+    Error,
+};
+
+/// Canned response returned for a mocked endpoint
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Extra response headers
+    pub headers: Vec<(String, String)>,
+    /// Response body
+    pub body: Vec<u8>,
+    /// Wait before answering
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// Empty response with the given status code
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: None,
+        }
+    }
+
+    /// 200 response with a text body
+    pub fn text(body: &str) -> Self {
+        Self::status(200)
+            .header("content-type", "text/plain; charset=UTF-8")
+            .body(body.as_bytes())
+    }
+
+    /// 200 response with a JSON body
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        let body = serde_json::to_vec(value).unwrap_or_default();
+        Self::status(200)
+            .header("content-type", "application/json")
+            .body(&body)
+    }
+
+    /// Add a response header
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the response body
+    pub fn body(mut self, body: &[u8]) -> Self {
+        self.body = body.to_vec();
+        self
+    }
+
+    /// Answer after a delay, e.g. to test timeouts
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// Request received by the mock
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: String,
+    /// Endpoint relative to /api/v2/, e.g. app/version
+    pub path: String,
+    /// Full path of the request, e.g. /qbt/api/v2/app/version behind a reverse proxy prefix
+    pub target: String,
+    /// Request headers with lowercase names
+    pub headers: HashMap<String, String>,
+    /// Raw request body
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Decoded form parameters of the body
+    pub fn form(&self) -> HashMap<String, String> {
+        url::form_urlencoded::parse(&self.body)
+            .into_owned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Responses of each path, the last one is repeated
+    routes: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// qBittorrent WebUI mock listening on a random local port
+///
+/// Endpoints are mocked by path relative to /api/v2/. Unmocked endpoints answer 404. Every
+/// request is recorded so tests can check the path, headers and form parameters sent.
+#[derive(Debug)]
+pub struct MockQbittorrent {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    task: JoinHandle<()>,
+}
+
+impl MockQbittorrent {
+    /// Start the mock server
+    pub async fn start() -> Result<Self, Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let task_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = task_state.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle(stream, state).await {
+                        ::log::debug!("mock connection error: {err}");
+                    }
+                });
+            }
+        });
+        Ok(Self { addr, state, task })
+    }
+
+    /// Base URL to pass to Client::new
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// Answer requests to path (relative to /api/v2/) with response
+    pub fn mock(&self, path: &str, response: MockResponse) {
+        self.mock_sequence(path, vec![response]);
+    }
+
+    /// Answer successive requests to path with responses in order, repeating the last one
+    pub fn mock_sequence(&self, path: &str, responses: Vec<MockResponse>) {
+        self.lock()
+            .routes
+            .insert(path.trim_start_matches('/').to_string(), responses.into());
+    }
+
+    /// Start a mock answering with the exchanges recorded in dir by record::Recorder
+    ///
+    /// Requests to a path get the responses recorded for it in order, the last one repeated.
+    pub async fn replay(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let mock = Self::start().await?;
+        let mut routes: HashMap<String, Vec<MockResponse>> = HashMap::new();
+        for exchange in load(dir)? {
+            let mut response = MockResponse::status(exchange.status).body(&exchange.body_bytes()?);
+            for (name, value) in &exchange.headers {
+                response = response.header(name, value);
+            }
+            routes.entry(exchange.path).or_default().push(response);
+        }
+        for (path, responses) in routes {
+            mock.mock_sequence(&path, responses);
+        }
+        Ok(mock)
+    }
+
+    /// Mock a successful auth/login that sets the given SID cookie
+    pub fn mock_login(&self, sid: &str) {
+        self.mock(
+            "auth/login",
+            MockResponse::text("Ok.").header("set-cookie", &format!("SID={sid}; path=/")),
+        );
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Last request received for path (relative to /api/v2/)
+    pub fn last_request(&self, path: &str) -> Option<RecordedRequest> {
+        let path = path.trim_start_matches('/');
+        self.lock()
+            .requests
+            .iter()
+            .rev()
+            .find(|request| request.path == path)
+            .cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockQbittorrent {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<Mutex<MockState>>) -> std::io::Result<()> {
+    let Some(request) = read_request(&mut stream, usize::MAX).await? else {
+        return Ok(());
+    };
+    let target = request.path().to_string();
+    let path = match target.split_once("/api/v2/") {
+        Some((_, path)) => path.to_string(),
+        None => target.trim_start_matches('/').to_string(),
+    };
+
+    let response = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let response = match state.routes.get_mut(&path) {
+            Some(responses) if responses.len() > 1 => responses.pop_front(),
+            Some(responses) => responses.front().cloned(),
+            None => None,
+        }
+        .unwrap_or_else(|| MockResponse::status(404));
+        state.requests.push(RecordedRequest {
+            method: request.method,
+            path,
+            target,
+            headers: request.headers,
+            body: request.body,
+        });
+        response
+    };
+
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+    write_response(
+        &mut stream,
+        response.status,
+        &response.headers,
+        &response.body,
+    )
+    .await
+}
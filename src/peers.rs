@@ -0,0 +1,268 @@
+// Peers
+//
+// Track the peers of all torrents over time, polling sync/torrentPeers of each torrent with
+// connected peers.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    maintenance::now,
+    sync::{Peer, PeerSession, SyncSession},
+    Client, Error,
+};
+
+/// Peer (by IP) seen by a PeerMonitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerStats {
+    /// Peer IP
+    pub ip: String,
+    /// Last client name reported
+    pub client: String,
+    /// Country name, if qBittorrent resolves peer countries
+    pub country: Option<String>,
+    /// ISO 3166 country code, if qBittorrent resolves peer countries
+    pub country_code: Option<String>,
+    /// Hashes of the torrents the peer is connected to
+    pub torrents: HashSet<String>,
+    /// Data downloaded from the peer over all connections (bytes)
+    pub downloaded: i64,
+    /// Data uploaded to the peer over all connections (bytes)
+    pub uploaded: i64,
+    /// Unix timestamp of the first poll that saw the peer
+    pub first_seen: i64,
+    /// Unix timestamp of the last poll that saw the peer
+    pub last_seen: i64,
+}
+
+impl PeerStats {
+    /// Whether the peer is connected to a torrent
+    pub fn is_connected(&self) -> bool {
+        !self.torrents.is_empty()
+    }
+}
+
+/// Change seen by PeerMonitor::poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// The peer connected to a torrent
+    Joined { ip: String, hash: String },
+    /// The peer disconnected from a torrent, or the torrent was removed
+    Left { ip: String, hash: String },
+}
+
+/// Tracks the unique peers (by IP) of all torrents
+///
+/// Each poll syncs the torrent list, then the peers of every torrent with connected peers.
+/// qBittorrent only reports the data exchanged on the current connection, so the monitor adds
+/// up the increments of each connection to keep totals across reconnections. Countries are
+/// only known if qBittorrent resolves them (the resolve_peer_countries preference).
+#[derive(Debug)]
+pub struct PeerMonitor {
+    pub(crate) client: Client,
+    torrents: SyncSession,
+    sessions: HashMap<String, PeerSession>,
+    /// Peers of each connection by torrent hash and ip:port, at the last poll
+    connections: HashMap<(String, String), Peer>,
+    peers: HashMap<String, PeerStats>,
+}
+
+impl PeerMonitor {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            torrents: SyncSession::new(),
+            sessions: HashMap::new(),
+            connections: HashMap::new(),
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Poll the torrents and their peers, returning the peers that joined or left a torrent
+    pub async fn poll(&mut self) -> Result<Vec<PeerEvent>, Error> {
+        self.client.sync(&mut self.torrents).await?;
+        let torrents = self.torrents.torrents()?;
+        let time = now();
+        let mut events = Vec::new();
+        let mut seen: HashSet<(String, String)> = HashSet::new();
+        for (hash, torrent) in &torrents {
+            let had_peers = self.connections.keys().any(|(other, _)| other == hash);
+            if torrent.num_seeds + torrent.num_leechs == 0 && !had_peers {
+                self.sessions.remove(hash);
+                continue;
+            }
+            let session = self
+                .sessions
+                .entry(hash.clone())
+                .or_insert_with(|| PeerSession::new(hash));
+            match self.client.sync_peers(session).await {
+                Ok(_) => {}
+                // Removed since the torrent list was synced
+                Err(Error::NoTorrentHash) => continue,
+                Err(err) => return Err(err),
+            }
+            for (key, peer) in session.peers()? {
+                seen.insert((hash.clone(), key.clone()));
+                self.record(hash, key, &peer, time, &mut events);
+            }
+        }
+        self.sessions.retain(|hash, _| torrents.contains_key(hash));
+
+        let gone: Vec<(String, String)> = self
+            .connections
+            .keys()
+            .filter(|connection| !seen.contains(*connection))
+            .cloned()
+            .collect();
+        for (hash, key) in gone {
+            self.connections.remove(&(hash.clone(), key.clone()));
+            let ip = peer_ip(&key);
+            // The peer may still be connected to the torrent from another port
+            let connected = self
+                .connections
+                .keys()
+                .any(|(other, key)| other == &hash && peer_ip(key) == ip);
+            if let Some(stats) = self.peers.get_mut(ip).filter(|_| !connected) {
+                if stats.torrents.remove(&hash) {
+                    events.push(PeerEvent::Left {
+                        ip: ip.to_string(),
+                        hash,
+                    });
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn record(
+        &mut self,
+        hash: &str,
+        key: String,
+        peer: &Peer,
+        time: i64,
+        events: &mut Vec<PeerEvent>,
+    ) {
+        let stats = self
+            .peers
+            .entry(peer.ip.clone())
+            .or_insert_with(|| PeerStats {
+                ip: peer.ip.clone(),
+                client: String::new(),
+                country: None,
+                country_code: None,
+                torrents: HashSet::new(),
+                downloaded: 0,
+                uploaded: 0,
+                first_seen: time,
+                last_seen: time,
+            });
+        if !peer.client.is_empty() {
+            stats.client.clone_from(&peer.client);
+        }
+        if peer.country.is_some() {
+            stats.country.clone_from(&peer.country);
+            stats.country_code.clone_from(&peer.country_code);
+        }
+        stats.last_seen = time;
+        if stats.torrents.insert(hash.to_string()) {
+            events.push(PeerEvent::Joined {
+                ip: peer.ip.clone(),
+                hash: hash.to_string(),
+            });
+        }
+        let (downloaded, uploaded) = self
+            .connections
+            .insert((hash.to_string(), key), peer.clone())
+            .map_or((0, 0), |previous| (previous.downloaded, previous.uploaded));
+        // Lower totals belong to a new connection
+        stats.downloaded += increment(downloaded, peer.downloaded);
+        stats.uploaded += increment(uploaded, peer.uploaded);
+    }
+
+    /// Peers connected at the last poll, with the hash of their torrent
+    pub fn connections(&self) -> impl Iterator<Item = (&str, &Peer)> {
+        self.connections
+            .iter()
+            .map(|((hash, _), peer)| (hash.as_str(), peer))
+    }
+
+    /// Torrents of the last poll
+    pub fn torrents(&self) -> &SyncSession {
+        &self.torrents
+    }
+
+    /// All peers seen, by IP
+    pub fn peers(&self) -> &HashMap<String, PeerStats> {
+        &self.peers
+    }
+
+    /// Number of peers connected to a torrent
+    pub fn connected(&self) -> usize {
+        self.peers
+            .values()
+            .filter(|stats| stats.is_connected())
+            .count()
+    }
+
+    /// The n peers we uploaded the most data to
+    pub fn top_by_upload(&self, n: usize) -> Vec<&PeerStats> {
+        let mut peers: Vec<&PeerStats> = self.peers.values().collect();
+        peers.sort_by(|a, b| b.uploaded.cmp(&a.uploaded).then_with(|| a.ip.cmp(&b.ip)));
+        peers.truncate(n);
+        peers
+    }
+
+    /// Number of peers seen by client name, without the version
+    pub fn clients(&self) -> HashMap<String, usize> {
+        let mut clients = HashMap::new();
+        for stats in self.peers.values() {
+            *clients.entry(client_name(&stats.client)).or_default() += 1;
+        }
+        clients
+    }
+
+    /// Number of peers seen by country code, for peers with a known country
+    pub fn countries(&self) -> HashMap<String, usize> {
+        let mut countries = HashMap::new();
+        for code in self
+            .peers
+            .values()
+            .filter_map(|stats| stats.country_code.clone())
+        {
+            *countries.entry(code).or_default() += 1;
+        }
+        countries
+    }
+}
+
+/// Data exchanged since the previous total of a connection
+fn increment(previous: i64, current: i64) -> i64 {
+    if current >= previous {
+        current - previous
+    } else {
+        current
+    }
+}
+
+/// IP of an ip:port key, IPv6 addresses are in brackets
+fn peer_ip(key: &str) -> &str {
+    let ip = key.rsplit_once(':').map_or(key, |(ip, _)| ip);
+    ip.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Client name without the version, e.g. qBittorrent for "qBittorrent 4.6.3"
+fn client_name(client: &str) -> String {
+    let name = client
+        .split_whitespace()
+        .take_while(|word| {
+            !word
+                .trim_start_matches('v')
+                .starts_with(|c: char| c.is_ascii_digit())
+        })
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if name.is_empty() {
+        client.to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,222 @@
+// Pieces
+//
+// Piece map built from the torrent pieces' states, and piece overview adding the piece hashes.
+
+use std::ops::{Range, RangeInclusive};
+
+use futures_util::future::try_join3;
+
+use crate::{
+    torrents::{File, PieceState},
+    Client, Error,
+};
+
+/// States of all pieces of a torrent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceMap {
+    states: Vec<PieceState>,
+    piece_size: i64,
+}
+
+impl PieceMap {
+    /// Build a piece map from pieces' states and the piece size (bytes) of the torrent
+    pub fn new(states: Vec<PieceState>, piece_size: i64) -> Self {
+        Self { states, piece_size }
+    }
+
+    /// Pieces' states
+    pub fn states(&self) -> &[PieceState] {
+        &self.states
+    }
+
+    /// Piece size (bytes)
+    pub fn piece_size(&self) -> i64 {
+        self.piece_size
+    }
+
+    /// Number of pieces
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// True if the torrent has no pieces, e.g. while its metadata is downloading
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Part of pieces already downloaded (0.0 to 1.0)
+    pub fn completion_ratio(&self) -> f64 {
+        if self.states.is_empty() {
+            return 0.0;
+        }
+        let downloaded = self
+            .states
+            .iter()
+            .filter(|state| **state == PieceState::AlreadyDownloaded)
+            .count();
+        downloaded as f64 / self.states.len() as f64
+    }
+
+    /// Number of pieces downloaded from the start without a gap
+    pub fn contiguous_downloaded_prefix(&self) -> usize {
+        self.states
+            .iter()
+            .take_while(|state| **state == PieceState::AlreadyDownloaded)
+            .count()
+    }
+
+    /// Ranges of consecutive piece indexes in the given state
+    pub fn ranges(&self, state: PieceState) -> Vec<RangeInclusive<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for (index, current) in self.states.iter().enumerate() {
+            match (*current == state, start) {
+                (true, None) => start = Some(index),
+                (false, Some(first)) => {
+                    ranges.push(first..=index - 1);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(first) = start {
+            ranges.push(first..=self.states.len() - 1);
+        }
+        ranges
+    }
+
+    /// True if every piece of the range is downloaded
+    pub fn is_downloaded(&self, mut pieces: RangeInclusive<usize>) -> bool {
+        pieces.all(|index| self.states.get(index) == Some(&PieceState::AlreadyDownloaded))
+    }
+
+    /// Piece indexes holding the bytes of a file
+    ///
+    /// files is the full contents list of the torrent, file the index of the file in it and
+    /// bytes a byte range relative to the start of the file. The file offset in the torrent is
+    /// the sum of the sizes of the preceding files, and the result is clamped to the piece_range
+    /// reported for the file. Returns None if the file or the range is out of bounds.
+    pub fn file_pieces(
+        &self,
+        files: &[File],
+        file: usize,
+        bytes: Range<i64>,
+    ) -> Option<RangeInclusive<usize>> {
+        let position = |f: &File, fallback: usize| f.index.unwrap_or(fallback as i64);
+        let (target, current) = files
+            .iter()
+            .enumerate()
+            .find(|(i, f)| position(f, *i) == file as i64)?;
+        if self.piece_size <= 0 || bytes.start < 0 || bytes.start >= bytes.end {
+            return None;
+        }
+        if bytes.end > current.size {
+            return None;
+        }
+        let offset: i64 = files
+            .iter()
+            .enumerate()
+            .filter(|(i, f)| position(f, *i) < position(current, target))
+            .map(|(_, f)| f.size)
+            .sum();
+        let first = (offset + bytes.start) / self.piece_size;
+        let last = (offset + bytes.end - 1) / self.piece_size;
+        let (first, last) = match current.piece_range.as_slice() {
+            [low, high] if low <= high => (first.clamp(*low, *high), last.clamp(*low, *high)),
+            _ => (first, last),
+        };
+        Some(first as usize..=last as usize)
+    }
+}
+
+/// Piece map of a torrent with its piece hashes, see Client::piece_overview
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceOverview {
+    map: PieceMap,
+    bitmap: Vec<u8>,
+    hashes: Vec<String>,
+}
+
+impl PieceOverview {
+    /// Build an overview from a piece map and the piece hashes of the torrent
+    pub fn new(map: PieceMap, hashes: Vec<String>) -> Self {
+        let mut bitmap = vec![0; map.len().div_ceil(8)];
+        for (index, state) in map.states().iter().enumerate() {
+            if *state == PieceState::AlreadyDownloaded {
+                bitmap[index / 8] |= 0x80 >> (index % 8);
+            }
+        }
+        Self {
+            map,
+            bitmap,
+            hashes,
+        }
+    }
+
+    /// Piece map of the torrent
+    pub fn map(&self) -> &PieceMap {
+        &self.map
+    }
+
+    /// Number of pieces
+    pub fn piece_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Piece size (bytes)
+    pub fn piece_size(&self) -> i64 {
+        self.map.piece_size()
+    }
+
+    /// Downloaded pieces, one bit per piece, high bit first as in a BitTorrent bitfield
+    pub fn bitmap(&self) -> &[u8] {
+        &self.bitmap
+    }
+
+    /// True if the piece is downloaded
+    pub fn has_piece(&self, index: usize) -> bool {
+        self.bitmap
+            .get(index / 8)
+            .is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    /// Hex encoded SHA1 hashes of the pieces, empty while the metadata is downloading
+    pub fn hashes(&self) -> &[String] {
+        &self.hashes
+    }
+}
+
+impl Client {
+    /// Get the piece map of a torrent
+    ///
+    /// Combines torrent pieces' states with the piece size from torrent properties.
+    ///
+    pub async fn get_piece_map(&mut self, hash: &str) -> Result<PieceMap, Error> {
+        let properties = self
+            .get_torrent_properties(hash)
+            .await?
+            .ok_or(Error::NoTorrentHash)?;
+        let states = self.get_piece_states(hash).await?;
+        Ok(PieceMap::new(states, properties.piece_size))
+    }
+
+    /// Get the piece map and the piece hashes of a torrent
+    ///
+    /// Torrent properties, pieces' states and pieces' hashes are requested concurrently.
+    ///
+    pub async fn piece_overview(&mut self, hash: &str) -> Result<PieceOverview, Error> {
+        let mut states_client = self.clone();
+        let mut hashes_client = self.clone();
+        let (properties, states, hashes) = try_join3(
+            self.get_torrent_properties(hash),
+            states_client.get_piece_states(hash),
+            hashes_client.get_piece_hashes(hash),
+        )
+        .await?;
+        let properties = properties.ok_or(Error::NoTorrentHash)?;
+        Ok(PieceOverview::new(
+            PieceMap::new(states, properties.piece_size),
+            hashes,
+        ))
+    }
+}
@@ -0,0 +1,173 @@
+// Policy
+//
+// Remove torrents by declared rules, e.g. torrents of a category seeded long enough, with a
+// dry-run mode and a log of the actions taken.
+
+use crate::{
+    query::TorrentQuery,
+    torrents::{GetTorrentList, Torrent},
+    Client, Error,
+};
+
+/// Removal rule of a PolicyEngine
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Name reported in the actions of the rule
+    pub name: String,
+    /// Torrents removed by the rule
+    pub query: TorrentQuery,
+    /// Also delete the downloaded data
+    pub delete_files: bool,
+    /// Only apply while the free space on disk is below this many bytes, removing the oldest
+    /// matching torrents until their data would free enough space. Requires delete_files,
+    /// nothing is freed otherwise.
+    pub free_space_below: Option<i64>,
+}
+
+impl Rule {
+    pub fn new(name: &str, query: TorrentQuery) -> Self {
+        Self {
+            name: name.to_string(),
+            query,
+            delete_files: false,
+            free_space_below: None,
+        }
+    }
+
+    pub fn delete_files(mut self, delete_files: bool) -> Self {
+        self.delete_files = delete_files;
+        self
+    }
+
+    pub fn free_space_below(mut self, bytes: i64) -> Self {
+        self.free_space_below = Some(bytes);
+        self
+    }
+}
+
+/// Removal decided by a PolicyEngine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyAction {
+    /// Torrent hash
+    pub hash: String,
+    /// Torrent name
+    pub name: String,
+    /// Name of the rule that matched
+    pub rule: String,
+    /// Whether the data is deleted with the torrent
+    pub delete_files: bool,
+    /// Size (bytes) of the downloaded data
+    pub size: i64,
+    /// False in dry-run mode
+    pub executed: bool,
+}
+
+/// Rules removing torrents, evaluated in order against the live torrent list
+///
+/// A torrent is removed by the first rule it matches. Torrents matching a protect query are
+/// never removed.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEngine {
+    rules: Vec<Rule>,
+    protect: Vec<TorrentQuery>,
+    dry_run: bool,
+}
+
+impl PolicyEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, evaluated after the previous ones
+    ///
+    /// Fails with Error::InvalidRule if the rule has free_space_below without delete_files,
+    /// which would remove every matching torrent without ever freeing enough space.
+    pub fn rule(mut self, rule: Rule) -> Result<Self, Error> {
+        if rule.free_space_below.is_some() && !rule.delete_files {
+            return Err(Error::InvalidRule(format!(
+                "{}: free_space_below requires delete_files",
+                rule.name
+            )));
+        }
+        self.rules.push(rule);
+        Ok(self)
+    }
+
+    /// Never remove torrents matching query, e.g. TorrentQuery::tag("keep")
+    pub fn protect(mut self, query: TorrentQuery) -> Self {
+        self.protect.push(query);
+        self
+    }
+
+    /// Report the actions without removing anything
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Evaluate the rules and remove the matching torrents
+    ///
+    /// Returns the actions in rule order. The free space is requested only if a rule
+    /// depends on it.
+    ///
+    pub async fn run(&self, client: &mut Client) -> Result<Vec<PolicyAction>, Error> {
+        let torrents = client.get_torrent_list(GetTorrentList::default()).await?;
+        let mut candidates: Vec<&Torrent> = torrents
+            .iter()
+            .filter(|torrent| torrent.id().is_some())
+            .filter(|torrent| !self.protect.iter().any(|query| query.matches(torrent)))
+            .collect();
+        let mut free_space = None;
+        let mut actions = Vec::new();
+
+        for rule in &self.rules {
+            let mut matching: Vec<&Torrent> = candidates
+                .iter()
+                .copied()
+                .filter(|torrent| rule.query.matches(torrent))
+                .collect();
+            if let Some(threshold) = rule.free_space_below {
+                let mut available = match free_space {
+                    Some(available) => available,
+                    None => client.get_free_space().await?,
+                };
+                matching.sort_by_key(|torrent| torrent.added_on);
+                matching.retain(|torrent| {
+                    if available >= threshold {
+                        return false;
+                    }
+                    available += torrent.completed;
+                    true
+                });
+                free_space = Some(available);
+            }
+            if matching.is_empty() {
+                continue;
+            }
+
+            let hashes: Vec<&str> = matching.iter().filter_map(|torrent| torrent.id()).collect();
+            candidates.retain(|torrent| !hashes.contains(&torrent.id().unwrap_or_default()));
+            for torrent in &matching {
+                ::log::debug!(
+                    "{}policy {}: remove {} ({})",
+                    if self.dry_run { "dry run: " } else { "" },
+                    rule.name,
+                    torrent.name,
+                    torrent.id().unwrap_or_default()
+                );
+            }
+            if !self.dry_run {
+                client.delete_torrent(hashes, rule.delete_files).await?;
+            }
+            actions.extend(matching.into_iter().map(|torrent| PolicyAction {
+                hash: torrent.id().unwrap_or_default().to_string(),
+                name: torrent.name.clone(),
+                rule: rule.name.clone(),
+                delete_files: rule.delete_files,
+                size: torrent.completed,
+                executed: !self.dry_run,
+            }));
+        }
+        Ok(actions)
+    }
+}
@@ -0,0 +1,194 @@
+// Prefs
+//
+// Typed views of the application preferences grouped by concern. Each group converts from and
+// to the flat Preferences struct, which is kept for raw access, and has a setter sending only
+// its fields.
+
+use crate::{
+    app::{BittorrentProtocol, Encryption, MaxRatioAct, Preferences, ProxyType, SchedulerDays},
+    Client, Error,
+};
+
+macro_rules! prefs_group {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident, $getter:ident, $setter:ident {
+            $($field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Fields are the Preferences fields of the same name, None when not set.
+        #[derive(Debug, Clone, Default, PartialEq)]
+        pub struct $name {
+            $(pub $field: Option<$ty>,)*
+        }
+
+        impl From<&Preferences> for $name {
+            fn from(prefs: &Preferences) -> Self {
+                Self {
+                    $($field: prefs.$field.clone(),)*
+                }
+            }
+        }
+
+        impl From<$name> for Preferences {
+            fn from(group: $name) -> Self {
+                Preferences {
+                    $($field: group.$field,)*
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl $name {
+            /// Copy the fields set in the group to prefs, keeping the others
+            pub fn apply_to(self, prefs: &mut Preferences) {
+                $(
+                    if self.$field.is_some() {
+                        prefs.$field = self.$field;
+                    }
+                )*
+            }
+        }
+
+        impl Preferences {
+            #[doc = concat!("Fields of the ", stringify!($name), " group")]
+            pub fn $getter(&self) -> $name {
+                $name::from(self)
+            }
+        }
+
+        impl Client {
+            #[doc = concat!("Set the fields of the ", stringify!($name), " group that are not None")]
+            ///
+            /// Only these fields are sent, the other preferences are unchanged.
+            ///
+            pub async fn $setter(&mut self, prefs: $name) -> Result<(), Error> {
+                self.set_preferences(prefs.into()).await
+            }
+        }
+    };
+}
+
+prefs_group! {
+    /// WebUI address, authentication, HTTPS and HTTP headers
+    pub struct WebUiPrefs, webui, set_webui_prefs {
+        web_ui_domain_list: String,
+        web_ui_address: String,
+        web_ui_port: i64,
+        web_ui_upnp: bool,
+        web_ui_username: String,
+        web_ui_password: String,
+        web_ui_csrf_protection_enabled: bool,
+        web_ui_clickjacking_protection_enabled: bool,
+        web_ui_secure_cookie_enabled: bool,
+        web_ui_max_auth_fail_count: i64,
+        web_ui_ban_duration: i64,
+        web_ui_session_timeout: i64,
+        web_ui_host_header_validation_enabled: bool,
+        bypass_local_auth: bool,
+        bypass_auth_subnet_whitelist_enabled: bool,
+        bypass_auth_subnet_whitelist: String,
+        alternative_webui_enabled: bool,
+        alternative_webui_path: String,
+        use_https: bool,
+        web_ui_https_key_path: String,
+        web_ui_https_cert_path: String,
+        web_ui_use_custom_http_headers_enabled: bool,
+        web_ui_custom_http_headers: String,
+    }
+}
+
+prefs_group! {
+    /// Listening port, connection limits, proxy, IP filtering and network interface
+    pub struct ConnectionPrefs, connection, set_connection_prefs {
+        listen_port: i64,
+        upnp: bool,
+        random_port: bool,
+        bittorrent_protocol: BittorrentProtocol,
+        max_connec: i64,
+        max_connec_per_torrent: i64,
+        max_uploads: i64,
+        max_uploads_per_torrent: i64,
+        proxy_type: ProxyType,
+        proxy_ip: String,
+        proxy_port: i64,
+        proxy_peer_connections: bool,
+        proxy_auth_enabled: bool,
+        proxy_username: String,
+        proxy_password: String,
+        proxy_torrents_only: bool,
+        proxy_hostname_lookup: bool,
+        proxy_bittorrent: bool,
+        proxy_rss: bool,
+        proxy_misc: bool,
+        ip_filter_enabled: bool,
+        ip_filter_path: String,
+        ip_filter_trackers: bool,
+        banned_ips: String,
+        current_network_interface: String,
+        current_interface_address: String,
+        outgoing_ports_min: i64,
+        outgoing_ports_max: i64,
+        enable_multi_connections_from_same_ip: bool,
+        upnp_lease_duration: i64,
+    }
+}
+
+prefs_group! {
+    /// Global and alternative speed limits and the alternative speed limits schedule
+    pub struct SpeedPrefs, speed, set_speed_prefs {
+        dl_limit: i64,
+        up_limit: i64,
+        alt_dl_limit: i64,
+        alt_up_limit: i64,
+        limit_utp_rate: bool,
+        limit_tcp_overhead: bool,
+        limit_lan_peers: bool,
+        scheduler_enabled: bool,
+        schedule_from_hour: i64,
+        schedule_from_min: i64,
+        schedule_to_hour: i64,
+        schedule_to_min: i64,
+        scheduler_days: SchedulerDays,
+    }
+}
+
+prefs_group! {
+    /// Peer discovery, encryption, queueing, seeding limits and automatically added trackers
+    pub struct BittorrentPrefs, bittorrent, set_bittorrent_prefs {
+        dht: bool,
+        pex: bool,
+        lsd: bool,
+        encryption: Encryption,
+        anonymous_mode: bool,
+        queueing_enabled: bool,
+        max_active_downloads: i64,
+        max_active_torrents: i64,
+        max_active_uploads: i64,
+        dont_count_slow_torrents: bool,
+        slow_torrent_dl_rate_threshold: i64,
+        slow_torrent_ul_rate_threshold: i64,
+        slow_torrent_inactive_timer: i64,
+        max_ratio_enabled: bool,
+        max_ratio: f64,
+        max_ratio_act: MaxRatioAct,
+        max_seeding_time_enabled: bool,
+        max_seeding_time: i64,
+        add_trackers_enabled: bool,
+        add_trackers: String,
+    }
+}
+
+prefs_group! {
+    /// RSS feeds refresh and automatic downloading
+    pub struct RssPrefs, rss, set_rss_prefs {
+        rss_refresh_interval: i64,
+        rss_max_articles_per_feed: i64,
+        rss_processing_enabled: bool,
+        rss_auto_downloading_enabled: bool,
+        rss_download_repack_proper_episodes: bool,
+        rss_smart_episode_filters: String,
+    }
+}
@@ -0,0 +1,144 @@
+// Probe
+//
+// Single call diagnostic of a WebUI for monitoring: whether it answers, whether it requires a
+// login, the versions it runs and the certificate of an HTTPS WebUI.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    app::BuildInfo,
+    cache::ResponseCache,
+    cert::server_certificate,
+    client::Session,
+    request::{ApiRequest, Method},
+    Client, Error,
+};
+
+/// Time given to the TLS handshake of the probe when the client has no request timeout
+const TLS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Certificate of an HTTPS WebUI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsInfo {
+    /// Issuer distinguished name, e.g. CN=R3, O=Let's Encrypt, C=US
+    pub issuer: String,
+    /// Subject distinguished name, e.g. CN=qbt.example.com
+    pub subject: String,
+    /// End of the validity period (Unix timestamp)
+    pub not_after: i64,
+    /// Whether the certificate is valid for the host and chains to a root of the Mozilla
+    /// root store, false for a self-signed certificate
+    pub trusted: bool,
+}
+
+impl TlsInfo {
+    /// Whether the validity period has ended
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs() as i64);
+        now > self.not_after
+    }
+}
+
+/// State of the server, returned by Client::probe
+#[derive(Debug)]
+pub struct ServerProbe {
+    /// Whether the server answered an HTTP request
+    pub reachable: bool,
+    /// Whether the WebUI is served over HTTPS, from the scheme of the URL
+    pub https: bool,
+    /// Certificate of an HTTPS WebUI, None over HTTP or if the handshake failed
+    pub tls: Option<TlsInfo>,
+    /// Duration of the request without session
+    pub latency: Option<Duration>,
+    /// Whether a request without session is refused. False if authentication is bypassed,
+    /// e.g. for localhost or a whitelisted subnet. None if the answer was neither.
+    pub auth_required: Option<bool>,
+    /// Application version, e.g. v4.6.2
+    pub app_version: Option<String>,
+    /// WebAPI version, e.g. 2.9.3
+    pub api_version: Option<String>,
+    /// Versions of Qt and the libraries the server was built with
+    pub build_info: Option<BuildInfo>,
+    /// First error, None if every request succeeded
+    pub error: Option<Error>,
+}
+
+impl ServerProbe {
+    /// Whether the server answered and every request succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.error.is_none()
+    }
+}
+
+impl Client {
+    /// Probe the server, never failing: errors are reported in ServerProbe::error
+    ///
+    /// Over HTTPS, the certificate is read by a separate handshake first, so it is reported
+    /// even when the transport rejects it, e.g. once expired. A request without session then
+    /// tells whether the server is reachable and requires a login, then the versions and build
+    /// info are requested with the session of the client, logging in with its credentials if
+    /// needed.
+    ///
+    pub async fn probe(&mut self) -> ServerProbe {
+        let mut probe = ServerProbe {
+            reachable: false,
+            https: self.url.scheme() == "https",
+            tls: None,
+            latency: None,
+            auth_required: None,
+            app_version: None,
+            api_version: None,
+            build_info: None,
+            error: None,
+        };
+        if probe.https {
+            let deadline = self
+                .request_deadline()
+                .unwrap_or_else(|| Instant::now() + TLS_TIMEOUT);
+            match server_certificate(&self.url, deadline).await {
+                Ok(tls) => probe.tls = Some(tls),
+                Err(err) => probe.error = Some(err),
+            }
+        }
+        let mut anonymous = self.clone();
+        anonymous.session = Arc::new(Mutex::new(Session::default()));
+        anonymous.cache = ResponseCache::default();
+        anonymous.logout_on_drop = None;
+        anonymous.request_stats = None;
+        let request = ApiRequest {
+            method: Method::Version,
+            arguments: None,
+        };
+        let started = Instant::now();
+        match anonymous.send_request(&request).await {
+            Ok(response) => {
+                probe.reachable = true;
+                probe.latency = Some(started.elapsed());
+                probe.auth_required = match response.status_code().as_u16() {
+                    200 => Some(false),
+                    401 | 403 => Some(true),
+                    _ => None,
+                };
+            }
+            Err(err) => {
+                probe.error.get_or_insert(err);
+                return probe;
+            }
+        }
+
+        let result = async {
+            probe.app_version = Some(self.get_version().await?);
+            probe.api_version = Some(self.get_api_version().await?);
+            probe.build_info = Some(self.get_build_info().await?);
+            Ok::<_, Error>(())
+        }
+        .await;
+        if let Err(err) = result {
+            probe.error.get_or_insert(err);
+        }
+        probe
+    }
+}
@@ -0,0 +1,190 @@
+// Query
+//
+// Filter already fetched torrents locally, with predicates richer than the filters of the
+// torrents/info endpoint.
+
+use std::ops::{Bound, Not, RangeBounds};
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::{
+    maintenance::now,
+    sync::SyncSession,
+    tagging::{domain_matches, tracker_domain},
+    torrents::{State, Torrent},
+    Error,
+};
+
+/// Predicate over torrents, combined with and, or and !
+///
+/// E.g. seeded torrents not tagged keep:
+/// `TorrentQuery::state("stalledUP").and(TorrentQuery::ratio(2.0..)).and(!TorrentQuery::tag("keep"))`
+#[derive(Debug, Clone)]
+pub enum TorrentQuery {
+    /// Every torrent
+    Any,
+    /// Torrents in the state, e.g. stalledUP
+    State(State),
+    /// Torrents in the category, empty for torrents without category
+    Category(String),
+    /// Torrents with the tag
+    Tag(String),
+    /// Torrents whose working tracker is on the domain or one of its subdomains
+    TrackerDomain(String),
+    /// Torrents whose name matches the regular expression
+    Name(Regex),
+    /// Torrents with a share ratio in the range
+    Ratio(Bound<f64>, Bound<f64>),
+    /// Torrents with a selected size (bytes) in the range
+    Size(Bound<i64>, Bound<i64>),
+    /// Torrents added at least this long ago
+    OlderThan(Duration),
+    /// Torrents added less than this long ago
+    NewerThan(Duration),
+    /// Torrents seeded for at least this long, never matches before qBittorrent 4.4
+    SeededFor(Duration),
+    /// Torrents matching all the queries
+    And(Vec<TorrentQuery>),
+    /// Torrents matching at least one of the queries
+    Or(Vec<TorrentQuery>),
+    /// Torrents not matching the query
+    Not(Box<TorrentQuery>),
+}
+
+impl TorrentQuery {
+    pub fn state(state: impl Into<State>) -> Self {
+        TorrentQuery::State(state.into())
+    }
+
+    /// Torrents in any of the states
+    pub fn states(states: &[&str]) -> Self {
+        TorrentQuery::Or(states.iter().map(|state| Self::state(*state)).collect())
+    }
+
+    pub fn category(category: &str) -> Self {
+        TorrentQuery::Category(category.to_string())
+    }
+
+    pub fn tag(tag: &str) -> Self {
+        TorrentQuery::Tag(tag.to_string())
+    }
+
+    /// Torrents with all the tags
+    pub fn all_tags(tags: &[&str]) -> Self {
+        TorrentQuery::And(tags.iter().map(|tag| Self::tag(tag)).collect())
+    }
+
+    /// Torrents with at least one of the tags
+    pub fn any_tag(tags: &[&str]) -> Self {
+        TorrentQuery::Or(tags.iter().map(|tag| Self::tag(tag)).collect())
+    }
+
+    pub fn tracker_domain(domain: &str) -> Self {
+        TorrentQuery::TrackerDomain(domain.to_lowercase())
+    }
+
+    /// Torrents whose name matches pattern, e.g. `(?i)debian.*\.iso`
+    pub fn name_matches(pattern: &str) -> Result<Self, Error> {
+        Ok(TorrentQuery::Name(Regex::new(pattern)?))
+    }
+
+    /// Torrents with a share ratio in range, e.g. `1.0..2.0` or `2.0..`
+    pub fn ratio(range: impl RangeBounds<f64>) -> Self {
+        TorrentQuery::Ratio(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+
+    /// Torrents with a selected size (bytes) in range
+    pub fn size(range: impl RangeBounds<i64>) -> Self {
+        TorrentQuery::Size(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+
+    pub fn older_than(age: Duration) -> Self {
+        TorrentQuery::OlderThan(age)
+    }
+
+    pub fn newer_than(age: Duration) -> Self {
+        TorrentQuery::NewerThan(age)
+    }
+
+    pub fn seeded_for(time: Duration) -> Self {
+        TorrentQuery::SeededFor(time)
+    }
+
+    /// Torrents matching both queries
+    pub fn and(self, other: TorrentQuery) -> Self {
+        match self {
+            TorrentQuery::And(mut queries) => {
+                queries.push(other);
+                TorrentQuery::And(queries)
+            }
+            query => TorrentQuery::And(vec![query, other]),
+        }
+    }
+
+    /// Torrents matching either query
+    pub fn or(self, other: TorrentQuery) -> Self {
+        match self {
+            TorrentQuery::Or(mut queries) => {
+                queries.push(other);
+                TorrentQuery::Or(queries)
+            }
+            query => TorrentQuery::Or(vec![query, other]),
+        }
+    }
+
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        match self {
+            TorrentQuery::Any => true,
+            TorrentQuery::State(state) => torrent.state() == *state,
+            TorrentQuery::Category(category) => torrent.category == *category,
+            TorrentQuery::Tag(tag) => torrent.tags.split(',').any(|t| t.trim() == tag),
+            TorrentQuery::TrackerDomain(domain) => tracker_domain(&torrent.tracker)
+                .is_some_and(|tracker| domain_matches(&tracker, domain)),
+            TorrentQuery::Name(pattern) => pattern.is_match(&torrent.name),
+            TorrentQuery::Ratio(start, end) => (*start, *end).contains(&torrent.ratio),
+            TorrentQuery::Size(start, end) => (*start, *end).contains(&torrent.size),
+            TorrentQuery::OlderThan(age) => added_ago(torrent) >= age.as_secs(),
+            TorrentQuery::NewerThan(age) => added_ago(torrent) < age.as_secs(),
+            TorrentQuery::SeededFor(time) => torrent.seeding_time.is_some_and(|seeding_time| {
+                seeding_time >= 0 && seeding_time as u64 >= time.as_secs()
+            }),
+            TorrentQuery::And(queries) => queries.iter().all(|query| query.matches(torrent)),
+            TorrentQuery::Or(queries) => queries.iter().any(|query| query.matches(torrent)),
+            TorrentQuery::Not(query) => !query.matches(torrent),
+        }
+    }
+
+    /// Torrents matching the query
+    pub fn filter<'a>(&self, torrents: impl IntoIterator<Item = &'a Torrent>) -> Vec<&'a Torrent> {
+        torrents
+            .into_iter()
+            .filter(|torrent| self.matches(torrent))
+            .collect()
+    }
+
+    /// Torrents of the sync session matching the query
+    pub fn filter_session(&self, session: &SyncSession) -> Result<Vec<Torrent>, Error> {
+        Ok(session
+            .torrents()?
+            .into_values()
+            .filter(|torrent| self.matches(torrent))
+            .collect())
+    }
+}
+
+impl Not for TorrentQuery {
+    type Output = TorrentQuery;
+
+    fn not(self) -> Self::Output {
+        match self {
+            TorrentQuery::Not(query) => *query,
+            query => TorrentQuery::Not(Box::new(query)),
+        }
+    }
+}
+
+/// Seconds since the torrent was added
+fn added_ago(torrent: &Torrent) -> u64 {
+    u64::try_from(now() - torrent.added_on).unwrap_or_default()
+}
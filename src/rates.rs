@@ -0,0 +1,238 @@
+// Rates
+//
+// Polling of the global transfer info and history of transfer rates.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::time::{sleep, Instant};
+
+use crate::{stats, transfer::TransferInfo, Client, Error};
+
+/// Interval between the samples of Client::measure_throughput
+const THROUGHPUT_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Global transfer rates at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateSample {
+    /// Time the sample was recorded
+    pub time: SystemTime,
+    /// Global download rate (bytes/s)
+    pub dl_speed: i64,
+    /// Global upload rate (bytes/s)
+    pub up_speed: i64,
+}
+
+/// Distribution of the rates of a set of samples (bytes/s)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateSummary {
+    /// Rates in ascending order
+    rates: Vec<i64>,
+}
+
+impl RateSummary {
+    pub fn new(mut rates: Vec<i64>) -> Self {
+        rates.sort_unstable();
+        Self { rates }
+    }
+
+    pub fn min(&self) -> i64 {
+        self.rates.first().copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> i64 {
+        self.rates.last().copied().unwrap_or(0)
+    }
+
+    pub fn average(&self) -> i64 {
+        if self.rates.is_empty() {
+            return 0;
+        }
+        self.rates.iter().sum::<i64>() / self.rates.len() as i64
+    }
+
+    /// Nearest-rank percentile, percentile from 0.0 to 100.0
+    pub fn percentile(&self, percentile: f64) -> i64 {
+        stats::percentile(&self.rates, percentile)
+    }
+
+    pub fn median(&self) -> i64 {
+        self.percentile(50.0)
+    }
+}
+
+/// Download and upload rates measured over a window
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Throughput {
+    /// Global download rates
+    pub dl: RateSummary,
+    /// Global upload rates
+    pub up: RateSummary,
+    /// Number of samples
+    pub samples: usize,
+    /// Time between the first and the last sample
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    /// Summary of samples, oldest first
+    pub fn from_samples<'a>(samples: impl IntoIterator<Item = &'a RateSample>) -> Self {
+        let samples: Vec<&RateSample> = samples.into_iter().collect();
+        let elapsed = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => last.time.duration_since(first.time).unwrap_or_default(),
+            _ => Duration::ZERO,
+        };
+        Self {
+            dl: RateSummary::new(samples.iter().map(|sample| sample.dl_speed).collect()),
+            up: RateSummary::new(samples.iter().map(|sample| sample.up_speed).collect()),
+            samples: samples.len(),
+            elapsed,
+        }
+    }
+}
+
+/// Ring buffer of the last transfer rate samples, e.g. to draw speed graphs
+#[derive(Debug, Clone)]
+pub struct RateHistory {
+    capacity: usize,
+    samples: VecDeque<RateSample>,
+}
+
+impl RateHistory {
+    /// History keeping the last capacity samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Record the rates of a transfer info, dropping the oldest sample if the history is full
+    pub fn record(&mut self, info: &TransferInfo) {
+        self.push(RateSample {
+            time: SystemTime::now(),
+            dl_speed: info.dl_info_speed,
+            up_speed: info.up_info_speed,
+        });
+    }
+
+    /// Add a sample, dropping the oldest one if the history is full
+    pub fn push(&mut self, sample: RateSample) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Samples from the oldest to the newest
+    pub fn samples(&self) -> impl Iterator<Item = &RateSample> {
+        self.samples.iter()
+    }
+
+    /// Newest sample
+    pub fn latest(&self) -> Option<&RateSample> {
+        self.samples.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Average download rate over the samples (bytes/s)
+    pub fn average_dl_speed(&self) -> i64 {
+        self.average(|sample| sample.dl_speed)
+    }
+
+    /// Average upload rate over the samples (bytes/s)
+    pub fn average_up_speed(&self) -> i64 {
+        self.average(|sample| sample.up_speed)
+    }
+
+    /// Highest download rate of the samples (bytes/s)
+    pub fn peak_dl_speed(&self) -> i64 {
+        self.samples()
+            .map(|sample| sample.dl_speed)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Highest upload rate of the samples (bytes/s)
+    pub fn peak_up_speed(&self) -> i64 {
+        self.samples()
+            .map(|sample| sample.up_speed)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Distribution of the rates of the samples
+    pub fn throughput(&self) -> Throughput {
+        Throughput::from_samples(self.samples())
+    }
+
+    fn average(&self, speed: impl Fn(&RateSample) -> i64) -> i64 {
+        if self.samples.is_empty() {
+            return 0;
+        }
+        self.samples().map(speed).sum::<i64>() / self.samples.len() as i64
+    }
+}
+
+impl Client {
+    /// Poll the global transfer info every interval
+    ///
+    /// The first transfer info is requested immediately. Request errors are yielded and polling
+    /// continues on the next interval. Feed the items to a RateHistory to keep the last rates.
+    ///
+    pub fn transfer_stream(
+        &mut self,
+        interval: Duration,
+    ) -> BoxStream<'_, Result<TransferInfo, Error>> {
+        stream::unfold((self, false), move |(client, polled)| async move {
+            if polled {
+                sleep(interval).await;
+            }
+            let info = client.get_transfer_info().await;
+            Some((info, (client, true)))
+        })
+        .boxed()
+    }
+
+    /// Sample the global transfer rates every 250ms over window and summarize them, e.g. to
+    /// detect throttling or check that alternative speed limits apply
+    ///
+    /// The rates are the ones reported by qBittorrent, which averages them over a few
+    /// seconds. Fails on the first request error.
+    ///
+    pub async fn measure_throughput(&mut self, window: Duration) -> Result<Throughput, Error> {
+        let start = Instant::now();
+        let mut samples = Vec::new();
+        loop {
+            let info = self.get_transfer_info().await?;
+            samples.push(RateSample {
+                time: SystemTime::now(),
+                dl_speed: info.dl_info_speed,
+                up_speed: info.up_info_speed,
+            });
+            if start.elapsed() + THROUGHPUT_SAMPLE_INTERVAL > window {
+                break;
+            }
+            sleep(THROUGHPUT_SAMPLE_INTERVAL).await;
+        }
+        Ok(Throughput::from_samples(&samples))
+    }
+}
@@ -0,0 +1,266 @@
+// Reannounce
+//
+// Watch for stuck torrents and reannounce or recheck them, backing off per torrent.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::time::sleep;
+
+use crate::{
+    maintenance::now,
+    torrents::{GetTorrentList, State, Torrent, TrackerStatus},
+    Client, Error,
+};
+
+/// Settings of Client::reannounce_stuck
+#[derive(Clone, Debug)]
+pub struct ReannounceConfig {
+    /// Interval between checks of the torrent list (default: 1min)
+    pub check_interval: Duration,
+    /// Minimum time without activity of a stalledDL, metaDL or forcedMetaDL torrent
+    /// (default: 10min)
+    pub stalled_after: Duration,
+    /// Also act on active torrents without a working tracker, fetching their trackers
+    /// (default: true)
+    pub check_trackers: bool,
+    /// Tracker lists fetched at once when checking trackers (default: 4)
+    pub tracker_concurrency: usize,
+    /// Wait before acting again on a torrent still stuck (default: 5min)
+    pub initial_backoff: Duration,
+    /// Maximum wait between actions on a torrent, the wait doubles after each action
+    /// (default: 1h)
+    pub max_backoff: Duration,
+    /// Recheck instead of reannouncing after this many reannounces without recovery
+    /// (default: none)
+    pub recheck_after: Option<u32>,
+}
+
+impl Default for ReannounceConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(60),
+            stalled_after: Duration::from_secs(600),
+            check_trackers: true,
+            tracker_concurrency: 4,
+            initial_backoff: Duration::from_secs(300),
+            max_backoff: Duration::from_secs(3600),
+            recheck_after: None,
+        }
+    }
+}
+
+impl ReannounceConfig {
+    /// Wait after the given action on a torrent (1 based)
+    pub fn backoff(&self, action: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(action.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Why a torrent is considered stuck
+#[derive(Debug, Clone, PartialEq)]
+pub enum StuckReason {
+    /// stalledDL, metaDL or forcedMetaDL without activity for this long
+    Stalled { idle: Duration },
+    /// No tracker is working, tracker is the first one reported NotWorking
+    TrackerNotWorking { tracker: String },
+}
+
+/// Action reported by Client::reannounce_stuck
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReannounceEvent {
+    /// The torrent was reannounced, attempt counts the actions on the torrent (1 based)
+    Reannounced {
+        hash: String,
+        name: String,
+        reason: StuckReason,
+        attempt: u32,
+    },
+    /// The torrent was rechecked
+    Rechecked {
+        hash: String,
+        name: String,
+        reason: StuckReason,
+        attempt: u32,
+    },
+    /// A torrent acted on is not stuck anymore, or was removed
+    Recovered { hash: String },
+}
+
+/// Actions taken on a stuck torrent
+struct Attempts {
+    count: u32,
+    next: Instant,
+}
+
+struct ReannounceState<'a> {
+    client: &'a mut Client,
+    config: ReannounceConfig,
+    attempts: HashMap<String, Attempts>,
+    pending: VecDeque<ReannounceEvent>,
+    checked: bool,
+}
+
+impl ReannounceState<'_> {
+    /// Stalled reason of a stalledDL, metaDL or forcedMetaDL torrent idle for stalled_after
+    fn stalled(&self, torrent: &Torrent) -> Option<StuckReason> {
+        let state = torrent.state();
+        if state != State::StalledDL && !state.is_fetching_metadata() {
+            return None;
+        }
+        let since = if torrent.last_activity > 0 {
+            torrent.last_activity
+        } else {
+            torrent.added_on
+        };
+        let idle = Duration::from_secs((now() - since).max(0) as u64);
+        (idle >= self.config.stalled_after).then_some(StuckReason::Stalled { idle })
+    }
+
+    /// Whether the trackers of an active torrent without a working tracker are checked
+    fn needs_tracker_check(&self, torrent: &Torrent) -> bool {
+        let active = matches!(
+            torrent.state(),
+            State::Downloading
+                | State::ForceDL
+                | State::Uploading
+                | State::ForcedUP
+                | State::StalledUP
+        );
+        self.config.check_trackers && active && torrent.tracker.is_empty()
+    }
+
+    async fn check(&mut self) -> Result<(), Error> {
+        let torrents = self
+            .client
+            .get_torrent_list(GetTorrentList::default())
+            .await?;
+        let mut stuck = Vec::new();
+        let mut unchecked = Vec::new();
+        for torrent in &torrents {
+            let Some(hash) = torrent.id() else {
+                continue;
+            };
+            if let Some(reason) = self.stalled(torrent) {
+                stuck.push((torrent, reason));
+            } else if self.needs_tracker_check(torrent) {
+                unchecked.push((torrent, hash));
+            }
+        }
+        let trackers = self
+            .client
+            .concurrent(self.config.tracker_concurrency)
+            .try_map(unchecked.iter(), |mut client, (_, hash)| async move {
+                client.get_torrent_trackers(hash).await
+            })
+            .await?;
+        for ((torrent, _), trackers) in unchecked.into_iter().zip(trackers) {
+            if let Some(tracker) = trackers
+                .into_iter()
+                .find(|tracker| tracker.status == TrackerStatus::NotWorking)
+            {
+                let tracker = tracker.url;
+                stuck.push((torrent, StuckReason::TrackerNotWorking { tracker }));
+            }
+        }
+
+        let stuck_hashes: Vec<&str> = stuck
+            .iter()
+            .filter_map(|(torrent, _)| torrent.id())
+            .collect();
+        let recovered: Vec<String> = self
+            .attempts
+            .keys()
+            .filter(|hash| !stuck_hashes.contains(&hash.as_str()))
+            .cloned()
+            .collect();
+        for hash in recovered {
+            self.attempts.remove(&hash);
+            self.pending.push_back(ReannounceEvent::Recovered { hash });
+        }
+
+        let now = Instant::now();
+        for (torrent, reason) in stuck {
+            let hash = torrent.id().unwrap_or_default().to_string();
+            let attempts = self.attempts.entry(hash.clone()).or_insert(Attempts {
+                count: 0,
+                next: now,
+            });
+            if attempts.next > now {
+                continue;
+            }
+            attempts.count += 1;
+            attempts.next = now + self.config.backoff(attempts.count);
+            let attempt = attempts.count;
+            let recheck = self
+                .config
+                .recheck_after
+                .is_some_and(|after| attempt > after);
+            let name = torrent.name.clone();
+            if recheck {
+                ::log::debug!("recheck stuck torrent {hash} ({reason:?})");
+                self.client.recheck_torrent(vec![&hash]).await?;
+                self.pending.push_back(ReannounceEvent::Rechecked {
+                    hash,
+                    name,
+                    reason,
+                    attempt,
+                });
+            } else {
+                ::log::debug!("reannounce stuck torrent {hash} ({reason:?})");
+                self.client.reannounce_torrent(vec![&hash]).await?;
+                self.pending.push_back(ReannounceEvent::Reannounced {
+                    hash,
+                    name,
+                    reason,
+                    attempt,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Reannounce stuck torrents, yielding the actions taken
+    ///
+    /// Checks the torrent list every check_interval. Torrents stalled for stalled_after, or
+    /// active without a working tracker, are reannounced; once recheck_after reannounces
+    /// didn't help, they are rechecked instead. Each torrent waits a backoff doubling after
+    /// every action before the next one. Request errors are yielded and checking continues on
+    /// the next interval.
+    ///
+    pub fn reannounce_stuck(
+        &mut self,
+        config: ReannounceConfig,
+    ) -> BoxStream<'_, Result<ReannounceEvent, Error>> {
+        let state = ReannounceState {
+            client: self,
+            config,
+            attempts: HashMap::new(),
+            pending: VecDeque::new(),
+            checked: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                if state.checked {
+                    sleep(state.config.check_interval).await;
+                }
+                state.checked = true;
+                if let Err(err) = state.check().await {
+                    return Some((Err(err), state));
+                }
+            }
+        })
+        .boxed()
+    }
+}
@@ -0,0 +1,212 @@
+// Record
+//
+// Requests and responses of a client saved to a directory by the Recorder middleware (record
+// feature), and served back by MockQbittorrent::replay (test-util feature) for hermetic tests
+// against captured server behavior.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Form fields replaced by "***" in the recordings
+pub const SANITIZED_FIELDS: [&str; 2] = ["password", "cookie"];
+
+/// Keys of JSON objects whose values are replaced by "***" in the recordings, at any depth of
+/// the JSON form fields and response bodies, e.g. of app/setPreferences and app/preferences.
+/// Keys ending with password are replaced too.
+pub const SANITIZED_JSON_KEYS: [&str; 3] = [
+    "proxy_username",
+    "mail_notification_username",
+    "dyndns_username",
+];
+
+/// Request and response pair, saved as a JSON file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Endpoint relative to /api/v2/, e.g. app/version
+    pub path: String,
+    /// Form parameters of the request, credentials sanitized, empty for multipart uploads
+    pub form: BTreeMap<String, String>,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// content-type and set-cookie headers of the response, session cookie sanitized
+    pub headers: Vec<(String, String)>,
+    /// Response body, decompressed, base64 encoded if it is not UTF-8
+    pub body: String,
+    /// Whether body is base64 encoded
+    #[serde(default)]
+    pub base64: bool,
+}
+
+impl Exchange {
+    /// Raw response body
+    pub fn body_bytes(&self) -> Result<Vec<u8>, Error> {
+        if self.base64 {
+            STANDARD
+                .decode(&self.body)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err).into())
+        } else {
+            Ok(self.body.clone().into_bytes())
+        }
+    }
+}
+
+/// Exchanges saved in dir, in recording order
+pub fn load(dir: impl AsRef<Path>) -> Result<Vec<Exchange>, Error> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "json")
+    });
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| Ok(serde_json::from_slice(&std::fs::read(path)?)?))
+        .collect()
+}
+
+#[cfg(feature = "record")]
+pub use recorder::Recorder;
+
+#[cfg(feature = "record")]
+mod recorder {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde_json::Value;
+
+    use super::{Exchange, SANITIZED_FIELDS, SANITIZED_JSON_KEYS};
+    use crate::{
+        middleware::{Middleware, OutgoingRequest, Response},
+        response::response_body,
+        Error,
+    };
+
+    /// Middleware saving every request and response of a client to a directory, see
+    /// ClientBuilder::middleware
+    ///
+    /// Each exchange is written to a numbered JSON file, so a directory should hold a single
+    /// recording session. Write errors are logged and don't fail the requests.
+    #[derive(Debug, Clone)]
+    pub struct Recorder {
+        dir: PathBuf,
+        sequence: Arc<AtomicUsize>,
+    }
+
+    impl Recorder {
+        /// Record into dir, created if missing
+        pub fn new(dir: impl Into<PathBuf>) -> Result<Self, Error> {
+            let dir = dir.into();
+            std::fs::create_dir_all(&dir)?;
+            Ok(Self {
+                dir,
+                sequence: Arc::default(),
+            })
+        }
+
+        fn write(&self, exchange: &Exchange) -> Result<(), Error> {
+            let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+            let name = format!("{sequence:05}-{}.json", exchange.path.replace('/', "_"));
+            std::fs::write(self.dir.join(name), serde_json::to_vec_pretty(exchange)?)?;
+            Ok(())
+        }
+    }
+
+    impl Middleware for Recorder {
+        fn on_response(&self, request: &OutgoingRequest, response: &Response) {
+            // Multipart uploads are not recorded field by field
+            let form_body: &[u8] = if request.content_type.starts_with("multipart/") {
+                &[]
+            } else {
+                &request.body
+            };
+            let form = url::form_urlencoded::parse(form_body)
+                .into_owned()
+                .map(|(name, value)| {
+                    if SANITIZED_FIELDS.contains(&name.as_str()) {
+                        (name, "***".to_string())
+                    } else {
+                        let value = sanitize_json_text(value.as_bytes())
+                            .and_then(|json| String::from_utf8(json).ok())
+                            .unwrap_or(value);
+                        (name, value)
+                    }
+                })
+                .collect();
+            let mut headers = Vec::new();
+            if let Some(content_type) = response.headers.get("content-type") {
+                headers.push(("content-type".to_string(), content_type.to_string()));
+            }
+            if let Some(set_cookie) = response.headers.get("set-cookie") {
+                headers.push(("set-cookie".to_string(), sanitize_cookie(set_cookie)));
+            }
+            let body = response_body(response)
+                .map(|body| body.to_vec())
+                .unwrap_or_else(|_| response.body().as_ref().to_vec());
+            let body = sanitize_json_text(&body).unwrap_or(body);
+            let (body, base64) = match String::from_utf8(body) {
+                Ok(body) => (body, false),
+                Err(err) => (STANDARD.encode(err.as_bytes()), true),
+            };
+            let exchange = Exchange {
+                path: request.path.clone(),
+                form,
+                status: response.status_code().as_u16(),
+                headers,
+                body,
+                base64,
+            };
+            if let Err(err) = self.write(&exchange) {
+                ::log::debug!("cannot record {}: {err}", request.path);
+            }
+        }
+    }
+
+    /// JSON text with the secret values replaced, None if text is not JSON or has no secret
+    fn sanitize_json_text(text: &[u8]) -> Option<Vec<u8>> {
+        let mut value: Value = serde_json::from_slice(text).ok()?;
+        sanitize_json(&mut value)
+            .then(|| serde_json::to_vec(&value).ok())
+            .flatten()
+    }
+
+    /// Replace the values of the secret keys at any depth of value, whether any was replaced
+    fn sanitize_json(value: &mut Value) -> bool {
+        match value {
+            Value::Object(object) => {
+                let mut sanitized = false;
+                for (key, value) in object.iter_mut() {
+                    if key.ends_with("password") || SANITIZED_JSON_KEYS.contains(&key.as_str()) {
+                        *value = Value::String("***".to_string());
+                        sanitized = true;
+                    } else {
+                        sanitized |= sanitize_json(value);
+                    }
+                }
+                sanitized
+            }
+            Value::Array(values) => values
+                .iter_mut()
+                .fold(false, |sanitized, value| sanitize_json(value) | sanitized),
+            _ => false,
+        }
+    }
+
+    /// Replace the values of a set-cookie header, keeping the cookie names and attributes
+    fn sanitize_cookie(set_cookie: &str) -> String {
+        let (cookie, attributes) = set_cookie.split_once(';').unwrap_or((set_cookie, ""));
+        let name = cookie.split('=').next().unwrap_or_default().trim();
+        match attributes {
+            "" => format!("{name}=recorded"),
+            attributes => format!("{name}=recorded;{attributes}"),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// Relocate
+//
+// Move the data of torrents to a new location and follow the moves until they are done.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::time::sleep;
+
+use crate::{
+    categories::same_path,
+    torrents::{GetTorrentList, State},
+    Client, Error,
+};
+
+/// Progress reported by Client::move_torrents
+#[derive(Debug)]
+pub enum MoveEvent {
+    /// qBittorrent started moving the data of the torrent
+    Moving { hash: String, name: String },
+    /// The data of the torrent is in the new location
+    Moved { hash: String, save_path: String },
+    /// The torrent was not moved: setLocation failed, the torrent was removed
+    /// (Error::NoTorrentHash), the move ended at another location (Error::MoveFailed) or
+    /// didn't end within the max wait (Error::WaitTimeout)
+    Failed { hash: String, error: Error },
+}
+
+struct MoveState<'a> {
+    client: &'a mut Client,
+    location: String,
+    poll_interval: Duration,
+    /// Moves not ended by then fail, None if the max wait overflows
+    deadline: Option<Instant>,
+    /// Torrents to send setLocation for
+    to_start: VecDeque<String>,
+    /// Torrents whose move is not finished
+    moving: Vec<String>,
+    /// Torrents seen in the moving state
+    seen_moving: HashSet<String>,
+    pending: VecDeque<MoveEvent>,
+    polled: bool,
+}
+
+impl MoveState<'_> {
+    async fn poll(&mut self) -> Result<(), Error> {
+        let list = GetTorrentList::builder().hashes(&self.moving).build();
+        let torrents = self.client.get_torrent_list(list).await?;
+        let mut still_moving = Vec::new();
+        for hash in std::mem::take(&mut self.moving) {
+            let Some(torrent) = torrents.iter().find(|torrent| torrent.id() == Some(&hash)) else {
+                let error = Error::NoTorrentHash;
+                self.pending.push_back(MoveEvent::Failed { hash, error });
+                continue;
+            };
+            if torrent.state() == State::Moving {
+                if self.seen_moving.insert(hash.clone()) {
+                    let name = torrent.name.clone();
+                    self.pending.push_back(MoveEvent::Moving {
+                        hash: hash.clone(),
+                        name,
+                    });
+                }
+                still_moving.push(hash);
+            } else if same_path(&torrent.save_path, &self.location) {
+                let save_path = torrent.save_path.clone();
+                self.pending.push_back(MoveEvent::Moved { hash, save_path });
+            } else if self.seen_moving.contains(&hash) {
+                let error = Error::MoveFailed;
+                self.pending.push_back(MoveEvent::Failed { hash, error });
+            } else {
+                // The move may be queued behind other moves
+                still_moving.push(hash);
+            }
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            for hash in still_moving.drain(..) {
+                let error = Error::WaitTimeout;
+                self.pending.push_back(MoveEvent::Failed { hash, error });
+            }
+        }
+        self.moving = still_moving;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Move the data of torrents to location, yielding their progress until every move ended
+    ///
+    /// setLocation is sent for each torrent separately, so a failure is reported as
+    /// MoveEvent::Failed for that torrent only. The torrents are then polled every
+    /// poll_interval until none of them is in the moving state. Moves queued by qBittorrent
+    /// are waited for, up to max_wait from the call: the torrents still moving then are
+    /// reported failed with Error::WaitTimeout. Request errors while polling are yielded and
+    /// polling continues.
+    ///
+    pub fn move_torrents(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        location: &str,
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> BoxStream<'_, Result<MoveEvent, Error>> {
+        let state = MoveState {
+            client: self,
+            location: location.to_string(),
+            poll_interval,
+            deadline: Instant::now().checked_add(max_wait),
+            to_start: hashes
+                .into_iter()
+                .map(|hash| hash.as_ref().to_string())
+                .collect(),
+            moving: Vec::new(),
+            seen_moving: HashSet::new(),
+            pending: VecDeque::new(),
+            polled: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                if let Some(hash) = state.to_start.pop_front() {
+                    match state
+                        .client
+                        .set_location(vec![&hash], &state.location)
+                        .await
+                    {
+                        Ok(()) => state.moving.push(hash),
+                        Err(error) => state.pending.push_back(MoveEvent::Failed { hash, error }),
+                    }
+                    continue;
+                }
+                if state.moving.is_empty() {
+                    return None;
+                }
+                if state.polled {
+                    sleep(state.poll_interval).await;
+                }
+                state.polled = true;
+                if let Err(err) = state.poll().await {
+                    return Some((Err(err), state));
+                }
+            }
+        })
+        .boxed()
+    }
+}
@@ -0,0 +1,119 @@
+// Rename
+//
+// Rename the files of a torrent in bulk, from a list of paths or a regular expression, checking
+// the new paths against the files of the torrent before each renameFile request.
+
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use crate::{Client, Error};
+
+/// Rename of a file by Client::rename_files and Client::rename_files_regex
+#[derive(Debug)]
+pub struct FileRename {
+    /// Path of the file before the rename
+    pub old_path: String,
+    /// Requested path of the file
+    pub new_path: String,
+    /// Error::NoFile if the file is not in the torrent, Error::RenameConflict if the new path
+    /// is already used or rejected by qBittorrent, Error::EmptyPath if it's empty
+    pub result: Result<(), Error>,
+}
+
+impl FileRename {
+    /// Whether the file was renamed
+    pub fn is_renamed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+impl Client {
+    /// Rename the files of the torrent from their old to their new path, in order
+    ///
+    /// Paths are relative to the save path as listed by get_torrent_contents, e.g.
+    /// `Show/E01.mkv`. A rename to a path used by another file, including a file renamed
+    /// before it, fails with Error::RenameConflict without a request, so nothing is overwritten.
+    /// Failures of a file don't stop the other renames, their error is in the result of the
+    /// file. Other errors, e.g. an unknown hash, are returned.
+    ///
+    pub async fn rename_files(
+        &mut self,
+        hash: &str,
+        renames: &[(&str, &str)],
+    ) -> Result<Vec<FileRename>, Error> {
+        let mut paths = self.file_paths(hash).await?;
+        let renames = renames
+            .iter()
+            .map(|(old_path, new_path)| (old_path.to_string(), new_path.to_string()))
+            .collect();
+        self.rename_paths(hash, &mut paths, renames).await
+    }
+
+    /// Rename the files of the torrent whose path matches pattern, replacing the matches with
+    /// replacement, e.g. `(?i)\.jpeg$` and `.jpg`
+    ///
+    /// replacement can refer to the capture groups as $1 or ${name}, see
+    /// regex::Regex::replace_all. Files whose path doesn't change are left out of the results,
+    /// conflicts are handled as by rename_files.
+    ///
+    pub async fn rename_files_regex(
+        &mut self,
+        hash: &str,
+        pattern: &str,
+        replacement: &str,
+    ) -> Result<Vec<FileRename>, Error> {
+        let pattern = Regex::new(pattern)?;
+        let mut paths = self.file_paths(hash).await?;
+        let mut sorted: Vec<&String> = paths.iter().collect();
+        sorted.sort();
+        let renames = sorted
+            .into_iter()
+            .filter_map(|path| {
+                let new_path = pattern.replace_all(path, replacement);
+                (new_path != path.as_str()).then(|| (path.clone(), new_path.into_owned()))
+            })
+            .collect();
+        self.rename_paths(hash, &mut paths, renames).await
+    }
+
+    async fn file_paths(&mut self, hash: &str) -> Result<HashSet<String>, Error> {
+        let files = self.get_torrent_contents(hash, "").await?;
+        Ok(files.into_iter().map(|file| file.name).collect())
+    }
+
+    async fn rename_paths(
+        &mut self,
+        hash: &str,
+        paths: &mut HashSet<String>,
+        renames: Vec<(String, String)>,
+    ) -> Result<Vec<FileRename>, Error> {
+        let mut results = Vec::with_capacity(renames.len());
+        for (old_path, new_path) in renames {
+            let result = if !paths.contains(&old_path) {
+                Err(Error::NoFile)
+            } else if old_path == new_path {
+                Ok(())
+            } else if paths.contains(&new_path) {
+                Err(Error::RenameConflict)
+            } else {
+                match self.rename_file(hash, &old_path, &new_path).await {
+                    Ok(()) => {
+                        paths.remove(&old_path);
+                        paths.insert(new_path.clone());
+                        Ok(())
+                    }
+                    Err(error @ (Error::RenameConflict | Error::EmptyPath)) => Err(error),
+                    Err(error) => return Err(error),
+                }
+            };
+            ::log::debug!("rename {old_path} to {new_path}: {result:?}");
+            results.push(FileRename {
+                old_path,
+                new_path,
+                result,
+            });
+        }
+        Ok(results)
+    }
+}
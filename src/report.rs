@@ -0,0 +1,166 @@
+// Report
+//
+// Reports computed from the torrent list.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    stats::percentile,
+    tagging::tracker_domain,
+    torrents::{GetTorrentList, Torrent},
+    Client, Error,
+};
+
+/// How torrents are grouped in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GroupBy {
+    /// Group by torrent category
+    Category,
+    /// Group by the domain of the working tracker
+    Tracker,
+}
+
+/// Time-to-complete statistics of one group (seconds)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionStats {
+    /// Category name or tracker domain. Empty if the torrent has no category or working tracker
+    pub group: String,
+    /// Number of torrents completed in the window
+    pub count: usize,
+    /// Fastest completion
+    pub min: i64,
+    /// Median completion
+    pub p50: i64,
+    /// 90th percentile
+    pub p90: i64,
+    /// 95th percentile
+    pub p95: i64,
+    /// 99th percentile
+    pub p99: i64,
+    /// Slowest completion
+    pub max: i64,
+    /// Average completion
+    pub mean: f64,
+}
+
+/// Time-to-complete distribution per category or tracker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionReport {
+    /// Start of the window (Unix Epoch), inclusive
+    pub from: i64,
+    /// End of the window (Unix Epoch), inclusive
+    pub to: i64,
+    /// Grouping used for the report
+    pub group_by: GroupBy,
+    /// Statistics of each group, sorted by group name
+    pub groups: Vec<CompletionStats>,
+}
+
+impl CompletionStats {
+    fn new(group: String, mut durations: Vec<i64>) -> Self {
+        durations.sort_unstable();
+        let count = durations.len();
+        let sum: i64 = durations.iter().sum();
+        Self {
+            group,
+            count,
+            min: durations[0],
+            p50: percentile(&durations, 50.0),
+            p90: percentile(&durations, 90.0),
+            p95: percentile(&durations, 95.0),
+            p99: percentile(&durations, 99.0),
+            max: durations[count - 1],
+            mean: sum as f64 / count as f64,
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl CompletionReport {
+    /// Build a report from torrents completed between from and to (Unix Epoch, inclusive)
+    ///
+    /// Torrents that are not completed or have inconsistent timestamps are skipped.
+    pub fn from_torrents(torrents: &[Torrent], group_by: GroupBy, from: i64, to: i64) -> Self {
+        let mut durations: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+        for torrent in torrents {
+            if torrent.completion_on <= 0
+                || torrent.completion_on < torrent.added_on
+                || torrent.completion_on < from
+                || torrent.completion_on > to
+            {
+                continue;
+            }
+            let group = match group_by {
+                GroupBy::Category => torrent.category.clone(),
+                GroupBy::Tracker => tracker_domain(&torrent.tracker).unwrap_or_default(),
+            };
+            durations
+                .entry(group)
+                .or_default()
+                .push(torrent.completion_on - torrent.added_on);
+        }
+        let groups = durations
+            .into_iter()
+            .map(|(group, durations)| CompletionStats::new(group, durations))
+            .collect();
+        Self {
+            from,
+            to,
+            group_by,
+            groups,
+        }
+    }
+
+    /// Export the report as JSON
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Export the groups as CSV with a header row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("group,count,min,p50,p90,p95,p99,max,mean\n");
+        for stats in &self.groups {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.1}\n",
+                csv_field(&stats.group),
+                stats.count,
+                stats.min,
+                stats.p50,
+                stats.p90,
+                stats.p95,
+                stats.p99,
+                stats.max,
+                stats.mean
+            ));
+        }
+        csv
+    }
+}
+
+impl Client {
+    /// Get time-to-complete statistics of torrents completed between from and to (Unix Epoch, inclusive)
+    ///
+    /// Durations are computed as completion_on - added_on, grouped by category or tracker domain.
+    ///
+    pub async fn get_completion_report(
+        &mut self,
+        group_by: GroupBy,
+        from: i64,
+        to: i64,
+    ) -> Result<CompletionReport, Error> {
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        Ok(CompletionReport::from_torrents(
+            &torrents, group_by, from, to,
+        ))
+    }
+}
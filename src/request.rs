@@ -1,13 +1,20 @@
 use std::convert::From;
 use std::fmt;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
 use bytes::Bytes;
+use futures_util::future::{select, Either};
 use netc::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::time::timeout_at;
 
+use crate::auth::{login_request, login_status};
 use crate::client::Client;
 use crate::error::Error;
+use crate::form::{json_to_form, Multipart};
 
 // use crate::response::{BlocklistUpdate, FreeSpace, PortTest, RpcResponse};
 
@@ -23,6 +30,32 @@ pub struct ApiRequest {
 pub enum Arguments {
     Json(Value),
     Form(String),
+    /// multipart/form-data body, for file uploads
+    #[serde(skip)]
+    Multipart(Multipart),
+}
+
+/// Content-Type of form encoded bodies
+pub(crate) const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+impl ApiRequest {
+    /// Body of the request, form encoded unless the arguments are multipart
+    pub(crate) fn body(&self) -> Bytes {
+        match &self.arguments {
+            Some(Arguments::Json(value)) => json_to_form(value).into(),
+            Some(Arguments::Form(value)) => value.clone().into(),
+            Some(Arguments::Multipart(form)) => form.body(),
+            None => Bytes::new(),
+        }
+    }
+
+    /// Content-Type header of the body
+    pub(crate) fn content_type(&self) -> String {
+        match &self.arguments {
+            Some(Arguments::Multipart(form)) => form.content_type(),
+            _ => FORM_CONTENT_TYPE.to_string(),
+        }
+    }
 }
 
 // #[derive(Debug, Serialize, Deserialize)]
@@ -100,7 +133,7 @@ pub enum Arguments {
 //     }
 // }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Method {
     Login,
     Logout,
@@ -111,6 +144,10 @@ pub enum Method {
     Preferences,
     SetPreferences,
     DefaultSavePath,
+    NetworkInterfaceList,
+    NetworkInterfaceAddressList,
+    SendTestEmail,
+    GetDirectoryContent,
     Main,
     Peers,
     MainData,
@@ -118,24 +155,51 @@ pub enum Method {
     TransferInfo,
     SpeedLimitsMode,
     ToggleSpeedLimitsMode,
+    SetSpeedLimitsMode,
     DownloadLimit,
     SetDownloadLimit,
     UploadLimit,
     SetUploadLimit,
     BanPeers,
     TorrentsInfo,
+    TorrentsCount,
     Properties,
     Trackers,
     Webseeds,
+    AddWebSeeds,
+    EditWebSeed,
+    RemoveWebSeeds,
     Files,
     PieceStates,
     PieceHashes,
     Pause,
     Resume,
+    Start,
     Delete,
     Recheck,
     Reannounce,
     Add,
+    AddTags,
+    Tags,
+    CreateTags,
+    AddPeers,
+    SetTorrentDownloadLimit,
+    SetTorrentUploadLimit,
+    SetCategory,
+    Categories,
+    CreateCategory,
+    EditCategory,
+    RemoveCategories,
+    SetLocation,
+    FilePrio,
+    RenameFile,
+    SetSavePath,
+    SetDownloadPath,
+    Export,
+    CreatorAddTask,
+    CreatorStatus,
+    CreatorTorrentFile,
+    CreatorDeleteTask,
 }
 
 impl fmt::Display for Method {
@@ -150,6 +214,10 @@ impl fmt::Display for Method {
             Method::Preferences => write!(f, "app/preferences"),
             Method::SetPreferences => write!(f, "app/setPreferences"),
             Method::DefaultSavePath => write!(f, "app/defaultSavePath"),
+            Method::NetworkInterfaceList => write!(f, "app/networkInterfaceList"),
+            Method::NetworkInterfaceAddressList => write!(f, "app/networkInterfaceAddressList"),
+            Method::SendTestEmail => write!(f, "app/sendTestEmail"),
+            Method::GetDirectoryContent => write!(f, "app/getDirectoryContent"),
             Method::Main => write!(f, "log/main"),
             Method::Peers => write!(f, "log/peers"),
             Method::MainData => write!(f, "sync/maindata"),
@@ -157,50 +225,182 @@ impl fmt::Display for Method {
             Method::TransferInfo => write!(f, "transfer/info"),
             Method::SpeedLimitsMode => write!(f, "transfer/speedLimitsMode"),
             Method::ToggleSpeedLimitsMode => write!(f, "transfer/toggleSpeedLimitsMode"),
+            Method::SetSpeedLimitsMode => write!(f, "transfer/setSpeedLimitsMode"),
             Method::DownloadLimit => write!(f, "transfer/downloadLimit"),
             Method::SetDownloadLimit => write!(f, "transfer/setDownloadLimit"),
             Method::UploadLimit => write!(f, "transfer/uploadLimit"),
             Method::SetUploadLimit => write!(f, "transfer/setUploadLimit"),
             Method::BanPeers => write!(f, "transfer/banPeers"),
             Method::TorrentsInfo => write!(f, "torrents/info"),
+            Method::TorrentsCount => write!(f, "torrents/count"),
             Method::Properties => write!(f, "torrents/properties"),
             Method::Trackers => write!(f, "torrents/trackers"),
             Method::Webseeds => write!(f, "torrents/webseeds"),
+            Method::AddWebSeeds => write!(f, "torrents/addWebSeeds"),
+            Method::EditWebSeed => write!(f, "torrents/editWebSeed"),
+            Method::RemoveWebSeeds => write!(f, "torrents/removeWebSeeds"),
             Method::Files => write!(f, "torrents/files"),
             Method::PieceStates => write!(f, "torrents/pieceStates"),
             Method::PieceHashes => write!(f, "torrents/pieceHashes"),
             Method::Pause => write!(f, "torrents/pause"),
             Method::Resume => write!(f, "torrents/resume"),
+            Method::Start => write!(f, "torrents/start"),
             Method::Delete => write!(f, "torrents/delete"),
             Method::Recheck => write!(f, "torrents/recheck"),
             Method::Reannounce => write!(f, "torrents/reannounce"),
             Method::Add => write!(f, "torrents/add"),
+            Method::AddTags => write!(f, "torrents/addTags"),
+            Method::Tags => write!(f, "torrents/tags"),
+            Method::CreateTags => write!(f, "torrents/createTags"),
+            Method::AddPeers => write!(f, "torrents/addPeers"),
+            Method::SetTorrentDownloadLimit => write!(f, "torrents/setDownloadLimit"),
+            Method::SetTorrentUploadLimit => write!(f, "torrents/setUploadLimit"),
+            Method::SetCategory => write!(f, "torrents/setCategory"),
+            Method::Categories => write!(f, "torrents/categories"),
+            Method::CreateCategory => write!(f, "torrents/createCategory"),
+            Method::EditCategory => write!(f, "torrents/editCategory"),
+            Method::RemoveCategories => write!(f, "torrents/removeCategories"),
+            Method::SetLocation => write!(f, "torrents/setLocation"),
+            Method::FilePrio => write!(f, "torrents/filePrio"),
+            Method::RenameFile => write!(f, "torrents/renameFile"),
+            Method::SetSavePath => write!(f, "torrents/setSavePath"),
+            Method::SetDownloadPath => write!(f, "torrents/setDownloadPath"),
+            Method::Export => write!(f, "torrents/export"),
+            Method::CreatorAddTask => write!(f, "torrentcreator/addTask"),
+            Method::CreatorStatus => write!(f, "torrentcreator/status"),
+            Method::CreatorTorrentFile => write!(f, "torrentcreator/torrentFile"),
+            Method::CreatorDeleteTask => write!(f, "torrentcreator/deleteTask"),
         }
     }
 }
 
+impl Method {
+    /// Whether sending the request twice has the same effect as sending it once
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(
+            self,
+            Method::Login
+                | Method::Shutdown
+                | Method::ToggleSpeedLimitsMode
+                | Method::Delete
+                | Method::Add
+                | Method::SendTestEmail
+                | Method::CreatorAddTask
+        )
+    }
+}
+
+/// ID of the next request sent by any client of the process
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
 impl Client {
+    /// Send a request, logging in first if needed
+    ///
+    /// Fails with Error::Timeout once the deadline or the request timeout of the client has
+    /// passed, and with Error::Cancelled once its cancellation token is cancelled. Dropping the
+    /// future is safe: the connection is closed and the session cookie is only updated from a
+    /// complete login response. Requests are counted in the request statistics if enabled (see
+    /// ClientBuilder::request_stats).
+    ///
+    /// Every request gets a process wide ID, logged with its endpoint when it starts and fails,
+    /// and added to its errors with Error::InContext if enabled (see
+    /// ClientBuilder::error_context).
+    ///
     pub async fn send_request(&mut self, input: &ApiRequest) -> Result<Response, Error> {
-        let body = match &input.arguments {
-            Some(Arguments::Json(value)) => {
-                let mut buf = vec![];
-                serde_json::to_writer(&mut buf, value)?;
-                buf.into()
+        let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        self.last_request = Some((input.method, request_id));
+        ::log::debug!("request {request_id}: {}", input.method);
+        let started = Instant::now();
+        let body = input.body();
+        let result = self.send_cancellable(input, &body).await;
+        if let Some(stats) = &self.request_stats {
+            stats.record(input.method, started.elapsed(), body.len(), &result);
+        }
+        result.map_err(|err| {
+            ::log::debug!("request {request_id}: {} failed: {err}", input.method);
+            self.in_context(err)
+        })
+    }
+
+    /// err in Error::InContext with the endpoint and ID of the last request of this client, if
+    /// enabled (see ClientBuilder::error_context)
+    ///
+    /// Wraps the errors of send_request and the ones derived from its response, e.g.
+    /// Error::NoTorrentHash for a 404 status.
+    pub(crate) fn in_context(&self, err: Error) -> Error {
+        match self.last_request {
+            Some((method, request_id))
+                if self.error_context && !matches!(err, Error::InContext { .. }) =>
+            {
+                Error::InContext {
+                    endpoint: method.to_string(),
+                    request_id,
+                    source: Box::new(err),
+                }
             }
-            Some(Arguments::Form(value)) => {
-                let body = value.clone();
-                body.into()
+            _ => err,
+        }
+    }
+
+    async fn send_cancellable(
+        &mut self,
+        input: &ApiRequest,
+        body: &Bytes,
+    ) -> Result<Response, Error> {
+        let cancel_token = self.cancel_token.clone();
+        let deadline = self.request_deadline();
+        let request = async {
+            match deadline {
+                Some(deadline) => timeout_at(deadline.into(), self.send_logged_in(input, body))
+                    .await
+                    .map_err(|_| Error::Timeout)?,
+                None => self.send_logged_in(input, body).await,
             }
-            None => Bytes::new(),
         };
-        let response = self.get_response(&input.method.to_string(), &body).await?;
+        let Some(cancel_token) = cancel_token else {
+            return request.await;
+        };
+        // Bound to a local so the temporaries of select are dropped before cancel_token
+        let result = match select(pin!(request), pin!(cancel_token.cancelled())).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(Error::Cancelled),
+        };
+        result
+    }
+
+    async fn send_logged_in(
+        &mut self,
+        input: &ApiRequest,
+        body: &Bytes,
+    ) -> Result<Response, Error> {
+        let credentials = {
+            let session = self.session();
+            let logged_out = session.cookie.is_empty() && input.method != Method::Login;
+            session.credentials.clone().filter(|_| logged_out)
+        };
+        if let Some(credentials) = credentials {
+            let login = login_request(&credentials.username, &credentials.password);
+            let response = self.send(&login, &login.body()).await?;
+            login_status(response.status_code().as_u16())?;
+        }
+        self.send(input, body).await
+    }
+
+    /// Send input with its body, built once by the caller
+    async fn send(&mut self, input: &ApiRequest, body: &Bytes) -> Result<Response, Error> {
+        self.throttle.wait(&input.method).await;
+        let response = self
+            .get_response_with_retry(&input.method, &input.content_type(), body)
+            .await;
+        self.cache.invalidate(input.method);
+        let response = response?;
         if input.method == Method::Login && response.status_code() == StatusCode::from(200) {
             let set_cookie = response
                 .headers
                 .get("set-cookie")
                 .ok_or(Error::NoSetCookie)?;
             let cookie = set_cookie.split(';').next().ok_or(Error::NoSID)?;
-            self.cookie = cookie.to_string();
+            self.session().cookie = cookie.to_string();
         }
         Ok(response)
     }
@@ -0,0 +1,114 @@
+// Reseed
+//
+// Add a torrent over data that is already on disk, e.g. for cross-seeding, and start it only
+// if the data is complete.
+
+use std::time::Duration;
+
+use crate::{meta::TorrentMeta, torrents::AddTorrent, Client, Error};
+
+/// Polls without seeing the torrent checking before assuming the check already finished
+const CHECK_START_POLLS: u32 = 3;
+
+/// Settings of Client::reseed
+#[derive(Clone, Debug)]
+pub struct ReseedConfig {
+    /// Add the torrent with skip_checking, the recheck verifies the data anyway
+    /// (default: false)
+    pub skip_checking: bool,
+    /// Category of the added torrent (default: none)
+    pub category: Option<String>,
+    /// Tags of the added torrent, split by ',' (default: none)
+    pub tags: Option<String>,
+    /// Interval between polls while the torrent is checked (default: 1s)
+    pub poll_interval: Duration,
+    /// Maximum wait for the check to finish (default: 1h)
+    pub max_wait: Duration,
+}
+
+impl Default for ReseedConfig {
+    fn default() -> Self {
+        Self {
+            skip_checking: false,
+            category: None,
+            tags: None,
+            poll_interval: Duration::from_secs(1),
+            max_wait: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Result of Client::reseed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReseedOutcome {
+    /// All the data was found, the torrent was resumed
+    FullMatch { hash: String },
+    /// Part of the data was found, the torrent stays paused
+    PartialMatch { hash: String, progress: f64 },
+    /// None of the data was found, the torrent stays paused
+    NoMatch { hash: String },
+}
+
+impl ReseedOutcome {
+    /// Hash of the added torrent
+    pub fn hash(&self) -> &str {
+        match self {
+            ReseedOutcome::FullMatch { hash }
+            | ReseedOutcome::PartialMatch { hash, .. }
+            | ReseedOutcome::NoMatch { hash } => hash,
+        }
+    }
+}
+
+impl Client {
+    /// Add the torrent of meta paused (stopped since Web API v2.11) with existing_data_path as
+    /// save path, recheck it and resume it only if all of its data was found
+    ///
+    /// The torrent is kept in qBittorrent whatever the outcome, remove it with delete_torrent
+    /// when it isn't wanted. Returns Error::WaitTimeout if the check doesn't finish within
+    /// config.max_wait.
+    ///
+    pub async fn reseed(
+        &mut self,
+        meta: TorrentMeta,
+        existing_data_path: &str,
+        config: ReseedConfig,
+    ) -> Result<ReseedOutcome, Error> {
+        let values = AddTorrent {
+            savepath: Some(existing_data_path.to_string()),
+            skip_checking: config.skip_checking.then(|| "true".to_string()),
+            category: config.category,
+            tags: config.tags,
+            ..AddTorrent::from_meta(&meta)
+        };
+        let (added, stopped) = self.add_stopped(values).await?;
+        let hash = added.hash;
+        self.recheck_torrent(vec![&hash]).await?;
+
+        let mut polls = 0;
+        let mut seen_checking = false;
+        let torrent = self
+            .wait_for_state(
+                &hash,
+                |torrent| {
+                    polls += 1;
+                    let checking = torrent.state().is_checking();
+                    seen_checking |= checking;
+                    !checking && (seen_checking || polls >= CHECK_START_POLLS)
+                },
+                config.poll_interval,
+                config.max_wait,
+            )
+            .await?;
+
+        if torrent.progress >= 1.0 {
+            self.start_added(&hash, stopped).await?;
+            Ok(ReseedOutcome::FullMatch { hash })
+        } else if torrent.progress > 0.0 {
+            let progress = torrent.progress;
+            Ok(ReseedOutcome::PartialMatch { hash, progress })
+        } else {
+            Ok(ReseedOutcome::NoMatch { hash })
+        }
+    }
+}
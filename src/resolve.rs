@@ -0,0 +1,111 @@
+// Resolve
+//
+// Address selection of the WebUI host: static addresses for host names, like curl --resolve,
+// and IPv4 or IPv6 only resolution. Requests are sent to the address with the host of the URL
+// in the Host header, so over http only: the transport would check the certificate of an https
+// server against the address instead of the host name.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use tokio::net::lookup_host;
+use url::{Host, Url};
+
+use crate::Error;
+
+/// How long a resolved address is reused before the host is looked up again
+const LOOKUP_TTL: Duration = Duration::from_secs(60);
+
+/// IP version of the addresses the client connects to, set with ClientBuilder::ip_version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpVersion {
+    /// Any address, as resolved by the transport
+    #[default]
+    Any,
+    /// IPv4 addresses only
+    V4,
+    /// IPv6 addresses only
+    V6,
+}
+
+impl IpVersion {
+    fn accepts(self, addr: &IpAddr) -> bool {
+        match self {
+            IpVersion::Any => true,
+            IpVersion::V4 => addr.is_ipv4(),
+            IpVersion::V6 => addr.is_ipv6(),
+        }
+    }
+}
+
+/// Address selection of a client
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Resolver {
+    pub(crate) ip_version: IpVersion,
+    /// Static addresses by lowercase host name
+    pub(crate) overrides: HashMap<String, IpAddr>,
+    /// Looked up addresses by host name with the time of the lookup, shared by clones
+    lookups: Arc<Mutex<HashMap<String, (IpAddr, Instant)>>>,
+}
+
+impl Resolver {
+    /// Whether requests to url are sent to another address than the one of its host
+    pub(crate) fn redirects(&self, url: &Url) -> bool {
+        match url.host() {
+            Some(Host::Domain(domain)) => {
+                self.ip_version != IpVersion::Any
+                    || self.overrides.contains_key(&domain.to_lowercase())
+            }
+            _ => false,
+        }
+    }
+
+    /// URL connecting to the selected address of the host of url, with the Host header for it,
+    /// or None if url is used as is
+    pub(crate) async fn resolve(&self, url: &Url) -> Result<Option<(Url, String)>, Error> {
+        let Some(Host::Domain(domain)) = url.host() else {
+            return Ok(None);
+        };
+        let addr = match self.overrides.get(&domain.to_lowercase()) {
+            Some(addr) => *addr,
+            None if self.ip_version == IpVersion::Any => return Ok(None),
+            None => self.lookup(domain, url).await?,
+        };
+        let host = match url.port() {
+            Some(port) => format!("{domain}:{port}"),
+            None => domain.to_string(),
+        };
+        let mut connect_url = url.clone();
+        connect_url
+            .set_ip_host(addr)
+            .map_err(|_| Error::NoAddress(domain.to_string()))?;
+        Ok(Some((connect_url, host)))
+    }
+
+    /// Address of the selected IP version for domain, looked up at most once per LOOKUP_TTL
+    async fn lookup(&self, domain: &str, url: &Url) -> Result<IpAddr, Error> {
+        let cached = self
+            .lookups
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(domain)
+            .filter(|(_, at)| at.elapsed() < LOOKUP_TTL)
+            .map(|(addr, _)| *addr);
+        if let Some(addr) = cached {
+            return Ok(addr);
+        }
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addr = lookup_host((domain, port))
+            .await?
+            .map(|addr| addr.ip())
+            .find(|addr| self.ip_version.accepts(addr))
+            .ok_or_else(|| Error::NoAddress(domain.to_string()))?;
+        self.lookups
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(domain.to_string(), (addr, Instant::now()));
+        Ok(addr)
+    }
+}
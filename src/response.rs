@@ -1,12 +1,17 @@
+use std::marker::PhantomData;
+
 use bytes::Bytes;
 use netc::Response;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use url::Url;
 
-use crate::{error::Error, Client};
+use crate::{error::Error, middleware::OutgoingRequest, request::Method, Client};
+
+/// Maximum length of the body kept in Error::Decode
+const BODY_SNIPPET_LEN: usize = 512;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ApiResponse {
     pub result: String,
     pub arguments: Option<Value>,
@@ -41,28 +46,244 @@ pub struct ApiResponse {
 // }
 
 impl Client {
-    pub(crate) async fn get_response(&self, method: &str, body: &Bytes) -> Result<Response, Error> {
+    /// Send one HTTP request to the WebUI
+    ///
+    /// netc clients are built per request with their URL and body, so every request opens a
+    /// new connection, only the session cookie is shared between requests and clones.
+    pub(crate) async fn get_response(
+        &self,
+        method: &str,
+        content_type: &str,
+        body: &Bytes,
+    ) -> Result<Response, Error> {
+        let cookie = self.session().cookie.clone();
         let cb = netc::Client::builder();
         let options = Url::options();
         let base_url = options.base_url(Some(&self.url));
-        let url = base_url.parse(method)?;
-        let mut client = cb
+        let mut request = OutgoingRequest {
+            path: method.to_string(),
+            url: base_url.parse(method)?,
+            headers: Vec::new(),
+            content_type: content_type.to_string(),
+            body: body.clone(),
+        };
+        self.middlewares.on_request(&mut request)?;
+        let (url, resolved_host) = match self.resolver.resolve(&request.url).await? {
+            Some((url, host)) => (url, Some(host)),
+            None => (request.url.clone(), None),
+        };
+        let headers = &self.proxy_headers;
+        let origin = headers
+            .origin
+            .clone()
+            .unwrap_or_else(|| self.url.origin().ascii_serialization());
+        let cb = cb
             .post(&url)
             .header("Cache-Control", "no-cache")
-            .header("Pragma", "no-cache")
-            .header("Cookie", &self.cookie)
-            .content_type("application/x-www-form-urlencoded; charset=utf-8")
-            .origin(&self.url.origin().ascii_serialization())
-            .body(body.clone())
-            .build()
-            .await?;
-        Ok(client.send().await?)
+            .header("Pragma", "no-cache");
+        #[cfg(feature = "compression")]
+        let cb = cb.header("Accept-Encoding", "gzip, deflate");
+        let cb = match &headers.referer {
+            Some(referer) => cb.header("Referer", referer),
+            None => cb,
+        };
+        let cb = match headers.host.as_ref().or(resolved_host.as_ref()) {
+            Some(host) => cb.header("Host", host),
+            None => cb,
+        };
+        let cb = cb
+            .header("Cookie", &cookie)
+            .content_type(&request.content_type)
+            .origin(&origin);
+        let cb = request
+            .headers
+            .iter()
+            .fold(cb, |cb, (name, value)| cb.header(name, value));
+        let mut client = cb.body(request.body.clone()).build().await?;
+        let response = client.send().await?;
+        self.middlewares.on_response(&request, &response);
+        Ok(response)
+    }
+
+    pub(crate) fn check_default_status<T>(
+        &self,
+        response: &Response,
+        value: T,
+    ) -> Result<T, Error> {
+        match response.status_code().as_u16() {
+            200 => Ok(value),
+            _ => Err(self.status_error(response)),
+        }
+    }
+
+    /// Error of a response with an unexpected status code: Error::Api with the diagnostic of a
+    /// JSON error body, e.g. `{"error": {"code": 3, "message": "..."}}`, otherwise
+    /// Error::WrongStatusCode, in the context of the request (see Client::in_context)
+    pub(crate) fn status_error(&self, response: &Response) -> Error {
+        let status = response.status_code().as_u16();
+        let err = response_body(response)
+            .ok()
+            .and_then(|body| api_error(status, &body))
+            .unwrap_or(Error::WrongStatusCode);
+        self.in_context(err)
+    }
+}
+
+/// Error::Api of a JSON error body, the code defaults to the status code
+fn api_error(status: u16, body: &[u8]) -> Option<Error> {
+    let value: Value = serde_json::from_slice(body).ok()?;
+    let error = value
+        .get("error")
+        .filter(|error| error.is_object())
+        .unwrap_or(&value);
+    let message = error
+        .get("message")
+        .or_else(|| value.get("error"))
+        .and_then(Value::as_str)?;
+    let code = match error.get("code") {
+        Some(Value::Number(code)) => code.as_i64(),
+        Some(Value::String(code)) => code.parse().ok(),
+        _ => None,
+    };
+    Some(Error::Api {
+        code: code.unwrap_or(i64::from(status)),
+        message: message.to_string(),
+    })
+}
+
+/// Deserialize the JSON body of a response, keeping the method and the start of the body on error
+pub(crate) fn decode<T: DeserializeOwned>(method: Method, response: &Response) -> Result<T, Error> {
+    decode_body(method, &response_body(response)?)
+}
+
+/// Deserialize a JSON body, see decode
+pub(crate) fn decode_body<T: DeserializeOwned>(method: Method, body: &[u8]) -> Result<T, Error> {
+    serde_json::from_slice(body).map_err(|source| Error::Decode {
+        method: method.to_string(),
+        source,
+        body_snippet: body_snippet(body),
+    })
+}
+
+/// Body of a response, decompressed if it was sent gzip or deflate encoded
+///
+/// Only a compressed body is copied, into its decompressed form.
+pub(crate) fn response_body(response: &Response) -> Result<Bytes, Error> {
+    let body = response.body();
+    #[cfg(feature = "compression")]
+    {
+        use std::io::Read;
+
+        let encoding = response
+            .headers
+            .get("content-encoding")
+            .map(|encoding| encoding.to_ascii_lowercase())
+            .unwrap_or_default();
+        let mut decompressed = Vec::new();
+        // Bodies already decompressed by the transport don't start with the gzip magic bytes
+        if encoding.contains("gzip") && body.as_ref().starts_with(&[0x1f, 0x8b]) {
+            flate2::read::GzDecoder::new(body.as_ref()).read_to_end(&mut decompressed)?;
+            return Ok(decompressed.into());
+        }
+        if encoding.contains("deflate") {
+            let mut decoder = flate2::read::ZlibDecoder::new(body.as_ref());
+            if decoder.read_to_end(&mut decompressed).is_ok() {
+                return Ok(decompressed.into());
+            }
+        }
+    }
+    // Bytes clones share the buffer of the response instead of copying it
+    Ok(Bytes::clone(&body))
+}
+
+/// Elements of a JSON array body, deserialized one at a time
+///
+/// Only the current element is deserialized, the raw body is held in full. Iteration stops
+/// after the first error.
+pub(crate) struct JsonArray<T> {
+    method: Method,
+    body: Bytes,
+    pos: usize,
+    done: bool,
+    element: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonArray<T> {
+    pub(crate) fn new(method: Method, response: &Response) -> Result<Self, Error> {
+        let mut array = Self {
+            method,
+            body: response_body(response)?,
+            pos: 0,
+            done: false,
+            element: PhantomData,
+        };
+        if !array.consume(b'[') {
+            return Err(array.error(serde::de::Error::custom("expected a JSON array")));
+        }
+        array.done = array.consume(b']');
+        Ok(array)
+    }
+
+    /// Skip whitespace, then the byte if it is next
+    fn consume(&mut self, byte: u8) -> bool {
+        while self
+            .body
+            .get(self.pos)
+            .is_some_and(|next| next.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+        let found = self.body.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn error(&mut self, source: serde_json::Error) -> Error {
+        self.done = true;
+        Error::Decode {
+            method: self.method.to_string(),
+            source,
+            body_snippet: body_snippet(&self.body[self.pos..]),
+        }
+    }
+
+    fn next_element(&mut self) -> Result<T, Error> {
+        let body = self.body.clone();
+        let mut elements = serde_json::Deserializer::from_slice(&body[self.pos..]).into_iter::<T>();
+        let element = match elements.next() {
+            Some(Ok(element)) => element,
+            Some(Err(source)) => return Err(self.error(source)),
+            None => {
+                return Err(self.error(serde::de::Error::custom("unexpected end of array")));
+            }
+        };
+        self.pos += elements.byte_offset();
+        if self.consume(b']') {
+            self.done = true;
+        } else if !self.consume(b',') {
+            return Err(self.error(serde::de::Error::custom("expected , or ]")));
+        }
+        Ok(element)
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonArray<T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        Some(self.next_element())
     }
 }
 
-pub(crate) fn check_default_status<T>(response: &Response, value: T) -> Result<T, Error> {
-    match response.status_code().as_u16() {
-        200 => Ok(value),
-        _ => Err(Error::WrongStatusCode),
+fn body_snippet(body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+    match body.char_indices().nth(BODY_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.into_owned(),
     }
 }
@@ -0,0 +1,128 @@
+// Retry
+//
+// Retry of transient request failures with exponential backoff.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use netc::Response;
+use rand::Rng;
+use tokio::time::{sleep, timeout};
+
+use crate::{request::Method, Client, Error};
+
+/// Retry policy for transient failures, set with ClientBuilder::retry_policy
+///
+/// Connection errors, timeouts and 5xx responses are retried up to max_retries times, waiting
+/// initial_backoff * 2^attempt (capped by max_backoff) between attempts. Non-idempotent methods
+/// (add, delete, shutdown, toggles, login) are only retried if retry_non_idempotent is set.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt (default: 3)
+    pub max_retries: u32,
+    /// Wait before the first retry (default: 200ms)
+    pub initial_backoff: Duration,
+    /// Maximum wait between retries (default: 10s)
+    pub max_backoff: Duration,
+    /// Randomize each wait between 50% and 100% of its value (default: true)
+    pub jitter: bool,
+    /// Timeout of a single attempt (default: none)
+    pub attempt_timeout: Option<Duration>,
+    /// Also retry non-idempotent methods (default: false)
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            attempt_timeout: None,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Wait before the retry following the given attempt (0 based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            backoff
+        }
+    }
+
+    fn applies_to(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || method.is_idempotent()
+    }
+}
+
+/// Whether the error is a connection failure or a timeout
+pub(crate) fn is_transient(err: &Error) -> bool {
+    matches!(err.root(), Error::Nc(_) | Error::Io(_) | Error::Timeout)
+}
+
+impl Client {
+    async fn attempt(
+        &self,
+        method: &str,
+        content_type: &str,
+        body: &Bytes,
+        attempt_timeout: Option<Duration>,
+    ) -> Result<Response, Error> {
+        match attempt_timeout {
+            Some(duration) => timeout(duration, self.get_response(method, content_type, body))
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => self.get_response(method, content_type, body).await,
+        }
+    }
+
+    /// Send a request, retrying transient failures according to the retry policy
+    pub(crate) async fn get_response_with_retry(
+        &self,
+        method: &Method,
+        content_type: &str,
+        body: &Bytes,
+    ) -> Result<Response, Error> {
+        let path = method.to_string();
+        let Some(policy) = &self.retry_policy else {
+            return self.get_response(&path, content_type, body).await;
+        };
+        let max_retries = if policy.applies_to(method) {
+            policy.max_retries
+        } else {
+            0
+        };
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .attempt(&path, content_type, body, policy.attempt_timeout)
+                .await;
+            let transient = match &result {
+                Ok(response) => response.status_code().as_u16() >= 500,
+                Err(err) => is_transient(err),
+            };
+            if !transient || attempt >= max_retries {
+                return result;
+            }
+            ::log::debug!("retry {method} after attempt {attempt}");
+            sleep(policy.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,390 @@
+// Schedule
+//
+// Client side speed limit schedule, switching the alternative speed limits or the global
+// limits at given times of the week, and the window of the qBittorrent scheduler.
+
+use std::fmt;
+#[cfg(feature = "chrono")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use bitflags::bitflags;
+use tokio::time::sleep;
+
+use crate::{
+    app::{Preferences, SchedulerDays},
+    maintenance::now,
+    prefs::SpeedPrefs,
+    retry::is_transient,
+    Client, Error,
+};
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const MINUTES_PER_WEEK: i64 = 7 * MINUTES_PER_DAY;
+
+bitflags! {
+    /// Days of the week of a schedule entry
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Weekdays: u8 {
+        const MONDAY = 1;
+        const TUESDAY = 2;
+        const WEDNESDAY = 4;
+        const THURSDAY = 8;
+        const FRIDAY = 16;
+        const SATURDAY = 32;
+        const SUNDAY = 64;
+        const WORKDAYS = 31;
+        const WEEKEND = 96;
+    }
+}
+
+/// Speed settings switched to by a schedule entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedMode {
+    /// Alternative speed limits enabled
+    AltSpeed,
+    /// Alternative speed limits disabled
+    Normal,
+    /// Alternative speed limits disabled and these global limits (bytes/s, 0 for no limit)
+    Limits { download: i64, upload: i64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScheduleEntry {
+    days: Weekdays,
+    /// Minutes since midnight
+    minute: i64,
+    mode: SpeedMode,
+}
+
+/// Offset of the local time of the schedule
+#[derive(Clone)]
+enum Timezone {
+    /// Fixed offset to UTC (seconds)
+    Fixed(i64),
+    /// Offset to UTC (seconds) at a Unix timestamp
+    #[cfg(feature = "chrono")]
+    Zone(Arc<dyn Fn(i64) -> i64 + Send + Sync>),
+}
+
+impl Timezone {
+    #[cfg_attr(not(feature = "chrono"), allow(unused_variables))]
+    fn offset(&self, timestamp: i64) -> i64 {
+        match self {
+            Timezone::Fixed(offset) => *offset,
+            #[cfg(feature = "chrono")]
+            Timezone::Zone(offset) => offset(timestamp),
+        }
+    }
+}
+
+impl fmt::Debug for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Fixed(offset) => f.debug_tuple("Fixed").field(offset).finish(),
+            #[cfg(feature = "chrono")]
+            Timezone::Zone(_) => f.write_str("Zone"),
+        }
+    }
+}
+
+/// Switches the speed limits at given times of the week, e.g. when the single window of the
+/// qBittorrent scheduler isn't enough
+///
+/// Each entry switches to its mode at a time on some days, until the next entry. The schedule
+/// repeats every week, so the last entry of the week is in effect before the first one. Times
+/// are in UTC unless utc_offset or timezone is set.
+///
+/// Modes are only applied when the schedule switches, so limits changed by hand in between are
+/// kept until the next entry.
+#[derive(Debug)]
+pub struct Scheduler {
+    client: Client,
+    entries: Vec<ScheduleEntry>,
+    timezone: Timezone,
+    check_interval: Duration,
+}
+
+impl Scheduler {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            entries: Vec::new(),
+            timezone: Timezone::Fixed(0),
+            check_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Switch to mode at hour:minute on days. Of entries at the same time, the last one added
+    /// is used.
+    pub fn at(mut self, days: Weekdays, hour: u32, minute: u32, mode: SpeedMode) -> Self {
+        self.entries.push(ScheduleEntry {
+            days,
+            minute: i64::from(hour.min(23)) * 60 + i64::from(minute.min(59)),
+            mode,
+        });
+        self
+    }
+
+    /// Times of the entries are at a fixed offset to UTC (seconds east of UTC)
+    pub fn utc_offset(mut self, seconds: i64) -> Self {
+        self.timezone = Timezone::Fixed(seconds);
+        self
+    }
+
+    /// Times of the entries are in a chrono time zone, e.g. a chrono-tz zone following
+    /// daylight saving time
+    #[cfg(feature = "chrono")]
+    pub fn timezone<Tz>(mut self, timezone: Tz) -> Self
+    where
+        Tz: chrono::TimeZone + Send + Sync + 'static,
+    {
+        use chrono::Offset;
+
+        self.timezone = Timezone::Zone(Arc::new(move |timestamp| {
+            chrono::DateTime::from_timestamp(timestamp, 0)
+                .map(|time| {
+                    let offset = timezone.offset_from_utc_datetime(&time.naive_utc());
+                    i64::from(offset.fix().local_minus_utc())
+                })
+                .unwrap_or_default()
+        }));
+        self
+    }
+
+    /// Interval between checks of the schedule (default: 1min)
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// Mode of the schedule at a Unix timestamp, None without entries
+    pub fn mode_at(&self, timestamp: i64) -> Option<SpeedMode> {
+        let local = timestamp + self.timezone.offset(timestamp);
+        // 1970-01-01 was a Thursday
+        let weekday = (local.div_euclid(86400) + 3).rem_euclid(7);
+        let now = weekday * MINUTES_PER_DAY + local.rem_euclid(86400) / 60;
+        let mut latest: Option<(i64, SpeedMode)> = None;
+        for entry in &self.entries {
+            for day in 0..7u8 {
+                if !entry.days.contains(Weekdays::from_bits_truncate(1 << day)) {
+                    continue;
+                }
+                // Minutes since the trigger, triggers later in the week were last week
+                let since = (now - (i64::from(day) * MINUTES_PER_DAY + entry.minute))
+                    .rem_euclid(MINUTES_PER_WEEK);
+                if latest.is_none_or(|(latest, _)| since <= latest) {
+                    latest = Some((since, entry.mode));
+                }
+            }
+        }
+        latest.map(|(_, mode)| mode)
+    }
+
+    /// Mode of the schedule now
+    pub fn current_mode(&self) -> Option<SpeedMode> {
+        self.mode_at(now())
+    }
+
+    /// Apply mode to the server
+    pub async fn apply(&mut self, mode: SpeedMode) -> Result<(), Error> {
+        self.client
+            .set_alt_speed_enabled(mode == SpeedMode::AltSpeed)
+            .await?;
+        if let SpeedMode::Limits { download, upload } = mode {
+            self.client.set_download_limit(download).await?;
+            self.client.set_upload_limit(upload).await?;
+        }
+        Ok(())
+    }
+
+    /// Apply the current mode, then apply each switch of the schedule
+    ///
+    /// Runs until a request fails with an error other than a connection failure or a
+    /// timeout. After such transient errors, the mode is applied again at the next check.
+    ///
+    pub async fn run(mut self) -> Result<(), Error> {
+        let mut applied = None;
+        loop {
+            let mode = self.current_mode();
+            if let Some(mode) = mode.filter(|mode| applied != Some(*mode)) {
+                match self.apply(mode).await {
+                    Ok(()) => {
+                        ::log::debug!("scheduler switched to {mode:?}");
+                        applied = Some(mode);
+                    }
+                    Err(err) if is_transient(&err) => {
+                        ::log::debug!("scheduler failed to switch to {mode:?}: {err}");
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            sleep(self.check_interval).await;
+        }
+    }
+}
+
+impl SchedulerDays {
+    /// Days of the week of the qBittorrent scheduler value, None for unknown values
+    pub fn weekdays(self) -> Option<Weekdays> {
+        Some(match self {
+            SchedulerDays::EveryDay => Weekdays::all(),
+            SchedulerDays::EveryWeekday => Weekdays::WORKDAYS,
+            SchedulerDays::EveryWeekend => Weekdays::WEEKEND,
+            SchedulerDays::EveryMonday => Weekdays::MONDAY,
+            SchedulerDays::EveryTuesday => Weekdays::TUESDAY,
+            SchedulerDays::EveryWednesday => Weekdays::WEDNESDAY,
+            SchedulerDays::EveryThursday => Weekdays::THURSDAY,
+            SchedulerDays::EveryFriday => Weekdays::FRIDAY,
+            SchedulerDays::EverySaturday => Weekdays::SATURDAY,
+            SchedulerDays::EverySunday => Weekdays::SUNDAY,
+            SchedulerDays::Unknown(_) => return None,
+        })
+    }
+}
+
+impl TryFrom<Weekdays> for SchedulerDays {
+    type Error = Error;
+
+    /// qBittorrent scheduler value of days, which must be every day, the workdays, the weekend
+    /// or a single day
+    fn try_from(days: Weekdays) -> Result<Self, Error> {
+        (0..10)
+            .map(SchedulerDays::from)
+            .find(|value| value.weekdays() == Some(days))
+            .ok_or_else(|| Error::InvalidSchedule(format!("days {days:?}")))
+    }
+}
+
+/// Window of the qBittorrent alternative speed limits scheduler, see
+/// Client::set_alt_speed_schedule
+///
+/// The window spans midnight when it ends before it starts, e.g. 22:00 to 06:00. As in
+/// qBittorrent, the days apply to the current day, so the part after midnight is only active
+/// if the next day is selected too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    from: (u32, u32),
+    to: (u32, u32),
+    days: SchedulerDays,
+}
+
+impl Schedule {
+    /// Window from from_hour:from_minute to to_hour:to_minute on days
+    ///
+    /// Fails with Error::InvalidSchedule if a time is out of range, if the window is empty or
+    /// if days is an unknown value.
+    pub fn new(
+        (from_hour, from_minute): (u32, u32),
+        (to_hour, to_minute): (u32, u32),
+        days: SchedulerDays,
+    ) -> Result<Self, Error> {
+        for (hour, minute) in [(from_hour, from_minute), (to_hour, to_minute)] {
+            if hour > 23 || minute > 59 {
+                return Err(Error::InvalidSchedule(format!("time {hour}:{minute:02}")));
+            }
+        }
+        if (from_hour, from_minute) == (to_hour, to_minute) {
+            return Err(Error::InvalidSchedule("empty window".to_string()));
+        }
+        if days.weekdays().is_none() {
+            return Err(Error::InvalidSchedule(format!("days {days:?}")));
+        }
+        Ok(Self {
+            from: (from_hour, from_minute),
+            to: (to_hour, to_minute),
+            days,
+        })
+    }
+
+    /// Start of the window (hour, minute)
+    pub fn start(&self) -> (u32, u32) {
+        self.from
+    }
+
+    /// End of the window (hour, minute)
+    pub fn end(&self) -> (u32, u32) {
+        self.to
+    }
+
+    /// Days of the window
+    pub fn days(&self) -> SchedulerDays {
+        self.days
+    }
+
+    /// True if the window ends on the next day
+    pub fn wraps_midnight(&self) -> bool {
+        self.to < self.from
+    }
+
+    /// True if the alternative speed limits are enabled by the window on day at hour:minute
+    pub fn is_active(&self, day: Weekdays, hour: u32, minute: u32) -> bool {
+        let time = (hour, minute);
+        let in_window = if self.wraps_midnight() {
+            time >= self.from || time < self.to
+        } else {
+            time >= self.from && time < self.to
+        };
+        in_window && self.days.weekdays().is_some_and(|days| days.contains(day))
+    }
+
+    /// Schedule of the preferences, None if the scheduler is disabled or its fields are
+    /// missing or invalid
+    pub fn from_preferences(preferences: &Preferences) -> Option<Self> {
+        if preferences.scheduler_enabled != Some(true) {
+            return None;
+        }
+        let time = |hour: Option<i64>, minute: Option<i64>| -> Option<(u32, u32)> {
+            Some((hour?.try_into().ok()?, minute?.try_into().ok()?))
+        };
+        Schedule::new(
+            time(
+                preferences.schedule_from_hour,
+                preferences.schedule_from_min,
+            )?,
+            time(preferences.schedule_to_hour, preferences.schedule_to_min)?,
+            preferences.scheduler_days?,
+        )
+        .ok()
+    }
+}
+
+impl From<Schedule> for SpeedPrefs {
+    fn from(schedule: Schedule) -> Self {
+        SpeedPrefs {
+            scheduler_enabled: Some(true),
+            schedule_from_hour: Some(i64::from(schedule.from.0)),
+            schedule_from_min: Some(i64::from(schedule.from.1)),
+            schedule_to_hour: Some(i64::from(schedule.to.0)),
+            schedule_to_min: Some(i64::from(schedule.to.1)),
+            scheduler_days: Some(schedule.days),
+            ..Default::default()
+        }
+    }
+}
+
+impl Client {
+    /// Get the alternative speed limits schedule of qBittorrent, None if it is disabled
+    pub async fn get_alt_speed_schedule(&mut self) -> Result<Option<Schedule>, Error> {
+        let preferences = self.get_preferences().await?;
+        Ok(Schedule::from_preferences(&preferences))
+    }
+
+    /// Enable the alternative speed limits schedule of qBittorrent with the window of schedule
+    ///
+    /// The window and the days are set with a single request, so the server never runs a
+    /// partially updated schedule.
+    ///
+    pub async fn set_alt_speed_schedule(&mut self, schedule: Schedule) -> Result<(), Error> {
+        self.set_speed_prefs(schedule.into()).await
+    }
+
+    /// Disable the alternative speed limits schedule of qBittorrent
+    pub async fn disable_alt_speed_schedule(&mut self) -> Result<(), Error> {
+        self.set_speed_prefs(SpeedPrefs {
+            scheduler_enabled: Some(false),
+            ..Default::default()
+        })
+        .await
+    }
+}
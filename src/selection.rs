@@ -0,0 +1,282 @@
+// Selection
+//
+// Select the files of a torrent to download by rules. New torrents are added paused, the
+// unselected files are skipped once the metadata is received, then they are resumed.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use tokio::time::sleep;
+
+use crate::{
+    torrents::{AddTorrent, File, GetTorrentList, Priority},
+    Client, Error,
+};
+
+/// Files selected by Client::add_with_selection and Client::apply_selection
+///
+/// A file is selected if it matches any of the globs, regular expressions or extensions (every
+/// file if there are none) and is at least min_size bytes. Patterns are matched against the
+/// path of the file in the torrent, e.g. `Show/Season 1/E01.mkv`.
+#[derive(Debug, Clone)]
+pub struct FileSelector {
+    patterns: Vec<Regex>,
+    extensions: Vec<String>,
+    min_size: i64,
+    poll_interval: Duration,
+    max_wait: Duration,
+}
+
+impl Default for FileSelector {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            extensions: Vec::new(),
+            min_size: 0,
+            poll_interval: Duration::from_secs(1),
+            max_wait: Duration::from_secs(600),
+        }
+    }
+}
+
+impl FileSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select files matching the glob, e.g. `*.mkv` or `Season 1/**`
+    ///
+    /// `*` and `?` don't match `/`, `**` does. The glob matches the end of the path from a
+    /// directory boundary, start it with `/` to match from the root of the torrent.
+    pub fn glob(mut self, glob: &str) -> Self {
+        self.patterns.push(glob_regex(glob));
+        self
+    }
+
+    /// Select files whose path matches the regular expression, e.g. `(?i)e0[1-3]\.mkv$`
+    pub fn regex(mut self, pattern: &str) -> Result<Self, Error> {
+        self.patterns.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Select files with the extension (case insensitive), e.g. "mkv"
+    pub fn extension(mut self, extension: &str) -> Self {
+        self.extensions
+            .push(extension.trim_start_matches('.').to_lowercase());
+        self
+    }
+
+    /// Skip files smaller than size bytes, e.g. samples
+    pub fn min_size(mut self, size: i64) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Interval between polls while waiting for the metadata (default: 1s)
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Maximum wait for the metadata and the file list (default: 10min)
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Whether the file is selected
+    pub fn matches(&self, file: &File) -> bool {
+        if file.size < self.min_size {
+            return false;
+        }
+        if self.patterns.is_empty() && self.extensions.is_empty() {
+            return true;
+        }
+        let extension = file
+            .name
+            .rsplit_once('.')
+            .filter(|(_, extension)| !extension.contains('/'))
+            .map(|(_, extension)| extension.to_lowercase());
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.is_match(&file.name))
+            || extension.is_some_and(|extension| self.extensions.contains(&extension))
+    }
+}
+
+/// Maximum number of file ids sent in a single filePrio request
+const FILE_PRIO_BATCH: usize = 500;
+
+/// Result of Client::add_with_selection and Client::apply_selection
+#[derive(Debug)]
+pub struct Selection {
+    /// ID of the torrent to pass to the other torrent methods
+    pub hash: String,
+    /// Torrent name
+    pub name: String,
+    /// Files left to download, with their index set
+    pub selected: Vec<File>,
+    /// Files set to not download, with their index set
+    pub skipped: Vec<File>,
+}
+
+impl Client {
+    /// Add a torrent and download only the files matched by selector
+    ///
+    /// The torrent is added paused (stopped since Web API v2.11) so no data is downloaded
+    /// before the selection. Once the metadata is received, the other files are set to not
+    /// download and the torrent is resumed, unless add was paused or stopped itself. Returns Error::WaitTimeout if the metadata or the
+    /// file list doesn't come within the max wait of selector, and Error::NoFileSelected if no
+    /// file matches. The torrent stays paused in qBittorrent on errors, remove it with
+    /// delete_torrent when it isn't wanted.
+    ///
+    pub async fn add_with_selection(
+        &mut self,
+        add: AddTorrent,
+        selector: FileSelector,
+    ) -> Result<Selection, Error> {
+        let (added, stopped) = self.add_stopped(add).await?;
+        let deadline = Instant::now().checked_add(selector.max_wait);
+        let timed_out = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        self.wait_for_metadata(&added.hash, selector.poll_interval, selector.max_wait)
+            .await?;
+
+        // The file list and filePrio can lag behind the metadata flag of the torrent list
+        let files = loop {
+            let files = self.get_torrent_contents(&added.hash, "").await?;
+            if !files.is_empty() {
+                break files;
+            }
+            if timed_out() {
+                return Err(Error::WaitTimeout);
+            }
+            sleep(selector.poll_interval).await;
+        };
+        let selection = select(added.hash, added.name, files, &selector)?;
+        loop {
+            match self.set_selection_priorities(&selection).await {
+                Err(Error::NoMetadata) if !timed_out() => sleep(selector.poll_interval).await,
+                Err(Error::NoMetadata) => return Err(Error::WaitTimeout),
+                result => break result?,
+            }
+        }
+        if !stopped.keep_stopped {
+            self.start_added(&selection.hash, stopped).await?;
+        }
+        Ok(selection)
+    }
+
+    /// Apply selector to a torrent already added, e.g. to skip the samples and extras of a
+    /// whole library
+    ///
+    /// Unselected files are set to not download, selected files that were not downloaded are
+    /// set to normal priority, other priorities are kept. Files are updated with one filePrio
+    /// request per priority, for at most 500 files each. Returns Error::NoMetadata if the
+    /// torrent has no metadata yet, and Error::NoFileSelected, without changing anything, if
+    /// no file matches. The torrent name is read from the torrent list.
+    ///
+    pub async fn apply_selection(
+        &mut self,
+        hash: &str,
+        selector: &FileSelector,
+    ) -> Result<Selection, Error> {
+        let files = self.get_torrent_contents(hash, "").await?;
+        if files.is_empty() {
+            return Err(Error::NoMetadata);
+        }
+        let selection = select(hash.to_string(), String::new(), files, selector)?;
+        let list = GetTorrentList::builder()
+            .hashes(vec![hash])
+            .limit(1)
+            .build();
+        let name = self
+            .get_torrent_list(list)
+            .await?
+            .into_iter()
+            .next()
+            .map(|torrent| torrent.name)
+            .ok_or(Error::NoTorrentHash)?;
+        self.set_selection_priorities(&selection).await?;
+        Ok(Selection { name, ..selection })
+    }
+
+    /// Skip the unselected files and download the selected files that were skipped
+    async fn set_selection_priorities(&mut self, selection: &Selection) -> Result<(), Error> {
+        let skip = selection
+            .skipped
+            .iter()
+            .filter(|file| Priority::from(file.priority) != Priority::Skip);
+        let download = selection
+            .selected
+            .iter()
+            .filter(|file| Priority::from(file.priority) == Priority::Skip);
+        for (files, priority) in [
+            (skip.collect::<Vec<_>>(), Priority::Skip),
+            (download.collect(), Priority::Normal),
+        ] {
+            let ids: Vec<i64> = files.iter().filter_map(|file| file.index).collect();
+            for batch in ids.chunks(FILE_PRIO_BATCH) {
+                self.set_file_priority(&selection.hash, batch, priority)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split files by selector, setting the index of files listed by servers before Web API 2.8.2
+/// to their position
+fn select(
+    hash: String,
+    name: String,
+    files: Vec<File>,
+    selector: &FileSelector,
+) -> Result<Selection, Error> {
+    let (selected, skipped): (Vec<File>, Vec<File>) = files
+        .into_iter()
+        .enumerate()
+        .map(|(position, mut file)| {
+            file.index.get_or_insert(position as i64);
+            file
+        })
+        .partition(|file| selector.matches(file));
+    if selected.is_empty() {
+        return Err(Error::NoFileSelected);
+    }
+    Ok(Selection {
+        hash,
+        name,
+        selected,
+        skipped,
+    })
+}
+
+/// Regular expression of a glob, anchored to the whole path
+fn glob_regex(glob: &str) -> Regex {
+    let mut pattern = String::from("^");
+    let glob = match glob.strip_prefix('/') {
+        Some(glob) => glob,
+        None => {
+            pattern.push_str("(?:.*/)?");
+            glob
+        }
+    };
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).expect("glob regex is escaped")
+}
@@ -0,0 +1,214 @@
+// Stats
+//
+// Snapshot of the transfer counters and torrent breakdowns of a session from a single
+// sync/maindata request, and the difference between two snapshots for periodic reports.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{maintenance::now, sync::SyncSession, Client, Error};
+
+/// Torrents of a state, category or tag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupStats {
+    /// Number of torrents
+    pub count: i64,
+    /// Total size (bytes) of the files selected for download
+    pub size: i64,
+    /// Amount of data downloaded (bytes)
+    pub downloaded: i64,
+    /// Amount of data uploaded (bytes)
+    pub uploaded: i64,
+}
+
+impl GroupStats {
+    fn difference(&self, earlier: &GroupStats) -> GroupStats {
+        GroupStats {
+            count: self.count - earlier.count,
+            size: self.size - earlier.size,
+            downloaded: self.downloaded - earlier.downloaded,
+            uploaded: self.uploaded - earlier.uploaded,
+        }
+    }
+}
+
+/// Session statistics returned by Client::stats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// Time of the snapshot (Unix Epoch)
+    pub time: i64,
+    /// Global download rate (bytes/s)
+    pub dl_speed: i64,
+    /// Global upload rate (bytes/s)
+    pub up_speed: i64,
+    /// Data downloaded this session (bytes)
+    pub session_downloaded: i64,
+    /// Data uploaded this session (bytes)
+    pub session_uploaded: i64,
+    /// Data downloaded over all sessions (bytes), 0 if not reported
+    pub alltime_downloaded: i64,
+    /// Data uploaded over all sessions (bytes), 0 if not reported
+    pub alltime_uploaded: i64,
+    /// Global share ratio, 0 if not reported
+    pub global_ratio: f64,
+    /// DHT nodes connected to
+    pub dht_nodes: i64,
+    /// Peer connections, 0 if not reported
+    pub peer_connections: i64,
+    /// All torrents
+    pub torrents: GroupStats,
+    /// Torrents by state, e.g. stalledUP
+    pub states: BTreeMap<String, GroupStats>,
+    /// Torrents by category, "" for torrents without category. Categories without torrents
+    /// are included
+    pub categories: BTreeMap<String, GroupStats>,
+    /// Torrents by tag, a torrent counts in each of its tags. Tags without torrents are
+    /// included
+    pub tags: BTreeMap<String, GroupStats>,
+}
+
+/// Change between two SessionStats, see SessionStats::delta
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsDelta {
+    /// Seconds between the snapshots
+    pub elapsed: i64,
+    /// Data downloaded between the snapshots (bytes), from the all-time counters if reported,
+    /// otherwise from the session counters
+    pub downloaded: i64,
+    /// Data uploaded between the snapshots (bytes), as downloaded
+    pub uploaded: i64,
+    /// Change of all torrents
+    pub torrents: GroupStats,
+    /// Change by state, for the states of either snapshot
+    pub states: BTreeMap<String, GroupStats>,
+    /// Change by category, for the categories of either snapshot
+    pub categories: BTreeMap<String, GroupStats>,
+    /// Change by tag, for the tags of either snapshot
+    pub tags: BTreeMap<String, GroupStats>,
+}
+
+impl SessionStats {
+    /// Statistics of the state of a sync session
+    pub fn from_session(session: &SyncSession) -> Result<Self, Error> {
+        let server_state = session.server_state()?;
+        let mut stats = SessionStats {
+            time: now(),
+            dl_speed: server_state.dl_info_speed,
+            up_speed: server_state.up_info_speed,
+            session_downloaded: server_state.dl_info_data,
+            session_uploaded: server_state.up_info_data,
+            alltime_downloaded: server_state.alltime_dl.unwrap_or_default(),
+            alltime_uploaded: server_state.alltime_ul.unwrap_or_default(),
+            global_ratio: server_state.alltime_ratio().unwrap_or_default(),
+            dht_nodes: server_state.dht_nodes,
+            peer_connections: server_state.total_peer_connections.unwrap_or_default(),
+            torrents: GroupStats::default(),
+            states: BTreeMap::new(),
+            categories: session
+                .categories()?
+                .into_keys()
+                .map(|name| (name, GroupStats::default()))
+                .collect(),
+            tags: session
+                .tags()
+                .iter()
+                .map(|tag| (tag.clone(), GroupStats::default()))
+                .collect(),
+        };
+        for torrent in session.torrents()?.values() {
+            let tags: Vec<&str> = torrent
+                .tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            for tag in &tags {
+                stats.tags.entry(tag.to_string()).or_default();
+            }
+            let mut groups = vec![
+                &mut stats.torrents,
+                stats.states.entry(torrent.state.clone()).or_default(),
+                stats
+                    .categories
+                    .entry(torrent.category.clone())
+                    .or_default(),
+            ];
+            for (tag, group) in stats.tags.iter_mut() {
+                if tags.contains(&tag.as_str()) {
+                    groups.push(group);
+                }
+            }
+            for group in groups {
+                group.count += 1;
+                group.size += torrent.size;
+                group.downloaded += torrent.downloaded;
+                group.uploaded += torrent.uploaded;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Change since earlier, e.g. the snapshot of the previous report
+    pub fn delta(&self, earlier: &SessionStats) -> StatsDelta {
+        let alltime = self.alltime_downloaded > 0 && earlier.alltime_downloaded > 0;
+        let (downloaded, uploaded) = if alltime {
+            (
+                self.alltime_downloaded - earlier.alltime_downloaded,
+                self.alltime_uploaded - earlier.alltime_uploaded,
+            )
+        } else {
+            (
+                self.session_downloaded - earlier.session_downloaded,
+                self.session_uploaded - earlier.session_uploaded,
+            )
+        };
+        StatsDelta {
+            elapsed: self.time - earlier.time,
+            downloaded,
+            uploaded,
+            torrents: self.torrents.difference(&earlier.torrents),
+            states: group_delta(&self.states, &earlier.states),
+            categories: group_delta(&self.categories, &earlier.categories),
+            tags: group_delta(&self.tags, &earlier.tags),
+        }
+    }
+}
+
+fn group_delta(
+    current: &BTreeMap<String, GroupStats>,
+    earlier: &BTreeMap<String, GroupStats>,
+) -> BTreeMap<String, GroupStats> {
+    current
+        .keys()
+        .chain(earlier.keys())
+        .map(|name| {
+            let current = current.get(name).copied().unwrap_or_default();
+            let earlier = earlier.get(name).copied().unwrap_or_default();
+            (name.clone(), current.difference(&earlier))
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted values, percent from 0.0 to 100.0, T::default() if there
+/// are no values
+pub(crate) fn percentile<T: Copy + Default>(sorted: &[T], percent: f64) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+    let rank = (percent.clamp(0.0, 100.0) * sorted.len() as f64 / 100.0).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Client {
+    /// Get the transfer counters of the session and the torrents by state, category and tag
+    ///
+    /// A single full sync/maindata request is used, keep a SyncSession and use
+    /// SessionStats::from_session to avoid transferring the whole torrent list each time.
+    ///
+    pub async fn stats(&mut self) -> Result<SessionStats, Error> {
+        let mut session = SyncSession::new();
+        self.sync(&mut session).await?;
+        SessionStats::from_session(&session)
+    }
+}
@@ -3,25 +3,27 @@
 // Sync API implements requests for obtaining changes since the last request. All Sync API methods are under "sync", e.g.: /api/v2/sync/methodName.
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 
 use crate::{
+    lenient,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
-    torrents::Torrent,
-    transfer::ConnectionStatus,
+    response::{decode, response_body},
+    torrents::{AddTorrent, State, Torrent},
+    transfer::{share_ratio, ConnectionStatus},
     Client, Error,
 };
 
-#[derive(Debug, Serialize, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GetMainData {
     /// Exclude messages with "message id" <= last_known_id (default: -1)
     pub rid: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GetPeersData {
     /// Torrent hash
     pub hash: String,
@@ -29,7 +31,7 @@ pub struct GetPeersData {
     pub rid: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MainData {
     /// Response ID
     pub rid: i64,
@@ -51,7 +53,7 @@ pub struct MainData {
     pub server_state: ServerState,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Category {
     /// Category name
@@ -60,30 +62,532 @@ pub struct Category {
     pub save_path: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerState {
     /// Global download rate (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_info_speed: i64,
     /// Data downloaded this session (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_info_data: i64,
     /// Global upload rate (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_info_speed: i64,
     /// Data uploaded this session (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_info_data: i64,
     /// Download rate limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_rate_limit: i64,
     /// Upload rate limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_rate_limit: i64,
     /// DHT nodes connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub dht_nodes: i64,
-    /// Connection status. See possible values here below
+    /// Connection status, Unknown if not reported
+    #[serde(default)]
     pub connection_status: ConnectionStatus,
     /// True if torrent queueing is enabled
     pub queueing: bool,
     /// True if alternative speed limits are enabled
     pub use_alt_speed_limits: bool,
     /// Transfer list refresh interval (milliseconds)
+    #[serde(deserialize_with = "lenient::i64")]
     pub refresh_interval: i64,
+    /// Free space on the disk of the default save path (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub free_space_on_disk: Option<i64>,
+    /// Data downloaded since the statistics were reset (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub alltime_dl: Option<i64>,
+    /// Data uploaded since the statistics were reset (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub alltime_ul: Option<i64>,
+    /// Global share ratio
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub global_ratio: Option<f64>,
+    /// Number of peer connections
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_peer_connections: Option<i64>,
+    /// Read cache hits (percentage)
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub read_cache_hits: Option<f64>,
+    /// Total buffers size (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_buffers_size: Option<i64>,
+    /// Write cache overload (percentage)
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub write_cache_overload: Option<f64>,
+    /// Read cache overload (percentage)
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub read_cache_overload: Option<f64>,
+    /// Number of queued disk IO jobs
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub queued_io_jobs: Option<i64>,
+    /// Average time in the disk IO queue (milliseconds)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub average_time_queue: Option<i64>,
+    /// Total size of queued data (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_queued_size: Option<i64>,
+    /// Data wasted this session (bytes)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_wasted_session: Option<i64>,
+}
+
+impl ServerState {
+    /// Share ratio since the statistics were reset: global_ratio, or computed from alltime_ul
+    /// and alltime_dl on servers not sending it. None if nothing was downloaded.
+    pub fn alltime_ratio(&self) -> Option<f64> {
+        match (self.global_ratio, self.alltime_ul, self.alltime_dl) {
+            (Some(ratio), ..) => Some(ratio),
+            (None, Some(uploaded), Some(downloaded)) => share_ratio(uploaded, downloaded),
+            _ => None,
+        }
+    }
+
+    /// Share ratio of the data transferred this session, None if nothing was downloaded
+    pub fn session_ratio(&self) -> Option<f64> {
+        share_ratio(self.up_info_data, self.dl_info_data)
+    }
+}
+
+/// Peer of a torrent, as sent by sync/torrentPeers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Peer {
+    /// Peer IP
+    pub ip: String,
+    /// Peer port
+    pub port: u16,
+    /// Client name and version
+    #[serde(default)]
+    pub client: String,
+    /// Client of the peer ID, e.g. -qB4630- (qBittorrent 4.6+)
+    #[serde(default)]
+    pub peer_id_client: Option<String>,
+    /// Connection type, e.g. BT or uTP
+    #[serde(default)]
+    pub connection: String,
+    /// Country name, only if resolving peer countries is enabled
+    #[serde(default)]
+    pub country: Option<String>,
+    /// ISO 3166 country code, only if resolving peer countries is enabled
+    #[serde(default)]
+    pub country_code: Option<String>,
+    /// Peer download rate (bytes/s)
+    #[serde(default)]
+    pub dl_speed: i64,
+    /// Peer upload rate (bytes/s)
+    #[serde(default)]
+    pub up_speed: i64,
+    /// Data downloaded from the peer on this connection (bytes)
+    #[serde(default)]
+    pub downloaded: i64,
+    /// Data uploaded to the peer on this connection (bytes)
+    #[serde(default)]
+    pub uploaded: i64,
+    /// Progress of the peer (percentage/100)
+    #[serde(default)]
+    pub progress: f64,
+    /// Share of the pieces of the peer we don't have (percentage/100)
+    #[serde(default)]
+    pub relevance: f64,
+    /// Peer flags, e.g. "D X"
+    #[serde(default)]
+    pub flags: String,
+    /// Description of the flags
+    #[serde(default)]
+    pub flags_desc: String,
+    /// Files the peer is exchanging, separated by newlines
+    #[serde(default)]
+    pub files: String,
+}
+
+/// Raw maindata response, possibly containing only the fields changed since the previous rid
+#[derive(Debug, Deserialize)]
+struct MainDataDelta {
+    rid: i64,
+    #[serde(default)]
+    full_update: bool,
+    #[serde(default)]
+    torrents: Map<String, Value>,
+    #[serde(default)]
+    torrents_removed: Vec<String>,
+    #[serde(default)]
+    categories: Map<String, Value>,
+    #[serde(default)]
+    categories_removed: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    tags_removed: Vec<String>,
+    #[serde(default)]
+    server_state: Map<String, Value>,
+}
+
+/// Raw torrentPeers response, possibly containing only the fields changed since the previous rid
+#[derive(Debug, Deserialize)]
+struct PeersDataDelta {
+    rid: i64,
+    #[serde(default)]
+    full_update: bool,
+    #[serde(default)]
+    peers: Map<String, Value>,
+    #[serde(default)]
+    peers_removed: Vec<String>,
+}
+
+/// Changes applied to a PeerSession by one torrentPeers response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeersDelta {
+    /// Whether the response contained all the data
+    pub full_update: bool,
+    /// Keys (ip:port) of peers seen for the first time
+    pub peers_added: Vec<String>,
+    /// Keys of peers added or changed
+    pub peers_changed: Vec<String>,
+    /// Keys of peers removed
+    pub peers_removed: Vec<String>,
+}
+
+/// Merged state of successive torrentPeers responses of a torrent
+#[derive(Debug, Default)]
+pub struct PeerSession {
+    hash: String,
+    rid: i64,
+    peers: HashMap<String, Map<String, Value>>,
+}
+
+/// Changes applied to a SyncSession by one maindata response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncDelta {
+    /// Whether the response contained all the data
+    pub full_update: bool,
+    /// Hashes of torrents seen for the first time
+    pub torrents_added: Vec<String>,
+    /// Hashes of torrents added or changed
+    pub torrents_changed: Vec<String>,
+    /// Hashes of torrents removed
+    pub torrents_removed: Vec<String>,
+    /// Names of categories seen for the first time
+    pub categories_added: Vec<String>,
+    /// Names of categories added or changed
+    pub categories_changed: Vec<String>,
+    /// Names of categories removed
+    pub categories_removed: Vec<String>,
+    /// Tags seen for the first time
+    pub tags_added: Vec<String>,
+    /// Tags removed
+    pub tags_removed: Vec<String>,
+    /// Whether any server state field changed
+    pub server_state_changed: bool,
+    /// Whether a full update was received after the session had synced, which happens when
+    /// qBittorrent lost the state of the session, e.g. after a restart
+    pub resynced: bool,
+}
+
+/// Merged state of successive maindata responses
+///
+/// qBittorrent only sends the fields that changed since the last rid, so the session keeps the
+/// raw objects and merges every response into them.
+#[derive(Debug, Default)]
+pub struct SyncSession {
+    rid: i64,
+    torrents: HashMap<String, Map<String, Value>>,
+    categories: HashMap<String, Map<String, Value>>,
+    tags: Vec<String>,
+    server_state: Map<String, Value>,
+}
+
+/// Interval between sync polls, adapted to the activity of the torrents
+///
+/// Polls every active_interval while a torrent is downloading. Once everything is idle, the
+/// interval starts at idle_interval and doubles after each idle poll up to max_idle_interval.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PollPolicy {
+    /// Interval while a torrent is downloading (default: 1s)
+    pub active_interval: Duration,
+    /// Interval after the first idle poll (default: 1s)
+    pub idle_interval: Duration,
+    /// Maximum interval when idle (default: 10s)
+    pub max_idle_interval: Duration,
+}
+
+impl Default for PollPolicy {
+    fn default() -> Self {
+        Self {
+            active_interval: Duration::from_secs(1),
+            idle_interval: Duration::from_secs(1),
+            max_idle_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl PollPolicy {
+    /// Poll every interval regardless of the activity
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            active_interval: interval,
+            idle_interval: interval,
+            max_idle_interval: interval,
+        }
+    }
+
+    /// Interval before the next poll after idle_polls successive idle polls (0 if active)
+    pub fn interval(&self, idle_polls: u32) -> Duration {
+        match idle_polls {
+            0 => self.active_interval,
+            polls => self
+                .idle_interval
+                .saturating_mul(2u32.saturating_pow(polls - 1))
+                .min(self.max_idle_interval),
+        }
+    }
+}
+
+/// Merge objects of a response into the session state, returning the added and changed keys
+fn merge_objects(
+    state: &mut HashMap<String, Map<String, Value>>,
+    values: Map<String, Value>,
+    full_update: bool,
+    removed: &mut Vec<String>,
+) -> (Vec<String>, Vec<String>) {
+    if full_update {
+        state.retain(|key, _| {
+            let keep = values.contains_key(key);
+            if !keep {
+                removed.push(key.clone());
+            }
+            keep
+        });
+    }
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (key, value) in values {
+        let Value::Object(fields) = value else {
+            continue;
+        };
+        match state.get_mut(&key) {
+            Some(current) if full_update => *current = fields,
+            Some(current) => current.extend(fields),
+            None => {
+                state.insert(key.clone(), fields);
+                added.push(key.clone());
+            }
+        }
+        changed.push(key);
+    }
+    (added, changed)
+}
+
+/// Torrent of merged fields, with the hash of the key
+fn to_torrent(hash: &str, fields: &Map<String, Value>) -> Result<Torrent, Error> {
+    let mut fields = fields.clone();
+    fields.insert("hash".to_string(), Value::String(hash.to_string()));
+    Ok(serde_json::from_value(Value::Object(fields))?)
+}
+
+impl SyncSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Response ID to send with the next request
+    pub fn rid(&self) -> i64 {
+        self.rid
+    }
+
+    /// Request a full update on the next sync, keeping the merged state
+    ///
+    /// The full update is compared with the current state, so the delta reports what changed
+    /// since the last sync.
+    pub fn reset(&mut self) {
+        self.rid = 0;
+    }
+
+    /// Merge a raw maindata response into the session
+    pub fn apply(&mut self, data: Value) -> Result<SyncDelta, Error> {
+        let data: MainDataDelta = serde_json::from_value(data)?;
+        let mut delta = SyncDelta {
+            full_update: data.full_update,
+            resynced: data.full_update && self.rid > 0,
+            ..Default::default()
+        };
+        self.rid = data.rid;
+
+        (delta.torrents_added, delta.torrents_changed) = merge_objects(
+            &mut self.torrents,
+            data.torrents,
+            data.full_update,
+            &mut delta.torrents_removed,
+        );
+        for hash in data.torrents_removed {
+            if self.torrents.remove(&hash).is_some() {
+                delta.torrents_removed.push(hash);
+            }
+        }
+
+        (delta.categories_added, delta.categories_changed) = merge_objects(
+            &mut self.categories,
+            data.categories,
+            data.full_update,
+            &mut delta.categories_removed,
+        );
+        for name in data.categories_removed {
+            if self.categories.remove(&name).is_some() {
+                delta.categories_removed.push(name);
+            }
+        }
+
+        if data.full_update {
+            delta.tags_removed = self
+                .tags
+                .iter()
+                .filter(|tag| !data.tags.contains(tag))
+                .cloned()
+                .collect();
+            self.tags.retain(|tag| data.tags.contains(tag));
+        }
+        for tag in data.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag.clone());
+                delta.tags_added.push(tag);
+            }
+        }
+        for tag in data.tags_removed {
+            if let Some(index) = self.tags.iter().position(|current| current == &tag) {
+                self.tags.remove(index);
+                delta.tags_removed.push(tag);
+            }
+        }
+
+        if data.full_update {
+            self.server_state = data.server_state;
+            delta.server_state_changed = true;
+        } else if !data.server_state.is_empty() {
+            self.server_state.extend(data.server_state);
+            delta.server_state_changed = true;
+        }
+
+        Ok(delta)
+    }
+
+    /// Raw fields of a torrent, as merged from the responses
+    pub fn raw_torrent(&self, hash: &str) -> Option<&Map<String, Value>> {
+        self.torrents.get(hash)
+    }
+
+    /// Whether a torrent is downloading data or metadata
+    pub fn is_active(&self) -> bool {
+        self.torrents.values().any(|fields| {
+            let state = fields.get("state").and_then(Value::as_str);
+            let dlspeed = fields.get("dlspeed").and_then(Value::as_i64);
+            state.is_some_and(|state| State::from(state).is_downloading())
+                || dlspeed.is_some_and(|dlspeed| dlspeed > 0)
+        })
+    }
+
+    /// Raw server state fields, as merged from the responses
+    pub fn raw_server_state(&self) -> &Map<String, Value> {
+        &self.server_state
+    }
+
+    /// Torrents of the session, by hash
+    pub fn torrents(&self) -> Result<HashMap<String, Torrent>, Error> {
+        self.torrents
+            .iter()
+            .map(|(hash, fields)| Ok((hash.clone(), to_torrent(hash, fields)?)))
+            .collect()
+    }
+
+    /// Torrent of the session, None if the session doesn't have it
+    pub fn torrent(&self, hash: &str) -> Result<Option<Torrent>, Error> {
+        self.torrents
+            .get(hash)
+            .map(|fields| to_torrent(hash, fields))
+            .transpose()
+    }
+
+    /// Categories of the session, by name
+    pub fn categories(&self) -> Result<HashMap<String, Category>, Error> {
+        self.categories
+            .iter()
+            .map(|(name, fields)| -> Result<(String, Category), Error> {
+                Ok((
+                    name.clone(),
+                    serde_json::from_value(Value::Object(fields.clone()))?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Tags of the session
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Global transfer info of the session
+    pub fn server_state(&self) -> Result<ServerState, Error> {
+        Ok(serde_json::from_value(Value::Object(
+            self.server_state.clone(),
+        ))?)
+    }
+}
+
+impl PeerSession {
+    /// Session of the peers of the torrent hash
+    pub fn new(hash: &str) -> Self {
+        Self {
+            hash: hash.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Hash of the torrent
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Response ID to send with the next request
+    pub fn rid(&self) -> i64 {
+        self.rid
+    }
+
+    /// Merge a raw torrentPeers response into the session
+    pub fn apply(&mut self, data: Value) -> Result<PeersDelta, Error> {
+        let data: PeersDataDelta = serde_json::from_value(data)?;
+        let mut delta = PeersDelta {
+            full_update: data.full_update,
+            ..Default::default()
+        };
+        self.rid = data.rid;
+        (delta.peers_added, delta.peers_changed) = merge_objects(
+            &mut self.peers,
+            data.peers,
+            data.full_update,
+            &mut delta.peers_removed,
+        );
+        for key in data.peers_removed {
+            if self.peers.remove(&key).is_some() {
+                delta.peers_removed.push(key);
+            }
+        }
+        Ok(delta)
+    }
+
+    /// Peers of the session, by ip:port
+    pub fn peers(&self) -> Result<HashMap<String, Peer>, Error> {
+        self.peers
+            .iter()
+            .map(|(key, fields)| -> Result<(String, Peer), Error> {
+                Ok((
+                    key.clone(),
+                    serde_json::from_value(Value::Object(fields.clone()))?,
+                ))
+            })
+            .collect()
+    }
 }
 
 impl Client {
@@ -125,10 +629,7 @@ impl Client {
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
     }
 
     /// Get torrent peers data
@@ -151,7 +652,7 @@ impl Client {
     ///
     /// String
     ///
-    /// The response is TODO
+    /// The raw JSON response, use sync_peers to get typed peers merged over successive rids.
     pub async fn get_peers_data(&mut self, values: GetPeersData) -> Result<String, Error> {
         let arguments = Arguments::Json(json!(values));
         let request = ApiRequest {
@@ -160,9 +661,105 @@ impl Client {
         };
         let response = self.send_request(&request).await?;
         match response.status_code().as_u16() {
-            200 => Ok(String::from_utf8(response.body().to_vec())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
+            200 => Ok(String::from_utf8(response_body(&response)?.to_vec())?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get main data changes since the last request of the session and merge them into it
+    ///
+    /// Unlike get_main_data, partial responses are supported: the session keeps the rid and the
+    /// merged state, so the full torrent list is available after every call. Returns
+    /// Error::NotAuth if the session is not valid anymore, e.g. after qBittorrent restarted.
+    ///
+    pub async fn sync(&mut self, session: &mut SyncSession) -> Result<SyncDelta, Error> {
+        let request = ApiRequest {
+            method: Method::MainData,
+            arguments: Some(Arguments::Form(format!("rid={}", session.rid))),
+        };
+        let response = self.send_request(&request).await?;
+        if response.status_code().as_u16() == 403 {
+            return Err(Error::NotAuth);
+        }
+        let data: Value =
+            self.check_default_status(&response, decode(request.method, &response)?)?;
+        session.apply(data)
+    }
+
+    /// Get the peer changes of the torrent of the session since its last request and merge
+    /// them into it
+    ///
+    /// Returns Error::NoTorrentHash if the torrent doesn't exist.
+    ///
+    pub async fn sync_peers(&mut self, session: &mut PeerSession) -> Result<PeersDelta, Error> {
+        let request = ApiRequest {
+            method: Method::TorrentPeers,
+            arguments: Some(Arguments::Form(format!(
+                "hash={}&rid={}",
+                session.hash, session.rid
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => session.apply(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get free space on the disk of the default save path (bytes)
+    ///
+    /// Reads server_state.free_space_on_disk of sync/maindata. The session of the client and
+    /// its clones keeps the rid of the last call, so only the first call transfers the full
+    /// torrent list, later ones only the changes since the previous call.
+    ///
+    pub async fn get_free_space(&mut self) -> Result<i64, Error> {
+        let last = self.session().free_space;
+        let rid = last.map_or(0, |(rid, _)| rid);
+        let request = ApiRequest {
+            method: Method::MainData,
+            arguments: Some(Arguments::Form(format!("rid={rid}"))),
+        };
+        let response = self.send_request(&request).await?;
+        let data: Value =
+            self.check_default_status(&response, decode(request.method, &response)?)?;
+        let full_update = data["full_update"].as_bool().unwrap_or(false);
+        // free_space_on_disk is only sent again once it changed
+        let free_space = match data["server_state"]["free_space_on_disk"].as_i64() {
+            Some(free_space) => free_space,
+            None if full_update => return Err(Error::NoFreeSpace),
+            None => last
+                .map(|(_, free_space)| free_space)
+                .ok_or(Error::NoFreeSpace)?,
+        };
+        let rid = data["rid"].as_i64().unwrap_or_default();
+        self.session().free_space = Some((rid, free_space));
+        Ok(free_space)
+    }
+
+    /// Check there is enough free space to add a torrent of size bytes
+    ///
+    /// Returns Error::InsufficientSpace if the free space is lower than size, to call before
+    /// submitting add. qBittorrent only reports the free space of the disk of the default save
+    /// path, so the check is skipped if add.savepath is set to another path.
+    ///
+    pub async fn check_space_for(&mut self, add: &AddTorrent, size: i64) -> Result<(), Error> {
+        if let Some(savepath) = &add.savepath {
+            let default = self.get_default_save_path().await?;
+            let separators = ['/', '\\'];
+            if savepath.trim_end_matches(separators) != default.trim_end_matches(separators) {
+                ::log::debug!("free space of {savepath} is not reported, skipping the check");
+                return Ok(());
+            }
+        }
+        let available = self.get_free_space().await?;
+        if available < size {
+            return Err(Error::InsufficientSpace {
+                required: size,
+                available,
+            });
         }
+        Ok(())
     }
 }
@@ -0,0 +1,87 @@
+// Tagging
+//
+// Helpers that apply tags to torrents based on their properties.
+
+use std::collections::HashMap;
+
+use url::Url;
+
+use crate::{torrents::GetTorrentList, Client, Error};
+
+/// Get the lowercase host of a tracker URL, e.g. `tracker.example.org` for
+/// `https://tracker.example.org:443/announce`
+pub fn tracker_domain(tracker: &str) -> Option<String> {
+    let url = Url::parse(tracker).ok()?;
+    url.host_str().map(str::to_lowercase)
+}
+
+/// Find the tag configured for a domain in a mapping with lowercase keys. A key matches the
+/// domain itself and all of its subdomains, the longest matching key wins.
+fn find_tag<'a>(mapping: &[(String, &'a String)], domain: &str) -> Option<&'a String> {
+    mapping
+        .iter()
+        .filter(|(key, _)| domain_matches(domain, key))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, tag)| *tag)
+}
+
+/// True if domain is key or one of its subdomains
+pub(crate) fn domain_matches(domain: &str, key: &str) -> bool {
+    domain == key
+        || domain
+            .strip_suffix(key)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+impl Client {
+    /// Tag torrents by the domain of their working tracker
+    ///
+    /// mapping is a map of tracker domain to tag name, e.g. `{"example.org": "example"}`.
+    /// A domain also matches its subdomains, so `example.org` applies to `tracker.example.org`,
+    /// and the most specific domain wins when several match.
+    /// Tags missing on the server are created before being applied. Torrents without a
+    /// working tracker or with an unmapped tracker domain are left untouched.
+    ///
+    /// Returns the hashes of tagged torrents grouped by tag.
+    ///
+    pub async fn auto_tag_by_tracker(
+        &mut self,
+        mapping: &HashMap<String, String>,
+    ) -> Result<HashMap<String, Vec<String>>, Error> {
+        let mapping: Vec<(String, &String)> = mapping
+            .iter()
+            .map(|(key, tag)| (key.to_lowercase(), tag))
+            .collect();
+        let torrents = self.get_torrent_list(GetTorrentList::default()).await?;
+        let mut tagged: HashMap<String, Vec<String>> = HashMap::new();
+        for torrent in torrents {
+            let Some(hash) = torrent.id().map(str::to_string) else {
+                continue;
+            };
+            let Some(domain) = tracker_domain(&torrent.tracker) else {
+                continue;
+            };
+            if let Some(tag) = find_tag(&mapping, &domain) {
+                tagged.entry(tag.clone()).or_default().push(hash);
+            }
+        }
+        if tagged.is_empty() {
+            return Ok(tagged);
+        }
+
+        let existing = self.get_tags().await?;
+        let missing: Vec<&str> = tagged
+            .keys()
+            .filter(|tag| !existing.contains(tag))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            self.create_tags(missing).await?;
+        }
+
+        for (tag, hashes) in &tagged {
+            self.add_tags(hashes, vec![tag.as_str()]).await?;
+        }
+        Ok(tagged)
+    }
+}
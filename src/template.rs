@@ -0,0 +1,101 @@
+// Template
+//
+// Save path templates of AddTorrent, expanded on the client side when the torrent is added.
+
+use crate::{
+    magnet::Magnet, maintenance::now, meta::TorrentMeta, tagging::tracker_domain,
+    torrents::AddTorrent,
+};
+
+impl AddTorrent {
+    /// Save the torrent to a path expanded from template when it is added, e.g.
+    /// "/data/{category}/{yyyy}/{mm}"
+    ///
+    /// Variables:
+    ///
+    /// {category} category of the torrent
+    /// {tag} first tag of the torrent
+    /// {tracker} domain of the first tracker of the magnet link or .torrent file
+    /// {yyyy} {mm} {dd} current UTC date
+    ///
+    /// Variables without a value expand to nothing, and the empty path segments they leave
+    /// are removed. Other text in braces is kept as is. The template replaces savepath.
+    ///
+    pub fn savepath_template(mut self, template: &str) -> Self {
+        self.savepath_template = Some(template.to_string());
+        self
+    }
+
+    /// Save path of the template for this torrent, None without template
+    pub fn expanded_savepath(&self) -> Option<String> {
+        let template = self.savepath_template.as_deref()?;
+        let (year, month, day) = civil_date(now().div_euclid(86400));
+        let tag = self
+            .tags
+            .as_deref()
+            .and_then(|tags| tags.split(',').map(str::trim).find(|tag| !tag.is_empty()));
+        let tracker = self
+            .first_tracker()
+            .and_then(|tracker| tracker_domain(&tracker));
+        let variables = [
+            ("{category}", self.category.clone().unwrap_or_default()),
+            ("{tag}", tag.unwrap_or_default().to_string()),
+            ("{tracker}", tracker.unwrap_or_default()),
+            ("{yyyy}", format!("{year:04}")),
+            ("{mm}", format!("{month:02}")),
+            ("{dd}", format!("{day:02}")),
+        ];
+        let mut path = template.to_string();
+        for (name, value) in variables {
+            path = path.replace(name, &value);
+        }
+        Some(remove_empty_segments(&path))
+    }
+
+    /// First tracker of a single magnet link or .torrent file
+    fn first_tracker(&self) -> Option<String> {
+        match (self.all_urls().as_slice(), self.torrents.is_empty()) {
+            ([url], true) => url.parse::<Magnet>().ok()?.trackers.into_iter().next(),
+            ([], false) => TorrentMeta::from_bytes(&self.torrents)
+                .ok()?
+                .trackers
+                .into_iter()
+                .next(),
+            _ => None,
+        }
+    }
+}
+
+/// Remove the empty segments left by empty variables, keeping a leading separator
+fn remove_empty_segments(path: &str) -> String {
+    let separator = if path.contains('\\') && !path.contains('/') {
+        '\\'
+    } else {
+        '/'
+    };
+    let segments: Vec<&str> = path
+        .split(separator)
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let joined = segments.join(&separator.to_string());
+    if path.starts_with(separator) {
+        format!("{separator}{joined}")
+    } else {
+        joined
+    }
+}
+
+/// Year, month and day of a number of days since 1970-01-01
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
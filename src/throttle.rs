@@ -0,0 +1,108 @@
+// Throttle
+//
+// Client-side rate limiting of requests with token buckets.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+
+use crate::request::Method;
+
+/// Allow at most `requests` requests every `per`, with bursts of up to `requests`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Number of requests
+    pub requests: u32,
+    /// Period in which the requests are allowed
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self { requests, per }
+    }
+
+    /// Allow at most requests per second
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.requests),
+            updated: Instant::now(),
+        }
+    }
+
+    /// Take a token and return how long to wait before sending. The token count may go
+    /// negative, which reserves the next free slot for concurrent callers.
+    fn acquire(&mut self) -> Duration {
+        let capacity = f64::from(self.limit.requests.max(1));
+        let rate = capacity / self.limit.per.as_secs_f64().max(f64::EPSILON);
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.updated = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+type SharedBucket = Arc<Mutex<Bucket>>;
+
+/// Rate limiters of a client, shared between its clones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Throttle {
+    default: Option<SharedBucket>,
+    methods: HashMap<Method, Option<SharedBucket>>,
+}
+
+impl Throttle {
+    pub(crate) fn new(
+        default: Option<RateLimit>,
+        methods: &HashMap<Method, Option<RateLimit>>,
+    ) -> Self {
+        let bucket = |limit: RateLimit| Arc::new(Mutex::new(Bucket::new(limit)));
+        Self {
+            default: default.map(bucket),
+            methods: methods
+                .iter()
+                .map(|(method, limit)| (*method, limit.map(bucket)))
+                .collect(),
+        }
+    }
+
+    /// Wait until a request with method may be sent
+    pub(crate) async fn wait(&self, method: &Method) {
+        let bucket = match self.methods.get(method) {
+            Some(bucket) => bucket.as_ref(),
+            None => self.default.as_ref(),
+        };
+        let Some(bucket) = bucket else {
+            return;
+        };
+        let delay = bucket
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .acquire();
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Timing
+//
+// Per-method request counts, errors, latencies and transferred bytes of a client, enabled by
+// ClientBuilder::request_stats, to find the endpoints that are slow on an instance.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use netc::Response;
+
+use crate::{request::Method, stats::percentile, Client, Error};
+
+/// Number of latencies kept per method for the percentiles
+const LATENCY_SAMPLES: usize = 1000;
+
+/// Statistics of the requests of a method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    /// Number of requests
+    pub count: u64,
+    /// Requests that failed or got a 4xx or 5xx status code
+    pub errors: u64,
+    /// Median latency of the last 1000 requests
+    pub p50: Duration,
+    /// 99th percentile latency of the last 1000 requests
+    pub p99: Duration,
+    /// Maximum latency
+    pub max: Duration,
+    /// Bytes of the request bodies
+    pub bytes_sent: u64,
+    /// Bytes of the response bodies, as received
+    pub bytes_received: u64,
+}
+
+/// Request statistics returned by Client::stats_snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Statistics by method, for the methods requested at least once
+    pub methods: HashMap<Method, MethodStats>,
+}
+
+impl ClientStats {
+    /// Statistics of all the methods together, without percentiles
+    pub fn total(&self) -> MethodStats {
+        self.methods
+            .values()
+            .fold(MethodStats::default(), |total, stats| MethodStats {
+                count: total.count + stats.count,
+                errors: total.errors + stats.errors,
+                max: total.max.max(stats.max),
+                bytes_sent: total.bytes_sent + stats.bytes_sent,
+                bytes_received: total.bytes_received + stats.bytes_received,
+                ..total
+            })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Record {
+    stats: MethodStats,
+    latencies: VecDeque<Duration>,
+}
+
+/// Request statistics of a client, shared between its clones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestStats {
+    records: Arc<Mutex<HashMap<Method, Record>>>,
+}
+
+impl RequestStats {
+    fn records(&self) -> MutexGuard<'_, HashMap<Method, Record>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record a request of method that took latency
+    pub(crate) fn record(
+        &self,
+        method: Method,
+        latency: Duration,
+        bytes_sent: usize,
+        result: &Result<Response, Error>,
+    ) {
+        let mut records = self.records();
+        let record = records.entry(method).or_default();
+        record.stats.count += 1;
+        record.stats.bytes_sent += bytes_sent as u64;
+        match result {
+            Ok(response) => {
+                record.stats.bytes_received += response.body().as_ref().len() as u64;
+                if response.status_code().as_u16() >= 400 {
+                    record.stats.errors += 1;
+                }
+            }
+            Err(_) => record.stats.errors += 1,
+        }
+        record.stats.max = record.stats.max.max(latency);
+        if record.latencies.len() == LATENCY_SAMPLES {
+            record.latencies.pop_front();
+        }
+        record.latencies.push_back(latency);
+    }
+
+    fn snapshot(&self) -> ClientStats {
+        let methods = self
+            .records()
+            .iter()
+            .map(|(method, record)| {
+                let mut latencies: Vec<Duration> = record.latencies.iter().copied().collect();
+                latencies.sort_unstable();
+                let stats = MethodStats {
+                    p50: percentile(&latencies, 50.0),
+                    p99: percentile(&latencies, 99.0),
+                    ..record.stats
+                };
+                (*method, stats)
+            })
+            .collect();
+        ClientStats { methods }
+    }
+}
+
+impl Client {
+    /// Statistics of the requests sent by the client and its clones, empty unless enabled with
+    /// ClientBuilder::request_stats
+    ///
+    /// Latencies are measured in send_request, so they include the login, rate limit waits and
+    /// retries of the request. Responses served from the cache are not counted.
+    pub fn stats_snapshot(&self) -> ClientStats {
+        self.request_stats
+            .as_ref()
+            .map(RequestStats::snapshot)
+            .unwrap_or_default()
+    }
+
+    /// Clear the request statistics, e.g. after each report
+    pub fn reset_stats(&self) {
+        if let Some(stats) = &self.request_stats {
+            stats.records().clear();
+        }
+    }
+}
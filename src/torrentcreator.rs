@@ -0,0 +1,361 @@
+/// Torrent creator
+///
+/// All Torrent creator API methods are under "torrentcreator", e.g.: /api/v2/torrentcreator/methodName.
+/// The torrent creator API is available since qBittorrent 5.0.
+use std::time::Duration;
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::time::sleep;
+
+use crate::{
+    enums::str_enum,
+    form::encode,
+    request::{ApiRequest, Arguments, Method},
+    response::{decode, response_body},
+    Client, Error,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddCreatorTask {
+    /// Path of the file or directory on the server to create the torrent from
+    pub source_path: String,
+    /// Path on the server to save the .torrent file to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub torrent_file_path: Option<String>,
+    /// Torrent format (default: hybrid)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<TorrentFormat>,
+    /// Piece size (bytes), 0 for automatic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub piece_size: Option<i64>,
+    /// Optimize file alignment (v1 torrents)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimize_alignment: Option<bool>,
+    /// Align files larger than this size (bytes), -1 for all files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padded_file_size_limit: Option<i64>,
+    /// Private torrent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+    /// Add the created torrent to start seeding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_seeding: Option<bool>,
+    /// Torrent comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Torrent source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Tracker URLs separated by |
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trackers: Option<String>,
+    /// Web seed URLs separated by |
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_seeds: Option<String>,
+}
+
+impl AddCreatorTask {
+    pub fn builder(source_path: &str) -> AddCreatorTaskBuilder {
+        AddCreatorTaskBuilder {
+            values: AddCreatorTask {
+                source_path: source_path.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Builder of AddCreatorTask
+#[derive(Debug)]
+pub struct AddCreatorTaskBuilder {
+    values: AddCreatorTask,
+}
+
+impl AddCreatorTaskBuilder {
+    /// Save the .torrent file to this path on the server
+    pub fn torrent_file_path(mut self, path: &str) -> Self {
+        self.values.torrent_file_path = Some(path.to_string());
+        self
+    }
+
+    /// Torrent format
+    pub fn format(mut self, format: TorrentFormat) -> Self {
+        self.values.format = Some(format);
+        self
+    }
+
+    /// Piece size (bytes), 0 for automatic
+    pub fn piece_size(mut self, piece_size: i64) -> Self {
+        self.values.piece_size = Some(piece_size);
+        self
+    }
+
+    /// Private torrent
+    pub fn private(mut self, private: bool) -> Self {
+        self.values.private = Some(private);
+        self
+    }
+
+    /// Add the created torrent to start seeding
+    pub fn start_seeding(mut self, start_seeding: bool) -> Self {
+        self.values.start_seeding = Some(start_seeding);
+        self
+    }
+
+    /// Torrent comment
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.values.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Torrent source
+    pub fn source(mut self, source: &str) -> Self {
+        self.values.source = Some(source.to_string());
+        self
+    }
+
+    /// Tracker URLs
+    pub fn trackers(mut self, trackers: Vec<&str>) -> Self {
+        self.values.trackers = Some(trackers.join("|"));
+        self
+    }
+
+    /// Web seed URLs
+    pub fn url_seeds(mut self, url_seeds: Vec<&str>) -> Self {
+        self.values.url_seeds = Some(url_seeds.join("|"));
+        self
+    }
+
+    pub fn build(self) -> AddCreatorTask {
+        self.values
+    }
+}
+
+str_enum! {
+    pub enum TorrentFormat {
+        V1 = "v1",
+        V2 = "v2",
+        Hybrid = "hybrid",
+    }
+}
+
+str_enum! {
+    pub enum CreatorTaskState {
+        Queued = "Queued",
+        Running = "Running",
+        Finished = "Finished",
+        Failed = "Failed",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorTaskStatus {
+    /// ID of the task
+    #[serde(rename = "taskID")]
+    pub task_id: String,
+    /// Path of the source file or directory
+    pub source: String,
+    /// State of the task
+    pub status: CreatorTaskState,
+    /// Progress (percentage)
+    #[serde(default)]
+    pub progress: Option<f64>,
+    /// Error message if the task failed
+    #[serde(default)]
+    pub error_message: Option<String>,
+    /// Path of the saved .torrent file
+    #[serde(default)]
+    pub torrent_file_path: Option<String>,
+    /// Torrent format
+    #[serde(default)]
+    pub format: Option<TorrentFormat>,
+    /// Piece size (bytes)
+    #[serde(default)]
+    pub piece_size: Option<i64>,
+    /// Private torrent
+    #[serde(default)]
+    pub private: Option<bool>,
+    /// Torrent comment
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Tracker URLs
+    #[serde(default)]
+    pub trackers: Vec<String>,
+    /// Web seed URLs
+    #[serde(default)]
+    pub url_seeds: Vec<String>,
+    /// Time when the task was added
+    #[serde(default)]
+    pub time_added: Option<String>,
+    /// Time when the task started
+    #[serde(default)]
+    pub time_started: Option<String>,
+    /// Time when the task finished
+    #[serde(default)]
+    pub time_finished: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddedCreatorTask {
+    #[serde(rename = "taskID")]
+    task_id: String,
+}
+
+impl Client {
+    /// Add torrent creation task
+    ///
+    /// Name: addTask
+    ///
+    /// Parameters:
+    ///
+    /// AddCreatorTask
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 400 Source path or parameters are not valid
+    /// 409 Too many active tasks
+    /// 200 All other scenarios
+    ///
+    /// The ID of the created task.
+    ///
+    pub async fn add_creator_task(&mut self, values: AddCreatorTask) -> Result<String, Error> {
+        let request = ApiRequest {
+            method: Method::CreatorAddTask,
+            arguments: Some(Arguments::Json(json!(values))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => {
+                let added: AddedCreatorTask = decode(request.method, &response)?;
+                Ok(added.task_id)
+            }
+            400 => Err(self.in_context(Error::BadResponse(String::from_utf8(
+                response_body(&response)?.to_vec(),
+            )?))),
+            409 => Err(self.in_context(Error::TooManyCreatorTasks)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get torrent creation tasks status
+    ///
+    /// Name: status
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// taskID string ID of the task, all tasks if omitted
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Task ID was not found
+    /// 200 All other scenarios
+    ///
+    /// Vec<CreatorTaskStatus>
+    ///
+    pub async fn get_creator_tasks(
+        &mut self,
+        task_id: Option<&str>,
+    ) -> Result<Vec<CreatorTaskStatus>, Error> {
+        let request = ApiRequest {
+            method: Method::CreatorStatus,
+            arguments: task_id.map(|id| Arguments::Form(format!("taskID={}", encode(id)))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoCreatorTask)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get status of a torrent creation task
+    pub async fn get_creator_task(&mut self, task_id: &str) -> Result<CreatorTaskStatus, Error> {
+        self.get_creator_tasks(Some(task_id))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(Error::NoCreatorTask)
+    }
+
+    /// Poll a torrent creation task every poll_interval until it is finished or failed
+    pub async fn wait_creator_task(
+        &mut self,
+        task_id: &str,
+        poll_interval: Duration,
+    ) -> Result<CreatorTaskStatus, Error> {
+        loop {
+            let status = self.get_creator_task(task_id).await?;
+            match status.status {
+                CreatorTaskState::Finished | CreatorTaskState::Failed => return Ok(status),
+                _ => sleep(poll_interval).await,
+            }
+        }
+    }
+
+    /// Get created torrent file
+    ///
+    /// Name: torrentFile
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// taskID string ID of the task
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Task ID was not found
+    /// 409 Task has not finished or failed
+    /// 200 All other scenarios
+    ///
+    /// The response is the raw .torrent file.
+    ///
+    pub async fn get_creator_torrent_file(&mut self, task_id: &str) -> Result<Bytes, Error> {
+        let request = ApiRequest {
+            method: Method::CreatorTorrentFile,
+            arguments: Some(Arguments::Form(format!("taskID={}", encode(task_id)))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(response_body(&response)?),
+            404 => Err(self.in_context(Error::NoCreatorTask)),
+            409 => Err(self.in_context(Error::CreatorTaskNotFinished)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Delete torrent creation task
+    ///
+    /// Name: deleteTask
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// taskID string ID of the task
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Task ID was not found
+    /// 200 All other scenarios
+    ///
+    pub async fn delete_creator_task(&mut self, task_id: &str) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::CreatorDeleteTask,
+            arguments: Some(Arguments::Form(format!("taskID={}", encode(task_id)))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            404 => Err(self.in_context(Error::NoCreatorTask)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+}
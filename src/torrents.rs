@@ -1,19 +1,36 @@
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use bytes::Bytes;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::json;
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use tokio::time::sleep;
 
 use crate::{
     client::Client,
+    enums::{int_enum, str_enum},
     error::Error,
+    form::{encode, join_hashes, join_lines, join_tags, join_urls, Multipart},
+    lenient,
+    magnet::Magnet,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
+    response::{decode, decode_body, response_body, JsonArray},
+    sync::Category,
+    transfer::{join_peers, PeerAddr},
 };
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GetTorrentList {
-    /// Filter torrent list by state. Allowed state filters: all, downloading, seeding, completed, paused, active, inactive, resumed, stalled, stalled_uploading, stalled_downloading, errored
+    /// Filter torrent list by state
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub filter: Option<String>,
+    pub filter: Option<TorrentFilter>,
     /// Get torrents with the given category (empty string means "without category"; no "category" parameter means "any category" <- broken until #11748 is resolved). Remember to URL-encode the category name. For example, My category becomes My%20category
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<String>,
@@ -22,7 +39,7 @@ pub struct GetTorrentList {
     pub tag: Option<String>,
     /// Sort torrents by given key. They can be sorted using any field of the response's JSON array (which are documented below) as the sort key.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<String>,
+    pub sort: Option<SortKey>,
     /// Enable reverse sorting. Defaults to false
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reverse: Option<bool>,
@@ -37,31 +54,304 @@ pub struct GetTorrentList {
     pub hashes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl GetTorrentList {
+    pub fn builder() -> GetTorrentListBuilder {
+        GetTorrentListBuilder::default()
+    }
+}
+
+/// Builder of GetTorrentList
+#[derive(Debug, Default)]
+pub struct GetTorrentListBuilder {
+    values: GetTorrentList,
+}
+
+impl GetTorrentListBuilder {
+    /// Filter torrent list by state
+    pub fn filter(mut self, filter: TorrentFilter) -> Self {
+        self.values.filter = Some(filter);
+        self
+    }
+
+    /// Get torrents with the given category, empty string means "without category"
+    pub fn category(mut self, category: &str) -> Self {
+        self.values.category = Some(category.to_string());
+        self
+    }
+
+    /// Get torrents with the given tag, empty string means "without tag"
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.values.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Sort torrents by given key
+    pub fn sort(mut self, sort: SortKey) -> Self {
+        self.values.sort = Some(sort);
+        self
+    }
+
+    /// Enable reverse sorting
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.values.reverse = Some(reverse);
+        self
+    }
+
+    /// Limit the number of torrents returned
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.values.limit = Some(limit);
+        self
+    }
+
+    /// Set offset (if less than 0, offset from end)
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.values.offset = Some(offset);
+        self
+    }
+
+    /// Filter by hashes
+    pub fn hashes(mut self, hashes: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.values.hashes = Some(join_hashes(hashes));
+        self
+    }
+
+    pub fn build(self) -> GetTorrentList {
+        self.values
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TorrentFilter {
+    All,
+    Downloading,
+    Seeding,
+    Completed,
+    Paused,
+    Active,
+    Inactive,
+    Resumed,
+    Stalled,
+    StalledUploading,
+    StalledDownloading,
+    Errored,
+}
+
+/// Sort key of the torrent list, named after the Torrent fields
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    AddedOn,
+    AmountLeft,
+    AutoTmm,
+    Availability,
+    Category,
+    Completed,
+    CompletionOn,
+    DlLimit,
+    DownloadPath,
+    Dlspeed,
+    Downloaded,
+    DownloadedSession,
+    Eta,
+    FLPiecePrio,
+    ForceStart,
+    Hash,
+    InfohashV1,
+    InfohashV2,
+    LastActivity,
+    MagnetUri,
+    MaxRatio,
+    MaxSeedingTime,
+    Name,
+    NumComplete,
+    NumIncomplete,
+    NumLeechs,
+    NumSeeds,
+    Priority,
+    Progress,
+    Ratio,
+    RatioLimit,
+    SavePath,
+    SeedingTimeLimit,
+    SeenComplete,
+    SeqDl,
+    Size,
+    State,
+    SuperSeeding,
+    Tags,
+    TimeActive,
+    TotalSize,
+    Tracker,
+    UpLimit,
+    Uploaded,
+    UploadedSession,
+    Upspeed,
+    /// Any other field, sent as is
+    Other(String),
+}
+
+impl SortKey {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SortKey::AddedOn => "added_on",
+            SortKey::AmountLeft => "amount_left",
+            SortKey::AutoTmm => "auto_tmm",
+            SortKey::Availability => "availability",
+            SortKey::Category => "category",
+            SortKey::Completed => "completed",
+            SortKey::CompletionOn => "completion_on",
+            SortKey::DlLimit => "dl_limit",
+            SortKey::DownloadPath => "download_path",
+            SortKey::Dlspeed => "dlspeed",
+            SortKey::Downloaded => "downloaded",
+            SortKey::DownloadedSession => "downloaded_session",
+            SortKey::Eta => "eta",
+            SortKey::FLPiecePrio => "f_l_piece_prio",
+            SortKey::ForceStart => "force_start",
+            SortKey::Hash => "hash",
+            SortKey::InfohashV1 => "infohash_v1",
+            SortKey::InfohashV2 => "infohash_v2",
+            SortKey::LastActivity => "last_activity",
+            SortKey::MagnetUri => "magnet_uri",
+            SortKey::MaxRatio => "max_ratio",
+            SortKey::MaxSeedingTime => "max_seeding_time",
+            SortKey::Name => "name",
+            SortKey::NumComplete => "num_complete",
+            SortKey::NumIncomplete => "num_incomplete",
+            SortKey::NumLeechs => "num_leechs",
+            SortKey::NumSeeds => "num_seeds",
+            SortKey::Priority => "priority",
+            SortKey::Progress => "progress",
+            SortKey::Ratio => "ratio",
+            SortKey::RatioLimit => "ratio_limit",
+            SortKey::SavePath => "save_path",
+            SortKey::SeedingTimeLimit => "seeding_time_limit",
+            SortKey::SeenComplete => "seen_complete",
+            SortKey::SeqDl => "seq_dl",
+            SortKey::Size => "size",
+            SortKey::State => "state",
+            SortKey::SuperSeeding => "super_seeding",
+            SortKey::Tags => "tags",
+            SortKey::TimeActive => "time_active",
+            SortKey::TotalSize => "total_size",
+            SortKey::Tracker => "tracker",
+            SortKey::UpLimit => "up_limit",
+            SortKey::Uploaded => "uploaded",
+            SortKey::UploadedSession => "uploaded_session",
+            SortKey::Upspeed => "upspeed",
+            SortKey::Other(key) => key,
+        }
+    }
+}
+
+impl From<&str> for SortKey {
+    fn from(key: &str) -> Self {
+        match key {
+            "added_on" => SortKey::AddedOn,
+            "amount_left" => SortKey::AmountLeft,
+            "auto_tmm" => SortKey::AutoTmm,
+            "availability" => SortKey::Availability,
+            "category" => SortKey::Category,
+            "completed" => SortKey::Completed,
+            "completion_on" => SortKey::CompletionOn,
+            "dl_limit" => SortKey::DlLimit,
+            "download_path" => SortKey::DownloadPath,
+            "dlspeed" => SortKey::Dlspeed,
+            "downloaded" => SortKey::Downloaded,
+            "downloaded_session" => SortKey::DownloadedSession,
+            "eta" => SortKey::Eta,
+            "f_l_piece_prio" => SortKey::FLPiecePrio,
+            "force_start" => SortKey::ForceStart,
+            "hash" => SortKey::Hash,
+            "infohash_v1" => SortKey::InfohashV1,
+            "infohash_v2" => SortKey::InfohashV2,
+            "last_activity" => SortKey::LastActivity,
+            "magnet_uri" => SortKey::MagnetUri,
+            "max_ratio" => SortKey::MaxRatio,
+            "max_seeding_time" => SortKey::MaxSeedingTime,
+            "name" => SortKey::Name,
+            "num_complete" => SortKey::NumComplete,
+            "num_incomplete" => SortKey::NumIncomplete,
+            "num_leechs" => SortKey::NumLeechs,
+            "num_seeds" => SortKey::NumSeeds,
+            "priority" => SortKey::Priority,
+            "progress" => SortKey::Progress,
+            "ratio" => SortKey::Ratio,
+            "ratio_limit" => SortKey::RatioLimit,
+            "save_path" => SortKey::SavePath,
+            "seeding_time_limit" => SortKey::SeedingTimeLimit,
+            "seen_complete" => SortKey::SeenComplete,
+            "seq_dl" => SortKey::SeqDl,
+            "size" => SortKey::Size,
+            "state" => SortKey::State,
+            "super_seeding" => SortKey::SuperSeeding,
+            "tags" => SortKey::Tags,
+            "time_active" => SortKey::TimeActive,
+            "total_size" => SortKey::TotalSize,
+            "tracker" => SortKey::Tracker,
+            "up_limit" => SortKey::UpLimit,
+            "uploaded" => SortKey::Uploaded,
+            "uploaded_session" => SortKey::UploadedSession,
+            "upspeed" => SortKey::Upspeed,
+            key => SortKey::Other(key.to_string()),
+        }
+    }
+}
+
+impl Serialize for SortKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SortKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let key = String::deserialize(deserializer)?;
+        Ok(SortKey::from(key.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Torrent {
     /// Time (Unix Epoch) when the torrent was added to the client
+    #[serde(deserialize_with = "lenient::i64")]
     pub added_on: i64,
     /// Amount of data left to download (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub amount_left: i64,
     /// Whether this torrent is managed by Automatic Torrent Management
     pub auto_tmm: bool,
     /// Percentage of file pieces currently available
+    #[serde(default, deserialize_with = "lenient::option_f64")]
     pub availability: Option<f64>,
     /// Category of the torrent
     pub category: String,
     /// Amount of transfer data completed (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub completed: i64,
     /// Time (Unix Epoch) when the torrent completed
+    #[serde(deserialize_with = "lenient::i64")]
     pub completion_on: i64,
     /// Torrent download speed limit (bytes/s). -1 if ulimited.
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_limit: i64,
     /// Torrent download speed (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dlspeed: i64,
     /// Amount of data downloaded
+    #[serde(deserialize_with = "lenient::i64")]
     pub downloaded: i64,
     /// Amount of data downloaded this session
-    pub downloaded_session: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub downloaded_session: Option<i64>,
     /// Torrent ETA (seconds)
+    #[serde(deserialize_with = "lenient::i64")]
     pub eta: i64,
     /// True if first last piece are prioritized
     pub f_l_piece_prio: bool,
@@ -70,40 +360,54 @@ pub struct Torrent {
     /// Torrent hash
     pub hash: Option<String>,
     /// Last time (Unix Epoch) when a chunk was downloaded/uploaded
+    #[serde(deserialize_with = "lenient::i64")]
     pub last_activity: i64,
     /// Magnet URI corresponding to this torrent
     pub magnet_uri: String,
     /// Maximum share ratio until torrent is stopped from seeding/uploading
-    pub max_ratio: f64,
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub max_ratio: Option<f64>,
     /// Maximum seeding time (seconds) until torrent is stopped from seeding
-    pub max_seeding_time: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub max_seeding_time: Option<i64>,
     /// Torrent name
     pub name: String,
     /// Number of seeds in the swarm
+    #[serde(deserialize_with = "lenient::i64")]
     pub num_complete: i64,
     /// Number of leechers in the swarm
+    #[serde(deserialize_with = "lenient::i64")]
     pub num_incomplete: i64,
     /// Number of leechers connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub num_leechs: i64,
     /// Number of seeds connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub num_seeds: i64,
     /// Torrent priority. Returns -1 if queuing is disabled or torrent is in seed mode
+    #[serde(deserialize_with = "lenient::i64")]
     pub priority: i64,
     /// Torrent progress (percentage/100)
+    #[serde(deserialize_with = "lenient::f64")]
     pub progress: f64,
     /// Torrent share ratio. Max ratio value: 9999.
+    #[serde(deserialize_with = "lenient::f64")]
     pub ratio: f64,
-    /// TODO (what is different from max_ratio?)
-    pub ratio_limit: f64,
+    /// Share ratio limit set for this torrent, -2 to use the global limit, -1 for no limit
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub ratio_limit: Option<f64>,
     /// Path where this torrent's data is stored
     pub save_path: String,
-    /// TODO (what is different from max_seeding_time?)
-    pub seeding_time_limit: i64,
+    /// Seeding time limit (minutes) set for this torrent, -2 to use the global limit, -1 for no limit
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub seeding_time_limit: Option<i64>,
     /// Time (Unix Epoch) when this torrent was last seen complete
+    #[serde(deserialize_with = "lenient::i64")]
     pub seen_complete: i64,
     /// True if sequential download is enabled
     pub seq_dl: bool,
     /// Total size (bytes) of files selected for download
+    #[serde(deserialize_with = "lenient::i64")]
     pub size: i64,
     /// Torrent state. See table here below for the possible values
     pub state: String,
@@ -112,142 +416,382 @@ pub struct Torrent {
     /// Comma-concatenated tag list of the torrent
     pub tags: String,
     /// Total active time (seconds)
+    #[serde(deserialize_with = "lenient::i64")]
     pub time_active: i64,
     /// Total size (bytes) of all file in this torrent (including unselected ones)
+    #[serde(deserialize_with = "lenient::i64")]
     pub total_size: i64,
     /// The first tracker with working status. Returns empty : String, if no tracker is working.
     pub tracker: String,
     /// Torrent upload speed limit (bytes/s). -1 if ulimited.
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_limit: i64,
     /// Amount of data uploaded
+    #[serde(deserialize_with = "lenient::i64")]
     pub uploaded: i64,
     /// Amount of data uploaded this session
-    pub uploaded_session: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub uploaded_session: Option<i64>,
     /// Torrent upload speed (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub upspeed: i64,
+    /// Path where this torrent's data is stored while incomplete (Web API v2.8.4+)
+    #[serde(default)]
+    pub download_path: Option<String>,
+    /// Torrent SHA1 info hash (Web API v2.8.4+)
+    #[serde(default)]
+    pub infohash_v1: Option<String>,
+    /// Torrent SHA256 info hash (Web API v2.8.4+)
+    #[serde(default)]
+    pub infohash_v2: Option<String>,
+    /// Absolute path of the torrent content, the file for single file torrents (qBittorrent 4.3.2+)
+    #[serde(default)]
+    pub content_path: Option<String>,
+    /// Total seeding time (seconds) (qBittorrent 4.4+)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub seeding_time: Option<i64>,
+    /// Number of trackers of the torrent (qBittorrent 4.5+)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub trackers_count: Option<i64>,
+    /// Inactive seeding time limit (minutes) set for this torrent, -2 to use the global limit, -1 for no limit (qBittorrent 4.6+)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub inactive_seeding_time_limit: Option<i64>,
+    /// Maximum inactive seeding time (minutes) until torrent is stopped from seeding (qBittorrent 4.6+)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub max_inactive_seeding_time: Option<i64>,
+    /// True if the metadata of the torrent is received (qBittorrent 5.0+)
+    #[serde(default)]
+    pub has_metadata: Option<bool>,
+    /// True if the torrent is private (qBittorrent 5.0+)
+    #[serde(default)]
+    pub private: Option<bool>,
+    /// Torrent comment (qBittorrent 5.0+)
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Torrent popularity, the ratio per month (qBittorrent 5.0+)
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub popularity: Option<f64>,
+    /// Number of seconds until the next announce (qBittorrent 5.0+)
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub reannounce: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum State {
-    /// Some error occurred, applies to paused torrents
-    Error,
-    /// Torrent data files is missing
-    MissingFiles,
-    /// Torrent is being seeded and data is being transferred
-    Uploading,
-    /// Torrent is paused and has finished downloading
-    PausedUP,
-    /// Queuing is enabled and torrent is queued for upload
-    QueuedUP,
-    /// Torrent is being seeded, but no connection were made
-    StalledUP,
-    /// Torrent has finished downloading and is being checked
-    CheckingUP,
-    /// Torrent is forced to uploading and ignore queue limit
-    ForcedUP,
-    /// Torrent is allocating disk space for download
-    Allocating,
-    /// Torrent is being downloaded and data is being transferred
-    Downloading,
-    /// Torrent has just started downloading and is fetching metadata
-    MetaDL,
-    /// Torrent is paused and has NOT finished downloading
-    PausedDL,
-    /// Queuing is enabled and torrent is queued for download
-    QueuedDL,
-    /// Torrent is being downloaded, but no connection were made
-    StalledDL,
-    /// Same as checkingUP, but torrent has NOT finished downloading
-    CheckingDL,
-    /// Torrent is forced to downloading to ignore queue limit
-    ForceDL,
-    /// Checking resume data on qBt startup
-    CheckingResumeData,
-    /// Torrent is moving to another location
-    Moving,
-    /// Unknown status
-    Unknown,
+#[cfg(feature = "chrono")]
+impl Torrent {
+    /// Time when the torrent was added to the client
+    pub fn added_on_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.added_on)
+    }
+
+    /// Time when the torrent completed, None if not completed yet
+    pub fn completion_on_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.completion_on)
+    }
+
+    /// Time when this torrent was last seen complete, None if never
+    pub fn seen_complete_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.seen_complete)
+    }
+
+    /// Last time when a chunk was downloaded/uploaded, None if never
+    pub fn last_activity_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.last_activity)
+    }
+}
+
+/// Convert a Unix timestamp to a UTC time. qBittorrent reports unset times as -1 or 0.
+#[cfg(feature = "chrono")]
+fn timestamp(secs: i64) -> Option<DateTime<Utc>> {
+    if secs <= 0 {
+        return None;
+    }
+    DateTime::from_timestamp(secs, 0)
+}
+
+impl Torrent {
+    /// State of the torrent, State::Other for the states unknown to this crate
+    pub fn state(&self) -> State {
+        State::from(self.state.as_str())
+    }
+
+    /// ID of the torrent to pass to the other torrent methods
+    ///
+    /// hash if present, otherwise the SHA1 info hash, otherwise the SHA256 info hash of a v2 only
+    /// torrent truncated to 40 characters as qBittorrent does.
+    pub fn id(&self) -> Option<&str> {
+        [&self.hash, &self.infohash_v1, &self.infohash_v2]
+            .into_iter()
+            .flatten()
+            .map(|hash| torrent_id(hash))
+            .find(|hash| !hash.is_empty())
+    }
+
+    /// True if the torrent has a SHA256 (v2) info hash, i.e. is a v2 or hybrid torrent
+    pub fn is_v2(&self) -> bool {
+        self.infohash_v2
+            .as_deref()
+            .is_some_and(|hash| !hash.is_empty())
+    }
+}
+
+/// True if hash is a SHA1 (40 hex characters) or SHA256 (64 hex characters) info hash
+pub fn is_info_hash(hash: &str) -> bool {
+    matches!(hash.len(), 40 | 64) && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Torrent ID used by the API for an info hash
+///
+/// qBittorrent identifies v2 only torrents by their SHA256 info hash truncated to 40
+/// characters, so 64 characters hashes are truncated. Other values are returned as is.
+pub fn torrent_id(hash: &str) -> &str {
+    if hash.len() == 64 && is_info_hash(hash) {
+        &hash[..40]
+    } else {
+        hash
+    }
+}
+
+str_enum! {
+    pub enum State {
+        /// Some error occurred, applies to paused torrents
+        Error = "error",
+        /// Torrent data files is missing
+        MissingFiles = "missingFiles",
+        /// Torrent is being seeded and data is being transferred
+        Uploading = "uploading",
+        /// Torrent is paused and has finished downloading
+        PausedUP = "pausedUP",
+        /// pausedUP since qBittorrent 5.0
+        StoppedUP = "stoppedUP",
+        /// Queuing is enabled and torrent is queued for upload
+        QueuedUP = "queuedUP",
+        /// Torrent is being seeded, but no connection were made
+        StalledUP = "stalledUP",
+        /// Torrent has finished downloading and is being checked
+        CheckingUP = "checkingUP",
+        /// Torrent is forced to uploading and ignore queue limit
+        ForcedUP = "forcedUP",
+        /// Torrent is allocating disk space for download
+        Allocating = "allocating",
+        /// Torrent is being downloaded and data is being transferred
+        Downloading = "downloading",
+        /// Torrent has just started downloading and is fetching metadata
+        MetaDL = "metaDL",
+        /// Same as metaDL, but the torrent is forced to download ignoring the queue limit
+        ForcedMetaDL = "forcedMetaDL",
+        /// Torrent is paused and has NOT finished downloading
+        PausedDL = "pausedDL",
+        /// pausedDL since qBittorrent 5.0
+        StoppedDL = "stoppedDL",
+        /// Queuing is enabled and torrent is queued for download
+        QueuedDL = "queuedDL",
+        /// Torrent is being downloaded, but no connection were made
+        StalledDL = "stalledDL",
+        /// Same as checkingUP, but torrent has NOT finished downloading
+        CheckingDL = "checkingDL",
+        /// Torrent is forced to downloading to ignore queue limit
+        ForceDL = "forcedDL",
+        /// Checking resume data on qBt startup
+        CheckingResumeData = "checkingResumeData",
+        /// Torrent is moving to another location
+        Moving = "moving",
+        /// Unknown status
+        Unknown = "unknown",
+    }
+}
+
+impl State {
+    /// error or missingFiles
+    pub fn is_errored(&self) -> bool {
+        matches!(self, State::Error | State::MissingFiles)
+    }
+
+    /// Fetching the metadata, metaDL or forcedMetaDL
+    pub fn is_fetching_metadata(&self) -> bool {
+        matches!(self, State::MetaDL | State::ForcedMetaDL)
+    }
+
+    /// Checking the data, checkingUP, checkingDL or checkingResumeData
+    pub fn is_checking(&self) -> bool {
+        matches!(
+            self,
+            State::CheckingUP | State::CheckingDL | State::CheckingResumeData
+        )
+    }
+
+    /// Paused or stopped, before or after finishing the download
+    pub fn is_stopped(&self) -> bool {
+        matches!(
+            self,
+            State::PausedUP | State::StoppedUP | State::PausedDL | State::StoppedDL
+        )
+    }
+
+    /// Downloading data or metadata, forced or not
+    pub fn is_downloading(&self) -> bool {
+        matches!(
+            self,
+            State::Downloading | State::ForceDL | State::MetaDL | State::ForcedMetaDL
+        )
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TorrentProperties {
     /// Torrent save path
     pub save_path: String,
     /// Torrent creation date (Unix timestamp)
+    #[serde(deserialize_with = "lenient::i64")]
     pub creation_date: i64,
     /// Torrent piece size (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub piece_size: i64,
     /// Torrent comment
     pub comment: String,
     /// Total data wasted for torrent (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub total_wasted: i64,
     /// Total data uploaded for torrent (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub total_uploaded: i64,
     /// Total data uploaded this session (bytes)
-    pub total_uploaded_session: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_uploaded_session: Option<i64>,
     /// Total data downloaded for torrent (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub total_downloaded: i64,
     /// Total data downloaded this session (bytes)
-    pub total_downloaded_session: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub total_downloaded_session: Option<i64>,
     /// Torrent upload limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_limit: i64,
     /// Torrent download limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_limit: i64,
     /// Torrent elapsed time (seconds)
+    #[serde(deserialize_with = "lenient::i64")]
     pub time_elapsed: i64,
     /// Torrent elapsed time while complete (seconds)
-    pub seeding_time: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub seeding_time: Option<i64>,
     /// Torrent connection count
+    #[serde(deserialize_with = "lenient::i64")]
     pub nb_connections: i64,
     /// Torrent connection count limit
+    #[serde(deserialize_with = "lenient::i64")]
     pub nb_connections_limit: i64,
     /// Torrent share ratio
+    #[serde(deserialize_with = "lenient::f64")]
     pub share_ratio: f64,
     /// When this torrent was added (unix timestamp)
+    #[serde(deserialize_with = "lenient::i64")]
     pub addition_date: i64,
     /// Torrent completion date (unix timestamp)
+    #[serde(deserialize_with = "lenient::i64")]
     pub completion_date: i64,
     /// Torrent creator
     pub created_by: String,
     /// Torrent average download speed (bytes/second)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_speed_avg: i64,
     /// Torrent download speed (bytes/second)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_speed: i64,
     /// Torrent ETA (seconds)
+    #[serde(deserialize_with = "lenient::i64")]
     pub eta: i64,
     /// Last seen complete date (unix timestamp)
+    #[serde(deserialize_with = "lenient::i64")]
     pub last_seen: i64,
     /// Number of peers connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub peers: i64,
     /// Number of peers in the swarm
+    #[serde(deserialize_with = "lenient::i64")]
     pub peers_total: i64,
     /// Number of pieces owned
+    #[serde(deserialize_with = "lenient::i64")]
     pub pieces_have: i64,
     /// Number of pieces of the torrent
+    #[serde(deserialize_with = "lenient::i64")]
     pub pieces_num: i64,
     /// Number of seconds until the next announce
-    pub reannounce: i64,
+    #[serde(default, deserialize_with = "lenient::option_i64")]
+    pub reannounce: Option<i64>,
     /// Number of seeds connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub seeds: i64,
     /// Number of seeds in the swarm
+    #[serde(deserialize_with = "lenient::i64")]
     pub seeds_total: i64,
     /// Torrent total size (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub total_size: i64,
     /// Torrent average upload speed (bytes/second)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_speed_avg: i64,
     /// Torrent upload speed (bytes/second)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_speed: i64,
+    /// Torrent name (qBittorrent 4.6+)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Torrent hash (qBittorrent 4.6+)
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// Torrent SHA1 info hash (qBittorrent 4.4+)
+    #[serde(default)]
+    pub infohash_v1: Option<String>,
+    /// Torrent SHA256 info hash (qBittorrent 4.4+)
+    #[serde(default)]
+    pub infohash_v2: Option<String>,
+    /// Path where this torrent's data is stored while incomplete (qBittorrent 4.4+)
+    #[serde(default)]
+    pub download_path: Option<String>,
+    /// True if the torrent is private (qBittorrent 4.6+, is_private since 5.0)
+    #[serde(default, rename = "isPrivate", alias = "is_private")]
+    pub is_private: Option<bool>,
+    /// True if the metadata of the torrent is received (qBittorrent 5.0+)
+    #[serde(default)]
+    pub has_metadata: Option<bool>,
+    /// Torrent popularity, the ratio per month (qBittorrent 5.0+)
+    #[serde(default, deserialize_with = "lenient::option_f64")]
+    pub popularity: Option<f64>,
+}
+
+#[cfg(feature = "chrono")]
+impl TorrentProperties {
+    /// Torrent creation date, None if unknown
+    pub fn creation_date_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.creation_date)
+    }
+
+    /// When this torrent was added
+    pub fn addition_date_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.addition_date)
+    }
+
+    /// Torrent completion date, None if not completed yet
+    pub fn completion_date_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.completion_date)
+    }
+
+    /// Last seen complete date, None if never
+    pub fn last_seen_time(&self) -> Option<DateTime<Utc>> {
+        timestamp(self.last_seen)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Tracker {
     /// Tracker url
     pub url: String,
     /// Tracker status. See the table below for possible values
-    pub status: i64,
-    /// Tracker priority tier. Lower tier trackers are tried before higher tiers. Tier numbers are valid when >= 0, < 0 is used as placeholder when tier does not exist for special entries (such as DHT).
-    pub tier: Tier,
+    pub status: TrackerStatus,
+    /// Tracker priority tier. Lower tier trackers are tried before higher tiers. None for special entries (such as DHT), reported as an empty string or a negative tier.
+    #[serde(deserialize_with = "deserialize_tier")]
+    pub tier: Option<i64>,
     /// Number of peers for current torrent, as reported by the tracker
     pub num_peers: i64,
     /// Number of seeds for current torrent, asreported by the tracker
@@ -260,35 +804,80 @@ pub struct Tracker {
     pub msg: String,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum TrackerStatus {
-    /// Tracker is disabled (used for DHT, PeX, and LSD)
-    Disabled = 0,
-    /// Tracker has not been contacted yet
-    NotContacted = 1,
-    /// Tracker has been contacted and is working
-    Working = 2,
-    /// Tracker is updating
-    Updating = 3,
-    /// Tracker has been contacted, but it is not working (or doesn't send proper replies)
-    NotWorking = 4,
+impl Tracker {
+    /// Tracker URL or special entry
+    pub fn entry(&self) -> TrackerEntry {
+        match self.url.as_str() {
+            "** [DHT] **" => TrackerEntry::Special(SpecialTracker::Dht),
+            "** [PeX] **" => TrackerEntry::Special(SpecialTracker::Pex),
+            "** [LSD] **" => TrackerEntry::Special(SpecialTracker::Lsd),
+            url => TrackerEntry::Url(url.to_string()),
+        }
+    }
+
+    /// True for the DHT, PeX and LSD entries
+    pub fn is_special(&self) -> bool {
+        matches!(self.entry(), TrackerEntry::Special(_))
+    }
+}
+
+/// Entry of the tracker list of a torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackerEntry {
+    /// Peer source listed with the trackers
+    Special(SpecialTracker),
+    /// Tracker URL
+    Url(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialTracker {
+    Dht,
+    Pex,
+    Lsd,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum Tier {
-    None(String),
-    Priority(i64),
+int_enum! {
+    pub enum TrackerStatus {
+        /// Tracker is disabled (used for DHT, PeX, and LSD)
+        Disabled = 0,
+        /// Tracker has not been contacted yet
+        NotContacted = 1,
+        /// Tracker has been contacted and is working
+        Working = 2,
+        /// Tracker is updating
+        Updating = 3,
+        /// Tracker has been contacted, but it is not working (or doesn't send proper replies)
+        NotWorking = 4,
+    }
+}
+
+/// Tier is a number for trackers, and an empty string or a negative number for special entries
+fn deserialize_tier<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawTier {
+        Number(i64),
+        Text(String),
+    }
+
+    Ok(match RawTier::deserialize(deserializer)? {
+        RawTier::Number(tier) if tier >= 0 => Some(tier),
+        RawTier::Number(_) => None,
+        RawTier::Text(tier) => tier.parse().ok().filter(|tier: &i64| *tier >= 0),
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Webseed {
     /// URL of the web seed
     pub url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct File {
     /// File index
     pub index: Option<i64>,
@@ -308,36 +897,43 @@ pub struct File {
     pub availability: f64,
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum Priority {
-    /// Do not download
-    Skip = 0,
-    /// Normal priority
-    Normal = 1,
-    /// High priority
-    High = 6,
-    /// Maximal priority
-    Maximum = 7,
+int_enum! {
+    pub enum Priority {
+        /// Do not download
+        Skip = 0,
+        /// Normal priority
+        Normal = 1,
+        /// High priority
+        High = 6,
+        /// Maximal priority
+        Maximum = 7,
+    }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum PieceState {
-    /// Not downloaded yet
-    NotDownloadedYet = 0,
-    /// Now downloading
-    NowDownloading = 1,
-    /// Already downloaded
-    AlreadyDownloaded = 2,
+int_enum! {
+    pub enum PieceState {
+        /// Not downloaded yet
+        NotDownloadedYet = 0,
+        /// Now downloading
+        NowDownloading = 1,
+        /// Already downloaded
+        AlreadyDownloaded = 2,
+    }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AddTorrent {
     /// URLs separated with newlines
     pub urls: String,
-    /// Raw data of torrent file. torrents can be presented multiple times.
+    /// URLs, added after urls. Joined with newlines when sent, so URLs can be added without
+    /// knowing the format of urls
+    #[serde(skip)]
+    pub url_list: Vec<String>,
+    /// Magnet links, added after urls and url_list
+    #[serde(skip)]
+    pub magnets: Vec<Magnet>,
+    /// Raw data of a .torrent file, uploaded as multipart/form-data
     pub torrents: Vec<u8>,
     /// Download folder
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -358,10 +954,21 @@ pub struct AddTorrent {
     /// Add torrents in the paused state. Possible values are true, false (default)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub paused: Option<String>,
-    /// Create the root folder. Possible values are true, false, unset (default)
+    /// Add torrents in the stopped state, replacing paused since Web API v2.11. Possible
+    /// values are true, false (default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped: Option<String>,
+    /// Create the root folder. Possible values are true, false, unset (default). Replaced by
+    /// contentLayout since Web API v2.7.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "root_folder")]
     pub root_folder: Option<String>,
+    /// Content layout of the torrent (Web API v2.7+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_layout: Option<ContentLayout>,
+    /// Stop the torrent when the condition is reached (Web API v2.8.18+)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_condition: Option<StopCondition>,
     /// Rename torrent
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rename: Option<String>,
@@ -386,6 +993,147 @@ pub struct AddTorrent {
     /// Prioritize download first last piece. Possible values are true, false (default)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_last_piece_prio: Option<String>,
+    /// Save path template expanded when the torrent is added, see
+    /// AddTorrent::savepath_template
+    #[serde(skip)]
+    pub savepath_template: Option<String>,
+}
+
+impl AddTorrent {
+    /// Add the torrents of URLs (http://, https://, magnet: or bc://bt/ links)
+    pub fn from_urls<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        AddTorrent {
+            url_list: urls.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// URLs to add: the non-empty lines of urls, then url_list, then the magnets
+    pub fn all_urls(&self) -> Vec<String> {
+        self.urls
+            .lines()
+            .chain(self.url_list.iter().map(String::as_str))
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .chain(self.magnets.iter().map(Magnet::to_string))
+            .collect()
+    }
+
+    /// Adapt the layout fields to the given Web API version (as returned by get_api_version)
+    ///
+    /// Versions before v2.7 get root_folder instead of contentLayout, and stopCondition is
+    /// dropped before v2.8.18. Newer versions get contentLayout instead of root_folder.
+    pub fn for_api_version(mut self, version: &str) -> Self {
+        if api_version_at_least(version, &[2, 7]) {
+            if self.content_layout.is_none() {
+                self.content_layout = match self.root_folder.as_deref() {
+                    Some("true") => Some(ContentLayout::Subfolder),
+                    Some("false") => Some(ContentLayout::NoSubfolder),
+                    _ => None,
+                };
+            }
+            self.root_folder = None;
+        } else if let Some(layout) = self.content_layout.take() {
+            self.root_folder = match layout {
+                ContentLayout::Original | ContentLayout::Other(_) => None,
+                ContentLayout::Subfolder => Some("true".to_string()),
+                ContentLayout::NoSubfolder => Some("false".to_string()),
+            };
+        }
+        if self.stop_condition.is_some() && !api_version_at_least(version, &[2, 8, 18]) {
+            ::log::debug!("stopCondition is not supported by Web API {version}");
+            self.stop_condition = None;
+        }
+        self
+    }
+
+    /// multipart/form-data body of the add request, with torrents as a .torrent file part
+    fn multipart(&self) -> Multipart {
+        let mut fields = json!(self);
+        if let Some(fields) = fields.as_object_mut() {
+            fields.remove("torrents");
+            if fields.get("urls").is_some_and(|urls| urls == "") {
+                fields.remove("urls");
+            }
+        }
+        let mut form = Multipart::new();
+        form.json_fields(&fields);
+        form.file(
+            "torrents",
+            "upload.torrent",
+            "application/x-bittorrent",
+            &self.torrents,
+        );
+        form
+    }
+}
+
+/// Torrent added by Client::add_torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedTorrent {
+    /// ID of the torrent to pass to the other torrent methods
+    pub hash: String,
+    /// Torrent name
+    pub name: String,
+}
+
+const ADDED_TORRENT_ATTEMPTS: usize = 20;
+const ADDED_TORRENT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Most recently added torrents, to find a torrent added without a known info hash
+fn newest_torrents() -> GetTorrentList {
+    GetTorrentList::builder()
+        .sort(SortKey::AddedOn)
+        .reverse(true)
+        .limit(50)
+        .build()
+}
+
+/// Torrent added by Client::add_stopped
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StoppedAdd {
+    /// The server has the stopped state of Web API v2.11+ instead of paused
+    pub(crate) stopped_state: bool,
+    /// The add values asked for the torrent to stay stopped
+    pub(crate) keep_stopped: bool,
+}
+
+pub(crate) fn api_version_at_least(version: &str, min: &[u32]) -> bool {
+    let version: Vec<u32> = version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    version.as_slice() >= min
+}
+
+str_enum! {
+    /// Layout of the torrent content in the save path
+    pub enum ContentLayout {
+        /// Keep the layout of the torrent
+        Original = "Original",
+        /// Always create a subfolder
+        Subfolder = "Subfolder",
+        /// Never create a subfolder
+        NoSubfolder = "NoSubfolder",
+    }
+}
+
+str_enum! {
+    /// Condition at which a newly added torrent is stopped
+    pub enum StopCondition {
+        None = "None",
+        /// Stop once the metadata is received
+        MetadataReceived = "MetadataReceived",
+        /// Stop once the files are checked
+        FilesChecked = "FilesChecked",
+    }
 }
 
 impl Client {
@@ -416,10 +1164,135 @@ impl Client {
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
+    }
+
+    /// Get torrent count (Web API v2.11.1+)
+    ///
+    /// Name: count
+    ///
+    /// Parameters:
+    ///
+    /// None
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// The number of torrents, as text. Older servers answer 404, returned as
+    /// Error::UnsupportedEndpoint.
+    ///
+    pub async fn torrent_count(&mut self) -> Result<i64, Error> {
+        let request = ApiRequest {
+            method: Method::TorrentsCount,
+            arguments: None,
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(String::from_utf8(response_body(&response)?.to_vec())?
+                .trim()
+                .parse()?),
+            404 => Err(self.in_context(Error::UnsupportedEndpoint)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Whether qBittorrent has the torrent
+    ///
+    /// Requests torrents/info filtered on hash with a limit of 1, so only this torrent is
+    /// transferred. The returned torrent must have the hash, so "all", an empty hash or several
+    /// hashes separated by | are never reported as existing.
+    ///
+    pub async fn torrent_exists(&mut self, hash: &str) -> Result<bool, Error> {
+        let id = torrent_id(hash);
+        let list = GetTorrentList::builder()
+            .hashes(vec![hash])
+            .limit(1)
+            .build();
+        Ok(self
+            .get_torrent_list(list)
+            .await?
+            .first()
+            .and_then(Torrent::id)
+            .is_some_and(|found| found.eq_ignore_ascii_case(id)))
+    }
+
+    /// Get torrent list as a stream of torrents
+    ///
+    /// Same as get_torrent_list, but the torrents are deserialized one at a time as the stream
+    /// is polled instead of into one Vec. The whole response body is still read into memory
+    /// before the first torrent, only the parsing is incremental. Request errors are yielded
+    /// as the only item, a torrent that fails to deserialize ends the stream.
+    ///
+    pub fn get_torrent_list_stream(
+        &mut self,
+        values: GetTorrentList,
+    ) -> BoxStream<'_, Result<Torrent, Error>> {
+        let list = async move {
+            let request = ApiRequest {
+                method: Method::TorrentsInfo,
+                arguments: Some(Arguments::Json(json!(values))),
+            };
+            let response = self.send_request(&request).await?;
+            self.check_default_status(&response, ())?;
+            JsonArray::new(request.method, &response).map(stream::iter)
+        };
+        stream::once(list).try_flatten().boxed()
+    }
+
+    /// Get torrent list page by page as a stream of torrents
+    ///
+    /// Requests page_size torrents at a time, from args.offset (not negative) on, and stops
+    /// after a short page or once args.limit torrents were yielded. The next page is only
+    /// requested when the stream is polled past the current one, so at most a page is in
+    /// memory. Torrents added or removed meanwhile shift the pages, sort by a stable key such
+    /// as added_on to limit it. Errors end the stream.
+    ///
+    pub fn iter_torrents(
+        &mut self,
+        args: GetTorrentList,
+        page_size: i64,
+    ) -> BoxStream<'_, Result<Torrent, Error>> {
+        let page_size = page_size.max(1);
+        let offset = args.offset.unwrap_or_default();
+        let remaining = args.limit;
+        let pages = stream::unfold(
+            Some((self, args, offset, remaining)),
+            move |state| async move {
+                let (client, mut args, offset, remaining) = state?;
+                let limit = remaining.map_or(page_size, |remaining| remaining.min(page_size));
+                if limit <= 0 {
+                    return None;
+                }
+                args.offset = Some(offset);
+                args.limit = Some(limit);
+                let request = ApiRequest {
+                    method: Method::TorrentsInfo,
+                    arguments: Some(Arguments::Json(json!(args))),
+                };
+                let page: Result<Vec<Torrent>, Error> = async {
+                    let response = client.send_request(&request).await?;
+                    client.check_default_status(&response, decode(request.method, &response)?)
+                }
+                .await;
+                match page {
+                    Ok(page) => {
+                        let len = page.len() as i64;
+                        let next = (len == limit).then(|| {
+                            let remaining = remaining.map(|remaining| remaining - len);
+                            (client, args, offset + len, remaining)
+                        });
+                        Some((Ok(page), next))
+                    }
+                    Err(err) => Some((Err(err), None)),
+                }
+            },
+        );
+        pages
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
     }
 
     /// Get torrent generic properties
@@ -438,23 +1311,32 @@ impl Client {
     /// 200 All other scenarios- see JSON below
     /// The response is:
     ///
-    /// empty, if the torrent hash is invalid
+    /// empty, if the torrent hash is invalid: None is returned
     /// otherwise, TorrentProperties
     ///
+    /// hash can be an InfoHash, e.g. `&torrent.info_hash()?`.
+    ///
     pub async fn get_torrent_properties(
         &mut self,
-        hash: String,
+        hash: &str,
     ) -> Result<Option<TorrentProperties>, Error> {
-        let arguments = Arguments::Form(format!("hash={hash}"));
+        let arguments = Arguments::Form(format!("hash={}", torrent_id(hash)));
         let request = ApiRequest {
             method: Method::Properties,
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
         match response.status_code().as_u16() {
-            200 => Ok(serde_json::from_reader(response.body().as_ref())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
+            200 => {
+                let body = response_body(&response)?;
+                if body.iter().all(u8::is_ascii_whitespace) {
+                    Ok(None)
+                } else {
+                    Ok(Some(decode_body(request.method, &body)?))
+                }
+            }
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
         }
     }
 
@@ -474,16 +1356,16 @@ impl Client {
     /// 200 All other scenarios- see JSON below
     ///
     pub async fn get_torrent_trackers(&mut self, hash: &str) -> Result<Vec<Tracker>, Error> {
-        let arguments = Arguments::Form(format!("hash={hash}"));
+        let arguments = Arguments::Form(format!("hash={}", torrent_id(hash)));
         let request = ApiRequest {
             method: Method::Trackers,
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
         match response.status_code().as_u16() {
-            200 => Ok(serde_json::from_reader(response.body().as_ref())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
+            200 => Ok(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
         }
     }
 
@@ -503,127 +1385,287 @@ impl Client {
     /// 200 All other scenarios- see JSON below
     ///
     /// Webseed
-    pub async fn get_torrent_seeds(&mut self, hash: &str) -> Result<Vec<Webseed>, Error> {
-        let arguments = Arguments::Form(format!("hash={hash}"));
+    ///
+    /// hash can be an InfoHash, e.g. `&torrent.info_hash()?`.
+    ///
+    pub async fn get_torrent_webseeds(&mut self, hash: &str) -> Result<Vec<Webseed>, Error> {
+        let arguments = Arguments::Form(format!("hash={}", torrent_id(hash)));
         let request = ApiRequest {
             method: Method::Webseeds,
             arguments: Some(arguments),
         };
         let response = self.send_request(&request).await?;
         match response.status_code().as_u16() {
-            200 => Ok(serde_json::from_reader(response.body().as_ref())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
+            200 => Ok(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
         }
     }
 
-    /// Get torrent contents
+    /// Get torrent web seeds
+    #[deprecated(note = "renamed get_torrent_webseeds")]
+    pub async fn get_torrent_seeds(&mut self, hash: &str) -> Result<Vec<Webseed>, Error> {
+        self.get_torrent_webseeds(hash).await
+    }
+
+    /// Add web seeds to torrent (qBittorrent 5.x)
     /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: files
+    /// Name: addWebSeeds
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hash string The hash of the torrent you want to get the contents of
-    /// indexes optional since 2.8.2 string The indexes of the files you want to retrieve. indexes can contain multiple values separated by |.
+    /// hash string The hash of the torrent
+    /// urls string The URLs of the web seeds to add, separated by |
+    ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
+    /// 400 At least one URL is not valid
     /// 404 Torrent hash was not found
-    /// 200 All other scenarios- see JSON below
-    /// The response is:
+    /// 200 All other scenarios
     ///
-    /// empty, if the torrent hash is invalid
-    /// otherwise, Vec<File>
+    /// Web API before 2.10.3 doesn't have the endpoint, Error::UnsupportedEndpoint is returned
+    /// without sending the request.
     ///
-    pub async fn get_torrent_contents(
-        &mut self,
-        hash: &str,
-        indexes: &str,
-    ) -> Result<Vec<File>, Error> {
-        let arguments = Arguments::Form(format!("hash={hash}&indexes={indexes}"));
-        let request = ApiRequest {
-            method: Method::Files,
-            arguments: Some(arguments),
-        };
-        let response = self.send_request(&request).await?;
-        match dbg!(response.status_code().as_u16()) {
-            200 => Ok(serde_json::from_reader(response.body().as_ref())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
-        }
+    pub async fn add_webseeds(&mut self, hash: &str, urls: &[&str]) -> Result<(), Error> {
+        self.change_webseeds(Method::AddWebSeeds, hash, urls).await
     }
 
-    /// Get torrent pieces' states
+    /// Replace a web seed of torrent (qBittorrent 5.x)
     /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: pieceStates
+    /// Name: editWebSeed
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hash string The hash of the torrent you want to get the pieces' states of
+    /// hash string The hash of the torrent
+    /// origUrl string The web seed URL to edit
+    /// newUrl string The new URL to replace the origUrl
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
+    /// 400 newUrl is not a valid URL
     /// 404 Torrent hash was not found
-    /// 200 All other scenarios- see JSON below
-    /// The response is:
+    /// 409 origUrl was not found
+    /// 200 All other scenarios
     ///
-    /// empty, if the torrent hash is invalid
-    /// otherwise, Vec<PieceState>
+    /// Web API before 2.10.3 doesn't have the endpoint, Error::UnsupportedEndpoint is returned
+    /// without sending the request.
     ///
-    pub async fn get_torrent_states(&mut self, hash: &str) -> Result<Vec<PieceState>, Error> {
+    pub async fn edit_webseed(
+        &mut self,
+        hash: &str,
+        orig_url: &str,
+        new_url: &str,
+    ) -> Result<(), Error> {
+        self.require_webseed_endpoints().await?;
         let request = ApiRequest {
-            method: Method::PieceStates,
-            arguments: Some(Arguments::Form(format!("hash={hash}"))),
+            method: Method::EditWebSeed,
+            arguments: Some(Arguments::Form(format!(
+                "hash={}&origUrl={}&newUrl={}",
+                torrent_id(hash),
+                encode(orig_url),
+                encode(new_url)
+            ))),
         };
         let response = self.send_request(&request).await?;
-        match dbg!(response.status_code().as_u16()) {
-            200 => Ok(serde_json::from_reader(response.body().as_ref())?),
-            404 => Err(Error::NoTorrentHash),
-            _ => Err(Error::WrongStatusCode),
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::InvalidWebseedUrl)),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            409 => Err(self.in_context(Error::NoWebseed)),
+            _ => Err(self.status_error(&response)),
         }
     }
 
-    /// Get torrent pieces' hashes
+    /// Remove web seeds from torrent (qBittorrent 5.x)
     /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: pieceHashes
+    /// Name: removeWebSeeds
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hash string The hash of the torrent you want to get the pieces' hashes of
+    /// hash string The hash of the torrent
+    /// urls string The URLs of the web seeds to remove, separated by |
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
+    /// 400 At least one URL is not valid
     /// 404 Torrent hash was not found
-    /// 200 All other scenarios- see JSON below
-    /// The response is:
+    /// 200 All other scenarios
     ///
-    /// empty, if the torrent hash is invalid
-    /// otherwise, Vec<String>.
+    /// Web API before 2.10.3 doesn't have the endpoint, Error::UnsupportedEndpoint is returned
+    /// without sending the request.
     ///
-    pub async fn get_torrent_hashes(&mut self, hash: &str) -> Result<Vec<String>, Error> {
+    pub async fn remove_webseeds(&mut self, hash: &str, urls: &[&str]) -> Result<(), Error> {
+        self.change_webseeds(Method::RemoveWebSeeds, hash, urls)
+            .await
+    }
+
+    /// The web seed endpoints answer 404 for an unknown hash, so an older server has to be
+    /// detected from its version rather than the status
+    async fn require_webseed_endpoints(&mut self) -> Result<(), Error> {
+        if api_version_at_least(&self.get_api_version().await?, &[2, 10, 3]) {
+            Ok(())
+        } else {
+            Err(self.in_context(Error::UnsupportedEndpoint))
+        }
+    }
+
+    async fn change_webseeds(
+        &mut self,
+        method: Method,
+        hash: &str,
+        urls: &[&str],
+    ) -> Result<(), Error> {
+        self.require_webseed_endpoints().await?;
         let request = ApiRequest {
-            method: Method::PieceHashes,
-            arguments: Some(Arguments::Form(format!("hash={hash}"))),
+            method,
+            arguments: Some(Arguments::Form(format!(
+                "hash={}&urls={}",
+                torrent_id(hash),
+                join_urls(urls)
+            ))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::InvalidWebseedUrl)),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
+        }
     }
 
-    /// Pause torrents
-    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    /// Get torrent contents
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: pause
+    /// Name: files
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hash string The hash of the torrent you want to get the contents of
+    /// indexes optional since 2.8.2 string The indexes of the files you want to retrieve. indexes can contain multiple values separated by |. Empty to retrieve all files.
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Torrent hash was not found
+    /// 200 All other scenarios- see JSON below
+    /// The response is:
+    ///
+    /// empty, if the torrent hash is invalid
+    /// otherwise, Vec<File>
+    ///
+    pub async fn get_torrent_contents(
+        &mut self,
+        hash: &str,
+        indexes: &str,
+    ) -> Result<Vec<File>, Error> {
+        let arguments = if indexes.is_empty() {
+            Arguments::Form(format!("hash={}", torrent_id(hash)))
+        } else {
+            Arguments::Form(format!("hash={}&indexes={indexes}", torrent_id(hash)))
+        };
+        let request = ApiRequest {
+            method: Method::Files,
+            arguments: Some(arguments),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get torrent pieces' states
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: pieceStates
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hash string The hash of the torrent you want to get the pieces' states of
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Torrent hash was not found
+    /// 200 All other scenarios- see JSON below
+    /// The response is:
+    ///
+    /// empty, if the torrent hash is invalid
+    /// otherwise, Vec<PieceState>
+    ///
+    /// hash can be an InfoHash, e.g. `&torrent.info_hash()?`.
+    ///
+    pub async fn get_piece_states(&mut self, hash: &str) -> Result<Vec<PieceState>, Error> {
+        let request = ApiRequest {
+            method: Method::PieceStates,
+            arguments: Some(Arguments::Form(format!("hash={}", torrent_id(hash)))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(decode(request.method, &response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get torrent pieces' states
+    #[deprecated(note = "renamed get_piece_states")]
+    pub async fn get_torrent_states(&mut self, hash: &str) -> Result<Vec<PieceState>, Error> {
+        self.get_piece_states(hash).await
+    }
+
+    /// Get torrent pieces' hashes
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: pieceHashes
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hash string The hash of the torrent you want to get the pieces' hashes of
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 404 Torrent hash was not found
+    /// 200 All other scenarios- see JSON below
+    /// The response is:
+    ///
+    /// empty, if the torrent hash is invalid
+    /// otherwise, Vec<String>.
+    ///
+    /// hash can be an InfoHash, e.g. `&torrent.info_hash()?`.
+    ///
+    pub async fn get_piece_hashes(&mut self, hash: &str) -> Result<Vec<String>, Error> {
+        let request = ApiRequest {
+            method: Method::PieceHashes,
+            arguments: Some(Arguments::Form(format!("hash={}", torrent_id(hash)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, decode(request.method, &response)?)
+    }
+
+    /// Get torrent pieces' hashes
+    #[deprecated(note = "renamed get_piece_hashes")]
+    pub async fn get_torrent_hashes(&mut self, hash: &str) -> Result<Vec<String>, Error> {
+        self.get_piece_hashes(hash).await
+    }
+
+    /// Pause torrents
+    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    ///
+    /// Name: pause
     ///
     /// Parameters:
     ///
@@ -636,146 +1678,916 @@ impl Client {
     ///
     /// HTTP Status Code Scenario
     /// 200 All scenarios
-    pub async fn pause_torrent(&mut self, hashes: Vec<&str>) -> Result<(), Error> {
+    pub async fn pause_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
         let request = ApiRequest {
             method: Method::Pause,
-            arguments: Some(Arguments::Form(format!("hashes={}", hashes.join("|")))),
+            arguments: Some(Arguments::Form(format!("hashes={}", join_hashes(hashes)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Resume torrents
+    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    ///
+    /// Name: resume
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to resume. hashes can contain multiple hashes separated by |, to resume multiple torrents, or set to all, to resume all torrents.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn resume_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::Resume,
+            arguments: Some(Arguments::Form(format!("hashes={}", join_hashes(hashes)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Start torrents, replacing resume since Web API v2.11
+    ///
+    /// Name: start
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to start. hashes can contain multiple hashes separated by |, to start multiple torrents, or set to all, to start all torrents.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn start_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::Start,
+            arguments: Some(Arguments::Form(format!("hashes={}", join_hashes(hashes)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Add values stopped, with stopped on Web API v2.11+ and paused before
+    ///
+    /// Returns the added torrent and whether values asked for it to stay stopped, which the
+    /// caller passes to start_added.
+    pub(crate) async fn add_stopped(
+        &mut self,
+        values: AddTorrent,
+    ) -> Result<(AddedTorrent, StoppedAdd), Error> {
+        let stopped_state = api_version_at_least(&self.get_api_version().await?, &[2, 11]);
+        let keep_stopped = [&values.paused, &values.stopped]
+            .iter()
+            .any(|value| value.as_deref() == Some("true"));
+        let values = if stopped_state {
+            AddTorrent {
+                paused: None,
+                stopped: Some("true".to_string()),
+                ..values
+            }
+        } else {
+            AddTorrent {
+                paused: Some("true".to_string()),
+                stopped: None,
+                ..values
+            }
+        };
+        let added = self.add_torrent(values).await?;
+        let stopped = StoppedAdd {
+            stopped_state,
+            keep_stopped,
+        };
+        Ok((added, stopped))
+    }
+
+    /// Start a torrent added by add_stopped, with start on Web API v2.11+ and resume before
+    pub(crate) async fn start_added(
+        &mut self,
+        hash: &str,
+        stopped: StoppedAdd,
+    ) -> Result<(), Error> {
+        if stopped.stopped_state {
+            self.start_torrent(vec![hash]).await
+        } else {
+            self.resume_torrent(vec![hash]).await
+        }
+    }
+
+    /// Delete torrents
+    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    ///
+    /// Name: delete
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to delete. hashes can contain multiple hashes separated by |, to delete multiple torrents, or set to all, to delete all torrents.
+    /// deleteFiles If set to true, the downloaded data will also be deleted, otherwise has no effect.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn delete_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        delete_files: bool,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::Delete,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&deleteFiles={}",
+                join_hashes(hashes),
+                delete_files
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Recheck torrents
+    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    ///
+    /// Name: recheck
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to recheck. hashes can contain multiple hashes separated by |, to recheck multiple torrents, or set to all, to recheck all torrents.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn recheck_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::Recheck,
+            arguments: Some(Arguments::Form(format!("hashes={}", join_hashes(hashes)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Reannounce torrents
+    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    ///
+    /// Name: reannounce
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to reannounce. hashes can contain multiple hashes separated by |, to reannounce multiple torrents, or set to all, to reannounce all torrents.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn reannounce_torrent(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::Reannounce,
+            arguments: Some(Arguments::Form(format!("hashes={}", join_hashes(hashes)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Add new torrent
+    /// This method can add torrents from server local file or from URLs. http://, https://, magnet: and bc://bt/ links are supported.
+    ///
+    /// add
+    ///
+    /// Parameters:
+    /// AddTorrent
+    ///
+    /// If root_folder, content_layout or stop_condition is set, the Web API version is requested
+    /// first to send the fields supported by the server (see AddTorrent::for_api_version). The
+    /// client defaults (see ClientBuilder::default_category) fill the category, tags and
+    /// savepath left unset, then a savepath_template is expanded into savepath.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 415 Torrent file is not valid
+    /// 200 All other scenarios
+    ///
+    /// The added torrent is then looked up in the torrent list: by info hash for a single magnet
+    /// link or .torrent file (see AddTorrent::target_id), otherwise as the newest torrent not in
+    /// the list before the add, named rename if set. Returns Error::AddedTorrentNotFound if it
+    /// doesn't show up within a few seconds.
+    ///
+    pub async fn add_torrent(&mut self, mut values: AddTorrent) -> Result<AddedTorrent, Error> {
+        self.add_defaults.apply(&mut values);
+        if let Some(savepath) = values.expanded_savepath() {
+            values.savepath = Some(savepath);
+        }
+        if values.root_folder.is_some()
+            || values.content_layout.is_some()
+            || values.stop_condition.is_some()
+        {
+            let version = self.get_api_version().await?;
+            values = values.for_api_version(&version);
+        }
+        if !values.url_list.is_empty() || !values.magnets.is_empty() {
+            values.urls = values.all_urls().join("\n");
+            values.url_list.clear();
+            values.magnets.clear();
+        }
+        let target = values.target_id();
+        let known = match target {
+            Some(_) => Vec::new(),
+            None => self.newest_torrent_ids().await?,
+        };
+        // qBittorrent only reads .torrent files from multipart/form-data uploads
+        let arguments = if values.torrents.is_empty() {
+            Arguments::Json(json!(values))
+        } else {
+            Arguments::Multipart(values.multipart())
+        };
+        let request = ApiRequest {
+            method: Method::Add,
+            arguments: Some(arguments),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 if response_body(&response)?.as_ref().starts_with(b"Fails") => {
+                Err(Error::NoValidTorrent)
+            }
+            200 => {
+                self.find_added_torrent(target.as_deref(), &known, values.rename.as_deref())
+                    .await
+            }
+            415 => Err(self.in_context(Error::NoValidTorrent)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// IDs of the most recently added torrents
+    async fn newest_torrent_ids(&mut self) -> Result<Vec<String>, Error> {
+        let torrents = self.get_torrent_list(newest_torrents()).await?;
+        Ok(torrents
+            .iter()
+            .filter_map(|torrent| torrent.id().map(str::to_string))
+            .collect())
+    }
+
+    async fn find_added_torrent(
+        &mut self,
+        target: Option<&str>,
+        known: &[String],
+        rename: Option<&str>,
+    ) -> Result<AddedTorrent, Error> {
+        for attempt in 0..ADDED_TORRENT_ATTEMPTS {
+            if attempt > 0 {
+                sleep(ADDED_TORRENT_INTERVAL).await;
+            }
+            let torrents = match target {
+                Some(id) => {
+                    let list = GetTorrentList::builder().hashes(vec![id]).build();
+                    self.get_torrent_list(list).await?
+                }
+                None => self.get_torrent_list(newest_torrents()).await?,
+            };
+            let added = torrents.into_iter().find_map(|torrent| {
+                let id = torrent.id()?.to_string();
+                let is_new = target.is_some() || !known.contains(&id);
+                let named = rename.is_none() || rename == Some(torrent.name.as_str());
+                (is_new && named).then_some(AddedTorrent {
+                    hash: id,
+                    name: torrent.name,
+                })
+            });
+            if let Some(added) = added {
+                return Ok(added);
+            }
+        }
+        Err(Error::AddedTorrentNotFound)
+    }
+
+    /// Add torrent tags
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: addTags
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents you want to tag. hashes can contain multiple hashes separated by | or set to all
+    /// tags string The list of tags you want to add to passed torrents, separated by ,
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn add_tags(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        tags: Vec<&str>,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::AddTags,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&tags={}",
+                join_hashes(hashes),
+                join_tags(&tags)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Get all tags
+    ///
+    /// Name: tags
+    ///
+    /// Parameters:
+    ///
+    /// None
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// Vec<String>
+    ///
+    pub async fn get_tags(&mut self) -> Result<Vec<String>, Error> {
+        let request = ApiRequest {
+            method: Method::Tags,
+            arguments: None,
+        };
+        let body = self.cached_body(&request).await?;
+        decode_body(request.method, &body)
+    }
+
+    /// Create tags
+    ///
+    /// Name: createTags
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// tags string The list of tags you want to create, separated by ,
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn create_tags(&mut self, tags: Vec<&str>) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::CreateTags,
+            arguments: Some(Arguments::Form(format!("tags={}", join_tags(&tags)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Add peers
+    ///
+    /// Name: addPeers
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hash of the torrent, or multiple hashes separated by a pipe |
+    /// peers string The peer to add, or multiple peers separated by a pipe |. Each peer is a colon-separated host:port
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 400 None of the supplied peers are valid
+    /// 200 All other scenarios
+    ///
+    pub async fn add_peers(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        peers: &[PeerAddr],
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::AddPeers,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&peers={}",
+                join_hashes(hashes),
+                join_peers(peers)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::NoValidPeers)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Set torrent download limit
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: setDownloadLimit
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents. hashes can contain multiple hashes separated by | or set to all
+    /// limit integer The download speed limit in bytes per second you want to set
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn set_torrent_download_limit(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        limit: i64,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::SetTorrentDownloadLimit,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&limit={limit}",
+                join_hashes(hashes)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Set torrent upload limit
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: setUploadLimit
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents. hashes can contain multiple hashes separated by | or set to all
+    /// limit integer The upload speed limit in bytes per second you want to set
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn set_torrent_upload_limit(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        limit: i64,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::SetTorrentUploadLimit,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&limit={limit}",
+                join_hashes(hashes)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Set torrent category
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: setCategory
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents. hashes can contain multiple hashes separated by | or set to all
+    /// category string The torrent category you want to set
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 409 Category name does not exist
+    /// 200 All other scenarios
+    ///
+    pub async fn set_category(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        category: &str,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::SetCategory,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&category={}",
+                join_hashes(hashes),
+                encode(category)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            409 => Err(self.in_context(Error::NoCategory)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Get all categories
+    ///
+    /// Name: categories
+    ///
+    /// Parameters:
+    ///
+    /// None
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// Map of category name to Category
+    ///
+    pub async fn get_categories(&mut self) -> Result<HashMap<String, Category>, Error> {
+        let request = ApiRequest {
+            method: Method::Categories,
+            arguments: None,
+        };
+        let body = self.cached_body(&request).await?;
+        decode_body(request.method, &body)
+    }
+
+    /// Add new category
+    ///
+    /// Name: createCategory
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// category string The category you want to create
+    /// savePath string Save path of the torrents of the category, empty for the default
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 400 Category name is empty
+    /// 409 Category name is invalid
+    /// 200 All other scenarios
+    ///
+    pub async fn create_category(&mut self, category: &str, save_path: &str) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::CreateCategory,
+            arguments: Some(Arguments::Form(format!(
+                "category={}&savePath={}",
+                encode(category),
+                encode(save_path)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 | 409 => Err(self.in_context(Error::InvalidCategory)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Edit category
+    ///
+    /// Name: editCategory
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// category string The category you want to edit
+    /// savePath string New save path of the torrents of the category
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 400 Category name is empty
+    /// 409 Category editing failed
+    /// 200 All other scenarios
+    ///
+    pub async fn edit_category(&mut self, category: &str, save_path: &str) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::EditCategory,
+            arguments: Some(Arguments::Form(format!(
+                "category={}&savePath={}",
+                encode(category),
+                encode(save_path)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::InvalidCategory)),
+            409 => Err(self.in_context(Error::CategoryEditFailed)),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+
+    /// Remove categories
+    ///
+    /// Name: removeCategories
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// categories string Categories to remove, separated by \n
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    pub async fn remove_categories(&mut self, categories: Vec<&str>) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::RemoveCategories,
+            arguments: Some(Arguments::Form(format!(
+                "categories={}",
+                join_lines(&categories)
+            ))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
+    }
+
+    /// Set torrent location
+    /// Requires knowing the torrent hash. You can get it from torrent list.
+    ///
+    /// Name: setLocation
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// hashes string The hashes of the torrents. hashes can contain multiple hashes separated by | or set to all
+    /// location string The location to download the torrent to. If the location doesn't exist, the torrent's location is unchanged.
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 400 Save path is empty
+    /// 403 User does not have write access to directory
+    /// 409 Unable to create save path directory
+    /// 200 All other scenarios
+    ///
+    pub async fn set_location(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        location: &str,
+    ) -> Result<(), Error> {
+        let request = ApiRequest {
+            method: Method::SetLocation,
+            arguments: Some(Arguments::Form(format!(
+                "hashes={}&location={}",
+                join_hashes(hashes),
+                encode(location)
+            ))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::EmptyPath)),
+            403 => Err(self.in_context(Error::NoWriteAccess)),
+            409 => Err(self.in_context(Error::CannotCreateDirectory)),
+            _ => Err(self.status_error(&response)),
+        }
     }
 
-    /// Resume torrents
-    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    /// Set file priority
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: resume
+    /// Name: filePrio
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hashes string The hashes of the torrents you want to resume. hashes can contain multiple hashes separated by |, to resume multiple torrents, or set to all, to resume all torrents.
+    /// hash string The hash of the torrent
+    /// id string File ids, separated by |
+    /// priority number File priority to set
+    ///
+    /// id values are the index field returned by the torrent contents API.
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
-    /// 200 All scenarios
+    /// 400 Priority is invalid
+    /// 400 At least one file id is not a valid integer
+    /// 404 Torrent hash was not found
+    /// 409 Torrent metadata hasn't downloaded yet
+    /// 409 At least one file id was not found
+    /// 200 All other scenarios
     ///
-    pub async fn resume_torrent(&mut self, hashes: Vec<&str>) -> Result<(), Error> {
+    pub async fn set_file_priority(
+        &mut self,
+        hash: &str,
+        ids: &[i64],
+        priority: Priority,
+    ) -> Result<(), Error> {
+        let ids: Vec<String> = ids.iter().map(i64::to_string).collect();
         let request = ApiRequest {
-            method: Method::Resume,
-            arguments: Some(Arguments::Form(format!("hashes={}", hashes.join("|")))),
+            method: Method::FilePrio,
+            arguments: Some(Arguments::Form(format!(
+                "hash={}&id={}&priority={}",
+                torrent_id(hash),
+                ids.join("|"),
+                i64::from(priority)
+            ))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::InvalidFilePriority)),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            409 => Err(self.in_context(Error::NoMetadata)),
+            _ => Err(self.status_error(&response)),
+        }
     }
 
-    /// Delete torrents
-    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    /// Rename file
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: delete
+    /// Name: renameFile
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hashes string The hashes of the torrents you want to delete. hashes can contain multiple hashes separated by |, to delete multiple torrents, or set to all, to delete all torrents.
-    /// deleteFiles If set to true, the downloaded data will also be deleted, otherwise has no effect.
+    /// hash string The hash of the torrent
+    /// oldPath string The old path of the file
+    /// newPath string The new path to use for the file
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
-    /// 200 All scenarios
+    /// 400 Missing newPath parameter
+    /// 409 Invalid newPath or oldPath, or newPath already in use
+    /// 200 All other scenarios
     ///
-    pub async fn delete_torrent(
+    pub async fn rename_file(
         &mut self,
-        hashes: Vec<&str>,
-        delete_files: bool,
+        hash: &str,
+        old_path: &str,
+        new_path: &str,
     ) -> Result<(), Error> {
         let request = ApiRequest {
-            method: Method::Delete,
+            method: Method::RenameFile,
             arguments: Some(Arguments::Form(format!(
-                "hashes={}&deleteFiles={}",
-                hashes.join("|"),
-                delete_files
+                "hash={}&oldPath={}&newPath={}",
+                torrent_id(hash),
+                encode(old_path),
+                encode(new_path)
             ))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::EmptyPath)),
+            409 => Err(self.in_context(Error::RenameConflict)),
+            _ => Err(self.status_error(&response)),
+        }
     }
 
-    /// Recheck torrents
-    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    /// Set torrent save path (Web API v2.8.4+)
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: recheck
+    /// Name: setSavePath
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hashes string The hashes of the torrents you want to recheck. hashes can contain multiple hashes separated by |, to recheck multiple torrents, or set to all, to recheck all torrents.
+    /// id string The hashes of the torrents. id can contain multiple hashes separated by | or set to all
+    /// path string The save path of the complete torrents
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
-    /// 200 All scenarios
+    /// 400 Save path is empty
+    /// 403 User does not have write access to the directory
+    /// 409 Unable to create save path directory
+    /// 200 All other scenarios
     ///
-    pub async fn recheck_torrent(&mut self, hashes: Vec<&str>) -> Result<(), Error> {
-        let request = ApiRequest {
-            method: Method::Recheck,
-            arguments: Some(Arguments::Form(format!("hashes={}", hashes.join("|")))),
-        };
-        let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+    /// Older servers answer 404, returned as Error::UnsupportedEndpoint.
+    ///
+    pub async fn set_save_path(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        path: &str,
+    ) -> Result<(), Error> {
+        self.set_path(Method::SetSavePath, hashes, path).await
     }
 
-    /// Reannounce torrents
-    /// Requires knowing the torrent hashes. You can get it from torrent list.
+    /// Set torrent download path (Web API v2.8.4+)
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// Name: reannounce
+    /// Name: setDownloadPath
     ///
     /// Parameters:
     ///
     /// Parameter Type Description
-    /// hashes string The hashes of the torrents you want to reannounce. hashes can contain multiple hashes separated by |, to reannounce multiple torrents, or set to all, to reannounce all torrents.
+    /// id string The hashes of the torrents. id can contain multiple hashes separated by | or set to all
+    /// path string The path where the data of incomplete torrents is stored
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
-    /// 200 All scenarios
+    /// 400 Download path is empty
+    /// 403 User does not have write access to the directory
+    /// 409 Unable to create download path directory
+    /// 200 All other scenarios
     ///
-    pub async fn reannounce_torrent(&mut self, hashes: Vec<&str>) -> Result<(), Error> {
+    /// Older servers answer 404, returned as Error::UnsupportedEndpoint.
+    ///
+    pub async fn set_download_path(
+        &mut self,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        path: &str,
+    ) -> Result<(), Error> {
+        self.set_path(Method::SetDownloadPath, hashes, path).await
+    }
+
+    async fn set_path(
+        &mut self,
+        method: Method,
+        hashes: impl IntoIterator<Item = impl AsRef<str>>,
+        path: &str,
+    ) -> Result<(), Error> {
         let request = ApiRequest {
-            method: Method::Reannounce,
-            arguments: Some(Arguments::Form(format!("hashes={}", hashes.join("|")))),
+            method,
+            arguments: Some(Arguments::Form(format!(
+                "id={}&path={}",
+                join_hashes(hashes),
+                encode(path)
+            ))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        match response.status_code().as_u16() {
+            200 => Ok(()),
+            400 => Err(self.in_context(Error::EmptyPath)),
+            403 => Err(self.in_context(Error::NoWriteAccess)),
+            404 => Err(self.in_context(Error::UnsupportedEndpoint)),
+            409 => Err(self.in_context(Error::CannotCreateDirectory)),
+            _ => Err(self.status_error(&response)),
+        }
     }
 
-    /// Add new torrent
-    /// This method can add torrents from server local file or from URLs. http://, https://, magnet: and bc://bt/ links are supported.
+    /// Export torrent
+    /// Requires knowing the torrent hash. You can get it from torrent list.
     ///
-    /// add
+    /// Name: export
     ///
     /// Parameters:
-    /// AddTorrent
+    ///
+    /// Parameter Type Description
+    /// hash string The hash of the torrent you want to export
     ///
     /// Returns:
     ///
     /// HTTP Status Code Scenario
-    /// 415 Torrent file is not valid
+    /// 404 Torrent hash was not found
+    /// 409 Torrent metadata hasn't downloaded yet
     /// 200 All other scenarios
-    pub async fn add_torrent(&mut self, values: AddTorrent) -> Result<String, Error> {
+    ///
+    /// The response is the raw .torrent file.
+    ///
+    pub async fn export_torrent(&mut self, hash: &str) -> Result<Bytes, Error> {
         let request = ApiRequest {
-            method: Method::Add,
-            arguments: Some(Arguments::Json(json!(values))),
+            method: Method::Export,
+            arguments: Some(Arguments::Form(format!("hash={}", torrent_id(hash)))),
         };
         let response = self.send_request(&request).await?;
-        match dbg!(response.status_code().as_u16()) {
-            200 => Ok(String::from_utf8(response.body().to_vec())?),
-            415 => Err(Error::NoValidTorrent),
-            _ => Err(Error::WrongStatusCode),
+        match response.status_code().as_u16() {
+            200 => Ok(response_body(&response)?),
+            404 => Err(self.in_context(Error::NoTorrentHash)),
+            409 => Err(self.in_context(Error::NoMetadata)),
+            _ => Err(self.status_error(&response)),
         }
     }
 
+    /// Export torrent and write the .torrent file to path
+    pub async fn export_torrent_to_file<P: AsRef<Path>>(
+        &mut self,
+        hash: &str,
+        path: P,
+    ) -> Result<(), Error> {
+        let data = self.export_torrent(hash).await?;
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
     // / Add trackers to torrent
     // / Requires knowing the torrent hash. You can get it from torrent list.
     // /
@@ -833,21 +2645,6 @@ impl Client {
     // 404 Torrent hash was not found
     // 409 All urls were not found
     // 200 All other scenarios
-    // Add peers
-    // Name: addPeers
-
-    // Parameters:
-
-    // Parameter Type Description
-    //     /// The hash of the torrent, or multiple hashes separated by a pipe |
-    //    pub hashes: String,
-    //     /// The peer to add, or multiple peers separated by a pipe |. Each peer is a colon-separated host:port
-    //    pub peers: String,
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 None of the supplied peers are valid
-    // 200 All other scenarios
     // Increase torrent priority
     // Requires knowing the torrent hash. You can get it from torrent list.
 
@@ -920,30 +2717,6 @@ impl Client {
     // HTTP Status Code Scenario
     // 409 Torrent queueing is not enabled
     // 200 All other scenarios
-    // Set file priority
-    // Name: filePrio
-
-    // Parameters:
-
-    // Parameter Type Description
-    //     /// The hash of the torrent
-    //    pub hash: String,
-    //     /// File ids, separated by |
-    //    pub id: String,
-    // priority number File priority to set (consult torrent contents API for possible values)
-    // id values correspond to file position inside the array returned by torrent contents API, e.g. id=0 for first file, id=1 for second file, etc.
-
-    // Since 2.8.2 it is reccomended to use index field returned by torrent contents API (since the files can be filtered and the index value may differ from the position inside the response array).
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 Priority is invalid
-    // 400 At least one file id is not a valid integer
-    // 404 Torrent hash was not found
-    // 409 Torrent metadata hasn't downloaded yet
-    // 409 At least one file id was not found
-    // 200 All other scenarios
     // Get torrent download limit
     // Requires knowing the torrent hash. You can get it from torrent list.
 
@@ -966,23 +2739,6 @@ impl Client {
     // {"8c212779b4abde7c6bc608063a0d008b7e40ce32":338944,"284b83c9c7935002391129fd97f43db5d7cc2ba0":123}
     // 8c212779b4abde7c6bc608063a0d008b7e40ce32 is the hash of the torrent and 338944 its download speed limit in bytes per second; this value will be zero if no limit is applied.
 
-    // Set torrent download limit
-    // Requires knowing the torrent hash. You can get it from torrent list.
-
-    // POST /api/v2/torrents/setDownloadLimit HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // hashes=8c212779b4abde7c6bc608063a0d008b7e40ce32|284b83c9c7935002391129fd97f43db5d7cc2ba0&limit=131072
-    // hashes can contain multiple hashes separated by | or set to all limit is the download speed limit in bytes per second you want to set.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
     // Set torrent share limit
     // Requires knowing the torrent hash. You can get it from torrent list.
 
@@ -1022,43 +2778,6 @@ impl Client {
     // {"8c212779b4abde7c6bc608063a0d008b7e40ce32":338944,"284b83c9c7935002391129fd97f43db5d7cc2ba0":123}
     // 8c212779b4abde7c6bc608063a0d008b7e40ce32 is the hash of the torrent in the request and 338944 its upload speed limit in bytes per second; this value will be zero if no limit is applied.
 
-    // Set torrent upload limit
-    // Requires knowing the torrent hash. You can get it from torrent list.
-
-    // POST /api/v2/torrents/setUploadLimit HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // hashes=8c212779b4abde7c6bc608063a0d008b7e40ce32|284b83c9c7935002391129fd97f43db5d7cc2ba0&limit=131072
-    // hashes can contain multiple hashes separated by | or set to all limit is the upload speed limit in bytes per second you want to set.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
-    // Set torrent location
-    // Requires knowing the torrent hash. You can get it from torrent list.
-
-    // POST /api/v2/torrents/setLocation HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // hashes=8c212779b4abde7c6bc608063a0d008b7e40ce32|284b83c9c7935002391129fd97f43db5d7cc2ba0&location=/mnt/nfs/media
-    // hashes can contain multiple hashes separated by | or set to all location is the location to download the torrent to. If the location doesn't exist, the torrent's location is unchanged.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 Save path is empty
-    // 403 User does not have write access to directory
-    // 409 Unable to create save path directory
-    // 200 All other scenarios
     // Set torrent name
     // Requires knowing the torrent hash. You can get it from torrent list.
 
@@ -1076,115 +2795,6 @@ impl Client {
     // 404 Torrent hash is invalid
     // 409 Torrent name is empty
     // 200 All other scenarios
-    // Set torrent category
-    // Requires knowing the torrent hash. You can get it from torrent list.
-
-    // POST /api/v2/torrents/setCategory HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // hashes=8c212779b4abde7c6bc608063a0d008b7e40ce32|284b83c9c7935002391129fd97f43db5d7cc2ba0&category=CategoryName
-    // hashes can contain multiple hashes separated by | or set to all
-
-    // category is the torrent category you want to set.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 409 Category name does not exist
-    // 200 All other scenarios
-    // Get all categories
-    // Name: categories
-
-    // Parameters:
-
-    // None
-
-    // Returns all categories in JSON format, e.g.:
-
-    // {
-    //     "Video": {
-    //         "name": "Video",
-    //         "savePath": "/home/user/torrents/video/"
-    //     },
-    //     "eBooks": {
-    //         "name": "eBooks",
-    //         "savePath": "/home/user/torrents/eBooks/"
-    //     }
-    // }
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
-    // Add new category
-    // POST /api/v2/torrents/createCategory HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // category=CategoryName&savePath=/path/to/dir
-    // category is the category you want to create.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 Category name is empty
-    // 409 Category name is invalid
-    // 200 All other scenarios
-    // Edit category
-    // POST /api/v2/torrents/editCategory HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // category=CategoryName&savePath=/path/to/save/torrents/to
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 Category name is empty
-    // 409 Category editing failed
-    // 200 All other scenarios
-    // Remove categories
-    // POST /api/v2/torrents/removeCategories HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // categories=Category1%0ACategory2
-    // categories can contain multiple cateogies separated by \n (%0A urlencoded)
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
-    // Add torrent tags
-    // Requires knowing the torrent hash. You can get it from torrent list.
-
-    // POST /api/v2/torrents/addTags HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // hashes=8c212779b4abde7c6bc608063a0d008b7e40ce32|284b83c9c7935002391129fd97f43db5d7cc2ba0&tags=TagName1,TagName2
-    // hashes can contain multiple hashes separated by | or set to all
-
-    // tags is the list of tags you want to add to passed torrents.
-
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
     // Remove torrent tags
     // Requires knowing the torrent hash. You can get it from torrent list.
 
@@ -1202,38 +2812,6 @@ impl Client {
 
     // Returns:
 
-    // HTTP Status Code Scenario
-    // 200 All scenarios
-    // Get all tags
-    // Name: tags
-
-    // Parameters:
-
-    // None
-
-    // Returns all tags in JSON format, e.g.:
-
-    // [
-    //     "Tag 1",
-    //     "Tag 2"
-    // ]
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 200 All scenarios
-    // Create tags
-    // POST /api/v2/torrents/createTags HTTP/1.1
-    // User-Agent: Fiddler
-    // Host: 127.0.0.1
-    // Cookie: SID=your_sid
-    // Content-Type: application/x-www-form-urlencoded
-    // Content-Length: length
-
-    // tags=TagName1,TagName2
-    // tags is a list of tags you want to create. Can contain multiple tags separated by ,.
-
-    // Returns:
-
     // HTTP Status Code Scenario
     // 200 All scenarios
     // Delete tags
@@ -1336,24 +2914,6 @@ impl Client {
 
     // HTTP Status Code Scenario
     // 200 All scenarios
-    // Rename file
-    // Name: renameFile
-
-    // Parameters:
-
-    // Parameter Type Description
-    //     /// The hash of the torrent
-    //    pub hash: String,
-    //     /// The old path of the torrent
-    //    pub oldPath: String,
-    //     /// The new path to use for the file
-    //    pub newPath: String,
-    // Returns:
-
-    // HTTP Status Code Scenario
-    // 400 Missing newPath parameter
-    // 409 Invalid newPath or oldPath, or newPath already in use
-    // 200 All other scenarios
     // Rename folder
     // Name: renameFolder
 
@@ -1,52 +1,137 @@
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
+    enums::{int_enum, str_enum},
+    form::encode,
+    lenient,
     request::{ApiRequest, Arguments, Method},
-    response::check_default_status,
+    response::{decode, response_body},
+    torrents::api_version_at_least,
     Client, Error,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TransferInfo {
     /// Global download rate (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_info_speed: i64,
     /// Data downloaded this session (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_info_data: i64,
     /// Global upload rate (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_info_speed: i64,
     /// Data uploaded this session (bytes)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_info_data: i64,
     /// Download rate limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub dl_rate_limit: i64,
     /// Upload rate limit (bytes/s)
+    #[serde(deserialize_with = "lenient::i64")]
     pub up_rate_limit: i64,
     /// DHT nodes connected to
+    #[serde(deserialize_with = "lenient::i64")]
     pub dht_nodes: i64,
-    /// Connection status. See possible values here below
+    /// Connection status, Unknown if not reported
+    #[serde(default)]
     pub connection_status: ConnectionStatus,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum ConnectionStatus {
-    Connected,
-    Firewalled,
-    Disconnected,
+impl TransferInfo {
+    /// Share ratio of the data transferred this session, None if nothing was downloaded
+    pub fn session_ratio(&self) -> Option<f64> {
+        share_ratio(self.up_info_data, self.dl_info_data)
+    }
+}
+
+/// Uploaded data divided by downloaded data, None if nothing was downloaded
+pub(crate) fn share_ratio(uploaded: i64, downloaded: i64) -> Option<f64> {
+    (downloaded > 0).then(|| uploaded as f64 / downloaded as f64)
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr)]
-#[repr(u8)]
-pub enum AltSpeedState {
-    Disabled = 0,
-    Enabled = 1,
+str_enum! {
+    /// Connection status of the server, Other for the values of builds not known by this crate
+    #[derive(Default)]
+    pub enum ConnectionStatus {
+        Connected = "connected",
+        Firewalled = "firewalled",
+        Disconnected = "disconnected",
+        /// Network interface down, reported by some builds
+        Offline = "offline",
+        /// Status not reported by the server
+        #[default]
+        Unknown = "unknown",
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+int_enum! {
+    pub enum AltSpeedState {
+        Disabled = 0,
+        Enabled = 1,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Limit {
     limit: i64,
 }
 
+/// Peer address used by banPeers and addPeers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerAddr {
+    /// Peer host name or IP
+    pub host: String,
+    /// Peer port
+    pub port: u16,
+}
+
+impl PeerAddr {
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self {
+            host: addr.ip().to_string(),
+            port: addr.port(),
+        }
+    }
+}
+
+impl From<(IpAddr, u16)> for PeerAddr {
+    fn from((ip, port): (IpAddr, u16)) -> Self {
+        Self {
+            host: ip.to_string(),
+            port,
+        }
+    }
+}
+
+impl fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Join peers into the pipe separated, form encoded `peers` value
+pub(crate) fn join_peers(peers: &[PeerAddr]) -> String {
+    let peers: Vec<String> = peers.iter().map(PeerAddr::to_string).collect();
+    encode(&peers.join("|"))
+}
+
 impl Client {
     /// Get global transfer info
     /// This method returns info you usually see in qBt status bar.
@@ -70,10 +155,7 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
     }
 
     /// Get alternative speed limits state
@@ -96,10 +178,7 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
-            &response,
-            serde_json::from_reader(response.body().as_ref())?,
-        )
+        self.check_default_status(&response, decode(request.method, &response)?)
     }
 
     /// Toggle alternative speed limits
@@ -120,7 +199,40 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        self.check_default_status(&response, ())
+    }
+
+    /// Enable or disable alternative speed limits
+    /// Name: setSpeedLimitsMode
+    ///
+    /// Parameters:
+    ///
+    /// Parameter Type Description
+    /// mode integer 1 to enable alternative speed limits, 0 to disable them
+    ///
+    /// Returns:
+    ///
+    /// HTTP Status Code Scenario
+    /// 200 All scenarios
+    ///
+    /// The endpoint exists since Web API v2.11. Older servers get the current state and are
+    /// toggled only if it differs, which can race with other clients toggling it.
+    ///
+    pub async fn set_alt_speed_enabled(&mut self, enabled: bool) -> Result<(), Error> {
+        let version = self.get_api_version().await?;
+        if !api_version_at_least(&version, &[2, 11]) {
+            let current = self.get_alt_speed_state().await? == AltSpeedState::Enabled;
+            if current != enabled {
+                self.toggle_alt_speed().await?;
+            }
+            return Ok(());
+        }
+        let request = ApiRequest {
+            method: Method::SetSpeedLimitsMode,
+            arguments: Some(Arguments::Form(format!("mode={}", u8::from(enabled)))),
+        };
+        let response = self.send_request(&request).await?;
+        self.check_default_status(&response, ())
     }
 
     /// Get global download limit
@@ -142,9 +254,9 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
+        self.check_default_status(
             &response,
-            String::from_utf8(response.body().to_vec())?.parse()?,
+            String::from_utf8(response_body(&response)?.to_vec())?.parse()?,
         )
     }
 
@@ -166,7 +278,7 @@ impl Client {
             arguments: Some(Arguments::Form(format!("limit={limit}"))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        self.check_default_status(&response, ())
     }
 
     /// Get global upload limit
@@ -188,9 +300,9 @@ impl Client {
             arguments: None,
         };
         let response = self.send_request(&request).await?;
-        check_default_status(
+        self.check_default_status(
             &response,
-            String::from_utf8(response.body().to_vec())?.parse()?,
+            String::from_utf8(response_body(&response)?.to_vec())?.parse()?,
         )
     }
 
@@ -211,7 +323,7 @@ impl Client {
             arguments: Some(Arguments::Form(format!("limit={limit}"))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, ())
+        self.check_default_status(&response, ())
     }
 
     /// Ban peers
@@ -226,12 +338,12 @@ impl Client {
     /// HTTP Status Code Scenario
     /// 200 All scenarios
     ///
-    pub async fn ban_peers(&mut self, peers: &str) -> Result<String, Error> {
+    pub async fn ban_peers(&mut self, peers: &[PeerAddr]) -> Result<(), Error> {
         let request = ApiRequest {
             method: Method::BanPeers,
-            arguments: Some(Arguments::Form(format!("peers={peers}"))),
+            arguments: Some(Arguments::Form(format!("peers={}", join_peers(peers)))),
         };
         let response = self.send_request(&request).await?;
-        check_default_status(&response, String::from_utf8(response.body().to_vec())?)
+        self.check_default_status(&response, ())
     }
 }
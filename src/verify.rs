@@ -0,0 +1,202 @@
+// Verify
+//
+// Check the data of a torrent on a locally mounted directory against its file list, and
+// optionally its piece hashes, without a recheck by qBittorrent.
+
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+
+use crate::{
+    meta::hex,
+    torrents::{File, Priority},
+    Client, Error,
+};
+
+/// Size of the reads of verify_pieces
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// State of a file found by verify_sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    /// The file has the expected size
+    Ok,
+    /// The file doesn't exist or isn't a regular file
+    Missing,
+    /// The file has another size (bytes)
+    SizeMismatch { expected: i64, actual: i64 },
+    /// The file is not selected for download and was not checked
+    Skipped,
+}
+
+/// File checked by verify_sizes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCheck {
+    /// Name of the file in the torrent (including relative path)
+    pub name: String,
+    /// Local path of the file
+    pub path: PathBuf,
+    /// State of the file
+    pub status: FileStatus,
+}
+
+/// Result of Client::verify_local
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Files of the torrent
+    pub files: Vec<FileCheck>,
+    /// Indexes of the pieces whose data is missing or doesn't match their hash, None if the
+    /// pieces were not checked
+    pub bad_pieces: Option<Vec<usize>>,
+}
+
+impl VerifyReport {
+    /// Whether all the checked files and pieces are correct
+    pub fn is_ok(&self) -> bool {
+        self.files
+            .iter()
+            .all(|file| matches!(file.status, FileStatus::Ok | FileStatus::Skipped))
+            && self
+                .bad_pieces
+                .as_ref()
+                .is_none_or(|pieces| pieces.is_empty())
+    }
+}
+
+/// Compare the sizes of the files under data_dir, the local directory of the save path, with
+/// the file list of the torrent
+pub async fn verify_sizes(files: &[File], data_dir: impl AsRef<Path>) -> Vec<FileCheck> {
+    let mut checks = Vec::with_capacity(files.len());
+    for file in files {
+        let path = data_dir.as_ref().join(&file.name);
+        let status = if Priority::from(file.priority) == Priority::Skip {
+            FileStatus::Skipped
+        } else {
+            match tokio::fs::metadata(&path).await {
+                Ok(metadata) if metadata.is_file() => match metadata.len() as i64 {
+                    actual if actual == file.size => FileStatus::Ok,
+                    actual => FileStatus::SizeMismatch {
+                        expected: file.size,
+                        actual,
+                    },
+                },
+                _ => FileStatus::Missing,
+            }
+        };
+        checks.push(FileCheck {
+            name: file.name.clone(),
+            path,
+            status,
+        });
+    }
+    checks
+}
+
+/// Hash the data of the files under data_dir piece by piece and return the indexes of the
+/// pieces not matching hashes, as returned by get_piece_hashes
+///
+/// The files must be in torrent order, as returned by get_torrent_contents. Missing or short
+/// files fail the pieces they overlap. Only SHA1 pieces of v1 torrents without padding files
+/// can be checked, otherwise returns Error::UnsupportedPieceHashes. Reads the whole data
+/// synchronously, call it from a blocking task.
+pub fn verify_pieces(
+    files: &[File],
+    data_dir: &Path,
+    piece_size: i64,
+    hashes: &[String],
+) -> Result<Vec<usize>, Error> {
+    let total_size: i64 = files.iter().map(|file| file.size).sum();
+    if piece_size <= 0 {
+        return Err(Error::UnsupportedPieceHashes);
+    }
+    let piece_count = (total_size as u64).div_ceil(piece_size as u64);
+    if hashes.len() as u64 != piece_count || hashes.iter().any(|hash| hash.len() != 40) {
+        return Err(Error::UnsupportedPieceHashes);
+    }
+
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut bad_pieces = Vec::new();
+    let mut piece = 0;
+    let mut filled = 0;
+    let mut failed = false;
+    for file in files {
+        let mut reader = fs::File::open(data_dir.join(&file.name))
+            .ok()
+            .map(BufReader::new);
+        let mut remaining = file.size;
+        while remaining > 0 {
+            let len = remaining
+                .min(piece_size - filled)
+                .min(READ_BUFFER_SIZE as i64) as usize;
+            match reader
+                .as_mut()
+                .map(|reader| reader.read_exact(&mut buffer[..len]))
+            {
+                Some(Ok(())) => hasher.update(&buffer[..len]),
+                _ => {
+                    reader = None;
+                    failed = true;
+                }
+            }
+            remaining -= len as i64;
+            filled += len as i64;
+            if filled == piece_size {
+                if failed || !hash_matches(&mut hasher, &hashes[piece]) {
+                    bad_pieces.push(piece);
+                }
+                piece += 1;
+                filled = 0;
+                failed = false;
+            }
+        }
+    }
+    // The last piece is shorter than piece_size
+    if filled > 0 && (failed || !hash_matches(&mut hasher, &hashes[piece])) {
+        bad_pieces.push(piece);
+    }
+    Ok(bad_pieces)
+}
+
+/// Whether the data hashed so far matches hash, resetting hasher
+fn hash_matches(hasher: &mut Sha1, hash: &str) -> bool {
+    hex(&hasher.finalize_reset()).eq_ignore_ascii_case(hash)
+}
+
+impl Client {
+    /// Verify the data of the torrent under data_dir, the local directory of its save path
+    ///
+    /// Checks the sizes of the files, and the piece hashes if check_pieces is set (see
+    /// verify_pieces). Nothing is changed in qBittorrent, a bad torrent can be rechecked with
+    /// recheck_torrent.
+    ///
+    pub async fn verify_local(
+        &mut self,
+        hash: &str,
+        data_dir: impl AsRef<Path>,
+        check_pieces: bool,
+    ) -> Result<VerifyReport, Error> {
+        let files = self.get_torrent_contents(hash, "").await?;
+        let data_dir = data_dir.as_ref().to_path_buf();
+        let checks = verify_sizes(&files, &data_dir).await;
+        let bad_pieces = if check_pieces {
+            let properties = self
+                .get_torrent_properties(hash)
+                .await?
+                .ok_or(Error::NoTorrentHash)?;
+            let hashes = self.get_piece_hashes(hash).await?;
+            let pieces = tokio::task::spawn_blocking(move || {
+                verify_pieces(&files, &data_dir, properties.piece_size, &hashes)
+            });
+            Some(pieces.await.map_err(std::io::Error::from)??)
+        } else {
+            None
+        };
+        Ok(VerifyReport {
+            files: checks,
+            bad_pieces,
+        })
+    }
+}
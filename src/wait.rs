@@ -0,0 +1,287 @@
+// Wait
+//
+// Wait for a torrent to reach a state, polling sync/maindata, and for qBittorrent to stop or to
+// be back after a restart, polling app/version.
+
+use std::{io::ErrorKind, time::Duration};
+
+use tokio::time::{sleep, timeout};
+
+use crate::{
+    request::{ApiRequest, Method},
+    retry::is_transient,
+    sync::SyncSession,
+    torrents::{torrent_id, AddTorrent, State, Torrent},
+    Client, Error,
+};
+
+/// Interval between the polls of Client::download
+const DOWNLOAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval between the polls of Client::shutdown_and_wait and Client::wait_until_up
+const SERVER_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Snapshot of a download reported by Client::download
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Torrent hash
+    pub hash: String,
+    /// Torrent name
+    pub name: String,
+    /// Torrent state
+    pub state: String,
+    /// Torrent progress (percentage/100)
+    pub progress: f64,
+    /// Data completed (bytes)
+    pub completed: i64,
+    /// Total size (bytes) of the files selected for download
+    pub size: i64,
+    /// Download speed (bytes/s)
+    pub dlspeed: i64,
+    /// Upload speed (bytes/s)
+    pub upspeed: i64,
+    /// ETA (seconds)
+    pub eta: i64,
+}
+
+impl From<&Torrent> for Progress {
+    fn from(torrent: &Torrent) -> Self {
+        Self {
+            hash: torrent.id().unwrap_or_default().to_string(),
+            name: torrent.name.clone(),
+            state: torrent.state.clone(),
+            progress: torrent.progress,
+            completed: torrent.completed,
+            size: torrent.size,
+            dlspeed: torrent.dlspeed,
+            upspeed: torrent.upspeed,
+            eta: torrent.eta,
+        }
+    }
+}
+
+impl Client {
+    /// Poll until the torrent matches predicate, returning the matching snapshot
+    ///
+    /// Polls sync/maindata every poll_interval, so only the changes are transferred after the
+    /// first poll. Returns Error::NoTorrentHash if the torrent doesn't exist or is removed, and
+    /// Error::WaitTimeout if it doesn't match within max_wait.
+    ///
+    pub async fn wait_for_state<F>(
+        &mut self,
+        hash: &str,
+        mut predicate: F,
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Torrent, Error>
+    where
+        F: FnMut(&Torrent) -> bool,
+    {
+        let hash = torrent_id(hash);
+        let mut session = SyncSession::new();
+        let wait = async {
+            loop {
+                self.sync(&mut session).await?;
+                let torrent = session.torrent(hash)?.ok_or(Error::NoTorrentHash)?;
+                if predicate(&torrent) {
+                    return Ok(torrent);
+                }
+                sleep(poll_interval).await;
+            }
+        };
+        timeout(max_wait, wait)
+            .await
+            .map_err(|_| Error::WaitTimeout)?
+    }
+
+    /// Wait until the torrent has finished downloading
+    pub async fn wait_for_completion(
+        &mut self,
+        hash: &str,
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Torrent, Error> {
+        self.wait_for_state(
+            hash,
+            |torrent| torrent.progress >= 1.0,
+            poll_interval,
+            max_wait,
+        )
+        .await
+    }
+
+    /// Wait until the metadata of the torrent is received, e.g. after adding a magnet link
+    pub async fn wait_for_metadata(
+        &mut self,
+        hash: &str,
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Torrent, Error> {
+        self.wait_for_state(hash, has_metadata, poll_interval, max_wait)
+            .await
+    }
+
+    /// Add a torrent and wait until it has finished downloading, returning the completed
+    /// torrent
+    ///
+    /// Waits for the metadata first, then calls on_progress with a snapshot every second
+    /// until the download is complete. Returns Error::DownloadFailed if the torrent ends in the
+    /// error or missingFiles state or is stopped before completion, e.g. added paused, and
+    /// Error::WaitTimeout if it isn't complete within max_wait.
+    ///
+    pub async fn download(
+        &mut self,
+        add: AddTorrent,
+        max_wait: Duration,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<Torrent, Error> {
+        let hash = self.add_torrent(add).await?.hash;
+        let download = async {
+            self.wait_for_state(
+                &hash,
+                |torrent| has_metadata(torrent) || is_failed(torrent),
+                DOWNLOAD_POLL_INTERVAL,
+                Duration::MAX,
+            )
+            .await?;
+            self.wait_for_state(
+                &hash,
+                |torrent| {
+                    on_progress(Progress::from(torrent));
+                    torrent.progress >= 1.0 || is_failed(torrent)
+                },
+                DOWNLOAD_POLL_INTERVAL,
+                Duration::MAX,
+            )
+            .await
+        };
+        let torrent = timeout(max_wait, download)
+            .await
+            .map_err(|_| Error::WaitTimeout)??;
+        if torrent.progress < 1.0 {
+            return Err(Error::DownloadFailed(torrent.state));
+        }
+        Ok(torrent)
+    }
+
+    /// Wait until the torrent is not being checked
+    ///
+    /// qBittorrent may take a moment to start checking after a recheck request, so this can
+    /// return before the check has started.
+    ///
+    pub async fn wait_for_checked(
+        &mut self,
+        hash: &str,
+        poll_interval: Duration,
+        max_wait: Duration,
+    ) -> Result<Torrent, Error> {
+        self.wait_for_state(
+            hash,
+            |torrent| !torrent.state().is_checking(),
+            poll_interval,
+            max_wait,
+        )
+        .await
+    }
+
+    /// Shut qBittorrent down and wait until it refuses connections
+    ///
+    /// Returns Error::WaitTimeout if the server still answers after max_wait. Only a refused or
+    /// dropped connection counts as down, other failures such as TLS or name resolution errors
+    /// are polled again until max_wait.
+    ///
+    pub async fn shutdown_and_wait(&mut self, max_wait: Duration) -> Result<(), Error> {
+        self.shutdown().await?;
+        let wait = async {
+            loop {
+                match self.ping().await {
+                    Err(err) if is_connection_refused(&err) => return Ok(()),
+                    Err(err) if matches!(err.root(), Error::Cancelled) => return Err(err),
+                    _ => sleep(SERVER_POLL_INTERVAL).await,
+                }
+            }
+        };
+        timeout(max_wait, wait)
+            .await
+            .map_err(|_| Error::WaitTimeout)?
+    }
+
+    /// Wait until qBittorrent answers again, e.g. after a restart
+    ///
+    /// Connection failures and 5xx answers, e.g. from a proxy in front of the restarting server,
+    /// are retried until max_wait, after which Error::WaitTimeout is returned. A session
+    /// forgotten by the restarted server is renewed with the saved credentials, and the response
+    /// cache is cleared.
+    ///
+    pub async fn wait_until_up(&mut self, max_wait: Duration) -> Result<(), Error> {
+        let wait = async {
+            loop {
+                match self.ping().await {
+                    Ok(true) => return Ok(()),
+                    Ok(false) => sleep(SERVER_POLL_INTERVAL).await,
+                    Err(err) if matches!(err.root(), Error::NotAuth) => {
+                        return if self.relogin().await? {
+                            Ok(())
+                        } else {
+                            Err(err)
+                        };
+                    }
+                    Err(err) if is_transient(&err) => sleep(SERVER_POLL_INTERVAL).await,
+                    Err(err) => return Err(err),
+                }
+            }
+        };
+        timeout(max_wait, wait)
+            .await
+            .map_err(|_| Error::WaitTimeout)??;
+        self.clear_cache();
+        Ok(())
+    }
+
+    /// Request app/version, bypassing the response cache
+    ///
+    /// Ok(false) if the server answers with a 5xx status, i.e. is not ready yet.
+    async fn ping(&mut self) -> Result<bool, Error> {
+        let request = ApiRequest {
+            method: Method::Version,
+            arguments: None,
+        };
+        let response = self.send_request(&request).await?;
+        match response.status_code().as_u16() {
+            200 => Ok(true),
+            403 => Err(self.in_context(Error::NotAuth)),
+            500..=599 => Ok(false),
+            _ => Err(self.status_error(&response)),
+        }
+    }
+}
+
+/// Whether err is a refused or dropped connection, looked up in the source chain
+fn is_connection_refused(err: &Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err.root());
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+            );
+        }
+        source = err.source();
+    }
+    false
+}
+
+fn is_failed(torrent: &Torrent) -> bool {
+    let state = torrent.state();
+    state.is_errored() || matches!(state, State::PausedDL | State::StoppedDL)
+}
+
+/// has_metadata since qBittorrent 5.0, otherwise not fetching metadata and with a known size
+fn has_metadata(torrent: &Torrent) -> bool {
+    torrent
+        .has_metadata
+        .unwrap_or(!torrent.state().is_fetching_metadata() && torrent.total_size > 0)
+}
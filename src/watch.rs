@@ -0,0 +1,56 @@
+// Watch
+//
+// Watched folders of the scan_dirs preference. qBittorrent replaces the whole map when it is
+// set, so changes read the current folders and send them back with the change applied.
+
+use std::collections::HashMap;
+
+use crate::{
+    app::{Preferences, ScanDir},
+    Client, Error,
+};
+
+impl Client {
+    /// Watched folders by path, with where their torrents are downloaded
+    pub async fn list_watch_folders(&mut self) -> Result<HashMap<String, ScanDir>, Error> {
+        Ok(self
+            .get_preferences_uncached()
+            .await?
+            .scan_dirs
+            .unwrap_or_default())
+    }
+
+    /// Watch the folder at path on the server, downloading its torrents to target
+    ///
+    /// The other watched folders are kept. If path is already watched, its target is replaced.
+    ///
+    pub async fn add_watch_folder(&mut self, path: &str, target: ScanDir) -> Result<(), Error> {
+        let mut folders = self.list_watch_folders().await?;
+        if folders.get(path) == Some(&target) {
+            return Ok(());
+        }
+        folders.insert(path.to_string(), target);
+        self.set_watch_folders(folders).await
+    }
+
+    /// Stop watching the folder at path, keeping the other watched folders
+    ///
+    /// Returns false without changing anything if path is not watched.
+    ///
+    pub async fn remove_watch_folder(&mut self, path: &str) -> Result<bool, Error> {
+        let mut folders = self.list_watch_folders().await?;
+        if folders.remove(path).is_none() {
+            return Ok(false);
+        }
+        self.set_watch_folders(folders).await?;
+        Ok(true)
+    }
+
+    async fn set_watch_folders(&mut self, folders: HashMap<String, ScanDir>) -> Result<(), Error> {
+        self.set_preferences(Preferences {
+            scan_dirs: Some(folders),
+            ..Default::default()
+        })
+        .await
+    }
+}
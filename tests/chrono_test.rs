@@ -0,0 +1,63 @@
+use chrono::{TimeZone, Utc};
+use rqa::torrents::{Torrent, TorrentProperties};
+use serde_json::Value;
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+#[test]
+fn test_torrent_times() {
+    let mut value = fixture("torrents_info.json")[0].clone();
+    let torrent: Torrent = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(
+        torrent.added_on_time(),
+        Some(Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap())
+    );
+    assert_eq!(
+        torrent.completion_on_time(),
+        Utc.timestamp_opt(1700003600, 0).single()
+    );
+    assert_eq!(
+        torrent.seen_complete_time(),
+        Utc.timestamp_opt(1700007000, 0).single()
+    );
+    assert_eq!(
+        torrent.last_activity_time(),
+        Utc.timestamp_opt(1700007200, 0).single()
+    );
+
+    value["completion_on"] = (-1).into();
+    value["seen_complete"] = 0.into();
+    let torrent: Torrent = serde_json::from_value(value).unwrap();
+    assert_eq!(torrent.completion_on_time(), None);
+    assert_eq!(torrent.seen_complete_time(), None);
+}
+
+#[test]
+fn test_torrent_properties_times() {
+    let mut value = fixture("torrent_properties_5.0.json");
+    let properties: TorrentProperties = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(
+        properties.creation_date_time(),
+        Utc.timestamp_opt(1699990000, 0).single()
+    );
+    assert_eq!(
+        properties.addition_date_time(),
+        Utc.timestamp_opt(1700000000, 0).single()
+    );
+    assert_eq!(
+        properties.completion_date_time(),
+        Utc.timestamp_opt(1700003600, 0).single()
+    );
+    assert_eq!(
+        properties.last_seen_time(),
+        Utc.timestamp_opt(1700007000, 0).single()
+    );
+
+    value["completion_date"] = (-1).into();
+    let properties: TorrentProperties = serde_json::from_value(value).unwrap();
+    assert_eq!(properties.completion_date_time(), None);
+}
@@ -0,0 +1,232 @@
+use proptest::prelude::*;
+use rqa::{
+    form::{encode, join_hashes, join_lines, join_tags, json_to_form},
+    sync::SyncSession,
+    torrents::{AddTorrent, GetTorrentList, Torrent, TorrentFilter},
+};
+use serde_json::{json, Value};
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+fn decode(value: &str) -> String {
+    url::form_urlencoded::parse(format!("v={value}").as_bytes())
+        .next()
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_default()
+}
+
+/// JSON pointers of the scalar values of value
+fn leaves(value: &Value, pointer: String, out: &mut Vec<String>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                leaves(value, format!("{pointer}/{key}"), out);
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                leaves(value, format!("{pointer}/{i}"), out);
+            }
+        }
+        _ => out.push(pointer),
+    }
+}
+
+/// value with up to mutations of its scalar values replaced by arbitrary ones
+fn mutated(value: Value, mutations: usize) -> impl Strategy<Value = Value> {
+    let mut paths = Vec::new();
+    leaves(&value, String::new(), &mut paths);
+    let scalar = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(|n| json!(n)),
+        ".*".prop_map(Value::from),
+        Just(json!([])),
+        Just(json!({})),
+    ];
+    prop::collection::vec((prop::sample::select(paths), scalar), 1..=mutations).prop_map(
+        move |mutations| {
+            let mut value = value.clone();
+            for (path, scalar) in mutations {
+                if let Some(leaf) = value.pointer_mut(&path) {
+                    *leaf = scalar;
+                }
+            }
+            value
+        },
+    )
+}
+
+fn filter() -> impl Strategy<Value = TorrentFilter> {
+    prop::sample::select(vec![
+        TorrentFilter::All,
+        TorrentFilter::Downloading,
+        TorrentFilter::Seeding,
+        TorrentFilter::Completed,
+        TorrentFilter::Paused,
+        TorrentFilter::Active,
+    ])
+}
+
+prop_compose! {
+    fn torrent_list()(
+        filter in prop::option::of(filter()),
+        category in prop::option::of(".*"),
+        tag in prop::option::of(".*"),
+        reverse in prop::option::of(any::<bool>()),
+        limit in prop::option::of(any::<i64>()),
+        offset in prop::option::of(any::<i64>()),
+        hashes in prop::option::of(prop::collection::vec("[0-9a-f]{40}|[0-9a-f]{64}", 0..4)),
+    ) -> GetTorrentList {
+        GetTorrentList {
+            filter,
+            category,
+            tag,
+            reverse,
+            limit,
+            offset,
+            hashes: hashes.map(|hashes| {
+                join_hashes(hashes.iter().map(String::as_str).collect::<Vec<_>>())
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+prop_compose! {
+    fn add_torrent()(
+        urls in prop::collection::vec("[^\n]*", 0..4),
+        savepath in prop::option::of(".*"),
+        category in prop::option::of(".*"),
+        tags in prop::option::of(prop::collection::vec("[^,]*", 0..4)),
+        rename in prop::option::of(".*"),
+        up_limit in prop::option::of(any::<i64>()),
+        ratio_limit in prop::option::of(-1e9f64..1e9),
+        auto_t_m_m in prop::option::of(any::<bool>()),
+    ) -> AddTorrent {
+        AddTorrent {
+            urls: urls.join("\n"),
+            savepath,
+            category,
+            tags: tags.map(|tags| tags.join(",")),
+            rename,
+            up_limit,
+            ratio_limit,
+            auto_t_m_m,
+            ..Default::default()
+        }
+    }
+}
+
+/// Form parameters of a form encoded body
+fn parse_form(body: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(body.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Form parameters expected for the fields of a JSON object
+fn form_fields(value: &Value) -> Vec<(String, String)> {
+    value
+        .as_object()
+        .unwrap()
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| match value {
+            Value::String(value) => (key.clone(), value.clone()),
+            value => (key.clone(), value.to_string()),
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn test_encode_round_trip(value in ".*") {
+        let encoded = encode(&value);
+        prop_assert!(encoded
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"*-._+%".contains(&b)));
+        prop_assert_eq!(decode(&encoded), value);
+    }
+
+    #[test]
+    fn test_json_to_form_round_trip(
+        fields in prop::collection::btree_map("[a-zA-Z_]+", prop::option::of(".*"), 0..8),
+    ) {
+        let value = json!(fields);
+        let form = parse_form(&json_to_form(&value));
+        let expected: Vec<(String, String)> = fields
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect();
+        prop_assert_eq!(form, expected);
+    }
+
+    #[test]
+    fn test_join_hashes(hashes in prop::collection::vec("[0-9a-f]{40}|[0-9a-f]{64}", 1..8)) {
+        let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+        let joined = join_hashes(&hashes);
+        let ids: Vec<&str> = joined.split('|').collect();
+        prop_assert_eq!(ids.len(), hashes.len());
+        for (id, hash) in ids.iter().zip(&hashes) {
+            prop_assert_eq!(id.len(), 40);
+            prop_assert!(hash.starts_with(id));
+        }
+        prop_assert_eq!(encode(&joined), joined.replace('|', "%7C"));
+    }
+
+    #[test]
+    fn test_join_tags(tags in prop::collection::vec("[^,]+", 1..8)) {
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        let joined = decode(&join_tags(&tags));
+        prop_assert_eq!(joined.split(',').collect::<Vec<_>>(), tags);
+    }
+
+    #[test]
+    fn test_join_lines(values in prop::collection::vec("[^\n]*", 1..8)) {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        let joined = decode(&join_lines(&values));
+        prop_assert_eq!(joined.split('\n').collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_torrent_list_round_trip(args in torrent_list()) {
+        let value = serde_json::to_value(&args).unwrap();
+        let decoded: GetTorrentList = serde_json::from_value(value.clone()).unwrap();
+        prop_assert_eq!(serde_json::to_value(&decoded).unwrap(), value.clone());
+        prop_assert_eq!(parse_form(&json_to_form(&value)), form_fields(&value));
+    }
+
+    #[test]
+    fn test_add_torrent_round_trip(args in add_torrent()) {
+        let value = serde_json::to_value(&args).unwrap();
+        let decoded: AddTorrent = serde_json::from_value(value.clone()).unwrap();
+        prop_assert_eq!(serde_json::to_value(&decoded).unwrap(), value.clone());
+        prop_assert_eq!(parse_form(&json_to_form(&value)), form_fields(&value));
+        prop_assert_eq!(decoded.all_urls(), args.all_urls());
+    }
+
+    #[test]
+    fn test_fuzz_torrent(value in mutated(fixture("torrents_info.json"), 4)) {
+        let _ = serde_json::from_value::<Vec<Torrent>>(value);
+    }
+
+    #[test]
+    fn test_fuzz_maindata(
+        full in mutated(fixture("maindata_full.json"), 4),
+        delta in mutated(fixture("maindata_delta.json"), 4),
+    ) {
+        let mut session = SyncSession::new();
+        let _ = session.apply(full);
+        let _ = session.apply(delta);
+        let _ = session.torrents();
+        let _ = session.categories();
+        let _ = session.server_state();
+    }
+}
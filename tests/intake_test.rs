@@ -0,0 +1,151 @@
+use rqa::{
+    intake::{IntakeServer, IntakeSource},
+    mock::{MockQbittorrent, MockResponse},
+    torrents::AddTorrent,
+    Client,
+};
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const HASH: &str = "8c212779b4abde7c6bc608063a0d008b7e40ce32";
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+async fn post(addr: std::net::SocketAddr, path: &str, headers: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "POST {path} HTTP/1.1\r\nhost: {addr}\r\ncontent-length: {}\r\n{headers}\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[tokio::test]
+async fn test_intake_server_adds_posted_torrents() {
+    let mock = MockQbittorrent::start().await.unwrap();
+    mock.mock_login("hBc7TxF76ERhvIw0jQQ4LZ7Z1jQUV0tQ");
+    mock.mock("torrents/add", MockResponse::text("Ok."));
+    mock.mock_sequence(
+        "torrents/info",
+        vec![
+            MockResponse::json(&Value::Array(Vec::new())),
+            MockResponse::json(&fixture("torrents_info.json")),
+        ],
+    );
+    let mut client = Client::new(&mock.url()).unwrap();
+    client.login("admin", "adminadmin").await.unwrap();
+
+    let source = IntakeSource::new(AddTorrent {
+        category: Some("tv".to_string()),
+        up_limit: Some(1024),
+        ..Default::default()
+    })
+    .token("secret");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = IntakeServer::new(client).source("autobrr", source);
+    tokio::spawn(server.serve(listener));
+
+    let magnet = format!("magnet:?xt=urn:btih:{HASH}");
+    let (status, body) = post(addr, "/add/autobrr", "x-intake-token: secret\r\n", &magnet).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "added");
+    assert_eq!(body["hash"], HASH);
+    let form = mock.last_request("torrents/add").unwrap().form();
+    assert_eq!(form["urls"], magnet);
+    assert_eq!(form["category"], "tv");
+    assert_eq!(form["upLimit"], "1024");
+
+    // Now listed, so not added again
+    let form_body = format!("url={}", magnet.replace(':', "%3A").replace('?', "%3F"));
+    let (status, body) = post(
+        addr,
+        "/add/autobrr",
+        "x-intake-token: secret\r\ncontent-type: application/x-www-form-urlencoded\r\n",
+        &form_body,
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "exists");
+    let adds = mock
+        .requests()
+        .iter()
+        .filter(|request| request.path == "torrents/add")
+        .count();
+    assert_eq!(adds, 1);
+
+    let (status, _) = post(addr, "/add/autobrr", "x-intake-token: wrong\r\n", &magnet).await;
+    assert_eq!(status, 401);
+    let (status, _) = post(addr, "/add/autobrr?token=secret", "", &magnet).await;
+    assert_eq!(status, 401);
+    let (status, _) = post(addr, "/add/other", "", &magnet).await;
+    assert_eq!(status, 404);
+    let (status, _) = post(addr, "/add/autobrr", "x-intake-token: secret\r\n", "").await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_intake_server_limits_requests() {
+    let mock = MockQbittorrent::start().await.unwrap();
+    mock.mock_login("hBc7TxF76ERhvIw0jQQ4LZ7Z1jQUV0tQ");
+    mock.mock("torrents/add", MockResponse::text("Ok."));
+    mock.mock_sequence(
+        "torrents/info",
+        vec![
+            MockResponse::json(&Value::Array(Vec::new())),
+            MockResponse::json(&fixture("torrents_info.json")),
+        ],
+    );
+    let mut client = Client::new(&mock.url()).unwrap();
+    client.login("admin", "adminadmin").await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = IntakeServer::new(client)
+        .source("autobrr", IntakeSource::new(AddTorrent::default()))
+        .read_timeout(std::time::Duration::from_millis(200));
+    tokio::spawn(server.serve(listener));
+
+    // Headers over 64 KiB, sent whole so the server reads all of them before answering
+    let mut head = b"POST /add/autobrr HTTP/1.1\r\nx-padding: ".to_vec();
+    head.resize(64 * 1024 + 1, b'a');
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&head).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 413"));
+
+    // Headers never completed
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"POST /add/autobrr HTTP/1.1\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 408"));
+
+    // Bytes after content-length are not part of the body
+    let magnet = format!("magnet:?xt=urn:btih:{HASH}");
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "POST /add/autobrr HTTP/1.1\r\ncontent-length: {}\r\n\r\n{magnet}\nextra",
+        magnet.len()
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+    let form = mock.last_request("torrents/add").unwrap().form();
+    assert_eq!(form["urls"], magnet);
+}
@@ -0,0 +1,238 @@
+use rqa::{
+    hash::InfoHash,
+    magnet::Magnet,
+    meta::{magnet_id, MetaFile, TorrentMeta},
+    torrents::AddTorrent,
+    Error,
+};
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn test_parse_v1_single_file() {
+    let meta = TorrentMeta::from_bytes(&fixture("debian.torrent")).unwrap();
+    assert_eq!(meta.name, "debian.iso");
+    assert_eq!(
+        meta.info_hash_v1.as_deref(),
+        Some("0412891ed6a0c136a729d32b73fb0222c7a48a91")
+    );
+    assert_eq!(meta.info_hash_v2, None);
+    assert_eq!(meta.id(), "0412891ed6a0c136a729d32b73fb0222c7a48a91");
+    assert_eq!(meta.total_size, 3654957056);
+    assert_eq!(meta.piece_length, 262144);
+    assert_eq!(meta.comment.as_deref(), Some("Debian CD"));
+    assert!(!meta.private);
+    assert_eq!(
+        meta.magnet_uri(),
+        "magnet:?xt=urn:btih:0412891ed6a0c136a729d32b73fb0222c7a48a91&dn=debian.iso\
+         &tr=http%3A%2F%2Fbttracker.debian.org%3A6969%2Fannounce"
+    );
+
+    let add = AddTorrent::from_meta(&meta);
+    assert_eq!(add.torrents, fixture("debian.torrent"));
+}
+
+#[test]
+fn test_parse_hybrid_multi_file() {
+    let meta = TorrentMeta::from_bytes(&fixture("hybrid.torrent")).unwrap();
+    assert_eq!(
+        meta.info_hash_v1.as_deref(),
+        Some("f2790cfa2fca8602ad33870bb5fec74c3130cd79")
+    );
+    assert_eq!(
+        meta.info_hash_v2.as_deref(),
+        Some("43066a971247e45bcf9604194bd7b5a1128259b0139f1f53c81033987afb898b")
+    );
+    assert_eq!(meta.id(), "f2790cfa2fca8602ad33870bb5fec74c3130cd79");
+    assert_eq!(
+        meta.files,
+        [
+            MetaFile {
+                path: "my dir/a.txt".to_string(),
+                length: 20000
+            },
+            MetaFile {
+                path: "my dir/sub/b.txt".to_string(),
+                length: 100
+            },
+        ]
+    );
+    assert_eq!(meta.total_size, 20100);
+    assert!(meta.private);
+    assert_eq!(
+        meta.trackers,
+        [
+            "udp://tracker.example:1337",
+            "http://backup.example/announce"
+        ]
+    );
+    assert!(meta.magnet_uri().starts_with(
+        "magnet:?xt=urn:btih:f2790cfa2fca8602ad33870bb5fec74c3130cd79\
+         &xt=urn:btmh:122043066a971247e45bcf9604194bd7b5a1128259b0139f1f53c81033987afb898b\
+         &dn=my+dir&tr="
+    ));
+}
+
+#[test]
+fn test_parse_v2_only() {
+    let meta = TorrentMeta::from_bytes(&fixture("v2.torrent")).unwrap();
+    assert_eq!(meta.info_hash_v1, None);
+    assert_eq!(meta.id(), "62c297318bcbc3c03c5c7c759774ddec8c15dbc1");
+    assert_eq!(meta.files.len(), 2);
+    assert_eq!(meta.files[1].path, "my dir/sub/b.txt");
+    assert!(meta.trackers.is_empty());
+}
+
+#[test]
+fn test_parse_invalid() {
+    for data in [
+        &b""[..],
+        b"d4:infoi1ee",
+        b"d4:infod4:name1:xee",
+        b"d4:info",
+        b"99999999999999999999:x",
+        b"i1ei2e",
+    ] {
+        assert!(matches!(
+            TorrentMeta::from_bytes(data),
+            Err(Error::InvalidTorrentFile(_))
+        ));
+    }
+}
+
+#[test]
+fn test_magnet_id() {
+    let hash = "0412891ed6a0c136a729d32b73fb0222c7a48a91";
+    assert_eq!(
+        magnet_id(&format!("magnet:?xt=urn:btih:{}&dn=x", hash.to_uppercase())).as_deref(),
+        Some(hash)
+    );
+    assert_eq!(
+        magnet_id("magnet:?xt=urn:btih:AQJISHWWUDATNJZJ2MVXH6YCELD2JCUR").as_deref(),
+        Some(hash)
+    );
+    let v2 = "62c297318bcbc3c03c5c7c759774ddec8c15dbc1d9460a1133aaaf2936cb3f1e";
+    assert_eq!(
+        magnet_id(&format!("magnet:?xt=urn:btmh:1220{v2}")).as_deref(),
+        Some(&v2[..40])
+    );
+    assert_eq!(magnet_id("http://example.com/a.torrent"), None);
+    assert_eq!(magnet_id("magnet:?dn=x"), None);
+}
+
+#[test]
+fn test_info_hash() {
+    let hash = "0412891ed6a0c136a729d32b73fb0222c7a48a91";
+    let parsed: InfoHash = hash.to_uppercase().parse().unwrap();
+    assert_eq!(parsed.as_str(), hash);
+    assert_eq!(parsed.to_string(), hash);
+    assert!(!parsed.is_v2());
+    let base32: InfoHash = "AQJISHWWUDATNJZJ2MVXH6YCELD2JCUR".parse().unwrap();
+    assert_eq!(base32, parsed);
+
+    let v2 = "62c297318bcbc3c03c5c7c759774ddec8c15dbc1d9460a1133aaaf2936cb3f1e";
+    let parsed: InfoHash = v2.parse().unwrap();
+    assert!(parsed.is_v2());
+    assert_eq!(parsed.id(), &v2[..40]);
+
+    for invalid in [
+        "",
+        &hash[..39],
+        "zz12891ed6a0c136a729d32b73fb0222c7a48a91",
+        "AQJISHWW",
+    ] {
+        assert!(matches!(
+            invalid.parse::<InfoHash>(),
+            Err(Error::InvalidInfoHash(_))
+        ));
+    }
+
+    let json = serde_json::to_string(&parsed).unwrap();
+    assert_eq!(json, format!("\"{v2}\""));
+    assert_eq!(serde_json::from_str::<InfoHash>(&json).unwrap(), parsed);
+    assert!(serde_json::from_str::<InfoHash>("\"abc\"").is_err());
+}
+
+#[test]
+fn test_magnet() {
+    let hash = "0412891ed6a0c136a729d32b73fb0222c7a48a91";
+    let uri = "magnet:?xt=urn:btih:AQJISHWWUDATNJZJ2MVXH6YCELD2JCUR&dn=debian+iso\
+               &tr=udp%3A%2F%2Fa.org%3A6969&ws=http%3A%2F%2Fseed.org%2F&x.pe=1.2.3.4%3A5";
+    let mut magnet: Magnet = uri.parse().unwrap();
+    assert_eq!(magnet.info_hash().map(InfoHash::as_str), Some(hash));
+    assert_eq!(magnet.id(), Some(hash));
+    assert_eq!(magnet.name.as_deref(), Some("debian iso"));
+    assert_eq!(magnet.trackers, ["udp://a.org:6969"]);
+    assert_eq!(magnet.webseeds, ["http://seed.org/"]);
+    assert_eq!(
+        magnet.params,
+        [("x.pe".to_string(), "1.2.3.4:5".to_string())]
+    );
+
+    magnet.add_trackers(["udp://a.org:6969", "http://b.org/announce"]);
+    assert_eq!(
+        magnet.trackers,
+        ["udp://a.org:6969", "http://b.org/announce"]
+    );
+    assert_eq!(
+        magnet.to_string(),
+        format!(
+            "magnet:?xt=urn:btih:{hash}&dn=debian+iso&tr=udp%3A%2F%2Fa.org%3A6969\
+             &tr=http%3A%2F%2Fb.org%2Fannounce&ws=http%3A%2F%2Fseed.org%2F&x.pe=1.2.3.4%3A5"
+        )
+    );
+    assert_eq!(magnet.to_string().parse::<Magnet>().unwrap(), magnet);
+
+    let meta = TorrentMeta::from_bytes(&fixture("hybrid.torrent")).unwrap();
+    let magnet: Magnet = meta.magnet_uri().parse().unwrap();
+    assert_eq!(magnet, Magnet::from(&meta));
+    assert!(magnet.info_hash_v2.is_some());
+    assert_eq!(magnet.id(), Some(meta.id()));
+
+    for invalid in [
+        "http://example.com/a.torrent",
+        "magnet:?dn=x",
+        "magnet:?xt=urn:btih:1234",
+    ] {
+        assert!(matches!(
+            invalid.parse::<Magnet>(),
+            Err(Error::InvalidMagnet(_))
+        ));
+    }
+}
+
+#[test]
+fn test_add_torrent_target_id() {
+    let meta = TorrentMeta::from_bytes(&fixture("debian.torrent")).unwrap();
+    assert_eq!(
+        AddTorrent::from_meta(&meta).target_id().as_deref(),
+        Some(meta.id())
+    );
+    let add = AddTorrent {
+        urls: format!("{}\n", meta.magnet_uri()),
+        ..Default::default()
+    };
+    assert_eq!(add.target_id().as_deref(), Some(meta.id()));
+    let add = AddTorrent {
+        urls: format!("{}\nhttp://example.com/a.torrent", meta.magnet_uri()),
+        ..Default::default()
+    };
+    assert_eq!(add.target_id(), None);
+    let add = AddTorrent::from_magnets(vec![Magnet::from(&meta)]);
+    assert_eq!(add.target_id().as_deref(), Some(meta.id()));
+    let add = AddTorrent {
+        urls: "http://example.com/a.torrent".to_string(),
+        ..add
+    };
+    assert_eq!(
+        add.all_urls(),
+        [
+            "http://example.com/a.torrent".to_string(),
+            meta.magnet_uri()
+        ]
+    );
+    assert_eq!(add.target_id(), None);
+}
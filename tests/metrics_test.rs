@@ -0,0 +1,38 @@
+use rqa::{
+    metrics::Exporter,
+    mock::{MockQbittorrent, MockResponse},
+    Client,
+};
+use serde_json::Value;
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+#[tokio::test]
+async fn test_exporter_renders_prometheus_text() {
+    let mock = MockQbittorrent::start().await.unwrap();
+    mock.mock_login("hBc7TxF76ERhvIw0jQQ4LZ7Z1jQUV0tQ");
+    mock.mock(
+        "sync/maindata",
+        MockResponse::json(&fixture("maindata_full.json")),
+    );
+    mock.mock(
+        "transfer/info",
+        MockResponse::json(&fixture("transfer_info.json")),
+    );
+    let mut client = Client::new(&mock.url()).unwrap();
+    client.login("admin", "adminadmin").await.unwrap();
+
+    let mut exporter = Exporter::new(client);
+    let text = exporter.scrape().await.unwrap();
+    assert!(text.contains("# TYPE qbittorrent_dht_nodes gauge\nqbittorrent_dht_nodes 386\n"));
+    assert!(text.contains("qbittorrent_upload_limit_bytes 1048576\n"));
+    assert!(text.contains("qbittorrent_torrents{state=\"stalledUP\"} 1\n"));
+    assert!(text.contains(
+        "qbittorrent_torrent_ratio{hash=\"8c212779b4abde7c6bc608063a0d008b7e40ce32\",name=\"debian.iso\",category=\"linux\"} 0.52\n"
+    ));
+    assert!(text.contains("qbittorrent_alltime_uploaded_bytes_total 48000000000\n"));
+}
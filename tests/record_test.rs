@@ -0,0 +1,138 @@
+use rqa::{
+    app::Preferences,
+    mock::{MockQbittorrent, MockResponse},
+    record::{load, Recorder},
+    torrents::GetTorrentList,
+    Client,
+};
+use serde_json::Value;
+
+fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let data = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    let dir = std::env::temp_dir().join(format!("rqa-record-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mock = MockQbittorrent::start().await.unwrap();
+    mock.mock_login("hBc7TxF76ERhvIw0jQQ4LZ7Z1jQUV0tQ");
+    mock.mock_sequence(
+        "app/version",
+        vec![MockResponse::text("v4.6.2"), MockResponse::text("v4.6.3")],
+    );
+    mock.mock(
+        "torrents/info",
+        MockResponse::json(&fixture("torrents_info.json")),
+    );
+    let mut client = Client::builder(&mock.url())
+        .middleware(Recorder::new(&dir).unwrap())
+        .build()
+        .unwrap();
+    client.login("admin", "adminadmin").await.unwrap();
+    let versions = (
+        client.get_version().await.unwrap(),
+        client.get_version().await.unwrap(),
+    );
+    let torrents = client
+        .get_torrent_list(GetTorrentList::default())
+        .await
+        .unwrap();
+    assert!(client.get_api_version().await.is_err());
+
+    let exchanges = load(&dir).unwrap();
+    let paths: Vec<&str> = exchanges
+        .iter()
+        .map(|exchange| exchange.path.as_str())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "auth/login",
+            "app/version",
+            "app/version",
+            "torrents/info",
+            "app/webapiVersion"
+        ]
+    );
+    assert_eq!(exchanges[0].form["username"], "admin");
+    assert_eq!(exchanges[0].form["password"], "***");
+    assert!(exchanges[0]
+        .headers
+        .contains(&("set-cookie".to_string(), "SID=recorded; path=/".to_string())));
+    assert_eq!(exchanges[4].status, 404);
+
+    let replay = MockQbittorrent::replay(&dir).await.unwrap();
+    let mut client = Client::new(&replay.url()).unwrap();
+    client.login("admin", "anything").await.unwrap();
+    assert_eq!(
+        (
+            client.get_version().await.unwrap(),
+            client.get_version().await.unwrap()
+        ),
+        versions
+    );
+    let replayed = client
+        .get_torrent_list(GetTorrentList::default())
+        .await
+        .unwrap();
+    assert_eq!(replayed.len(), torrents.len());
+    assert_eq!(replayed[0].hash, torrents[0].hash);
+    assert!(client.get_api_version().await.is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_record_sanitizes_json_secrets() {
+    let dir = std::env::temp_dir().join(format!("rqa-record-secrets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mock = MockQbittorrent::start().await.unwrap();
+    mock.mock(
+        "app/preferences",
+        MockResponse::json(&serde_json::json!({
+            "locale": "en",
+            "proxy_username": "proxy-user",
+            "proxy_password": "proxy-secret",
+            "mail_notification_password": "mail-secret",
+            "web_ui_username": "admin"
+        })),
+    );
+    mock.mock("app/setPreferences", MockResponse::text(""));
+    let mut client = Client::builder(&mock.url())
+        .middleware(Recorder::new(&dir).unwrap())
+        .build()
+        .unwrap();
+    let preferences = client.get_preferences().await.unwrap();
+    assert_eq!(preferences.proxy_password.as_deref(), Some("proxy-secret"));
+    client
+        .set_preferences(Preferences {
+            web_ui_password: Some("new-secret".to_string()),
+            dyndns_username: Some("dyndns-user".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let exchanges = load(&dir).unwrap();
+    let recorded = serde_json::to_string(&exchanges).unwrap();
+    for secret in [
+        "proxy-user",
+        "proxy-secret",
+        "mail-secret",
+        "new-secret",
+        "dyndns-user",
+    ] {
+        assert!(!recorded.contains(secret), "{secret} recorded");
+    }
+    let body: Value = serde_json::from_str(&exchanges[0].body).unwrap();
+    assert_eq!(body["proxy_password"], "***");
+    assert_eq!(body["locale"], "en");
+    assert_eq!(body["web_ui_username"], "admin");
+    let json: Value = serde_json::from_str(&exchanges[1].form["json"]).unwrap();
+    assert_eq!(json["web_ui_password"], "***");
+    std::fs::remove_dir_all(&dir).unwrap();
+}